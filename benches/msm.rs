@@ -0,0 +1,49 @@
+use criterion::criterion_group;
+use criterion::{black_box, Criterion};
+use criterion::{criterion_main, BenchmarkId};
+
+use plonky::{msm_execute, msm_execute_parallel, msm_execute_with_accumulation, msm_precompute, AffinePoint, Curve, Field, MsmAccumulation, Tweedledum};
+use std::time::Duration;
+
+type C = Tweedledum;
+type SF = <C as Curve>::ScalarField;
+
+const NUM_GENERATORS: usize = 1 << 10;
+const WINDOW_SIZES: [usize; 4] = [6, 8, 10, 12];
+
+fn msm(c: &mut Criterion) {
+    let generators: Vec<AffinePoint<C>> = (0..NUM_GENERATORS).map(|_| AffinePoint::random()).collect();
+    let generators = AffinePoint::batch_to_projective(&generators);
+    let scalars: Vec<SF> = (0..NUM_GENERATORS).map(|_| SF::rand()).collect();
+
+    let mut group = c.benchmark_group(format!("msm_{}_generators", NUM_GENERATORS));
+    for &w in WINDOW_SIZES.iter() {
+        let precomputation = msm_precompute(&generators, w);
+        group.bench_with_input(BenchmarkId::new("serial", w), &w, |b, &_w| {
+            b.iter(|| msm_execute(&precomputation, black_box(&scalars)));
+        });
+        group.bench_with_input(BenchmarkId::new("serial_affine_batch", w), &w, |b, &_w| {
+            b.iter(|| {
+                msm_execute_with_accumulation(
+                    &precomputation,
+                    black_box(&scalars),
+                    MsmAccumulation::AffineBatch,
+                )
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", w), &w, |b, &_w| {
+            b.iter(|| msm_execute_parallel(&precomputation, black_box(&scalars)));
+        });
+    }
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default()
+        .sample_size(10)
+        .warm_up_time(Duration::from_secs(1))
+        .measurement_time(Duration::from_secs(1));
+    targets = msm
+);
+
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+
+use plonky::{Circuit, CircuitBuilder, Curve, Field, PartialWitness, Tweedledee, Tweedledum, Witness};
+use rayon::ThreadPoolBuilder;
+use std::time::Duration;
+
+type C = Tweedledee;
+type InnerC = Tweedledum;
+type SF = <C as Curve>::ScalarField;
+
+const DEGREE_LOG: usize = 16;
+
+/// Builds a circuit of `2^DEGREE_LOG` gates, consisting mostly of Rescue permutations.
+fn rescue_circuit() -> (Circuit<C>, Witness<SF>) {
+    let mut builder = CircuitBuilder::<C>::new(128);
+    let input = builder.add_virtual_target();
+    let mut hash = input;
+    // Leave some room for the blinding and padding gates added by build().
+    while builder.num_gates() < (1 << DEGREE_LOG) - 100 {
+        hash = builder.rescue_hash_n_to_1(&[hash]);
+    }
+    let circuit = builder.build();
+    assert_eq!(circuit.degree(), 1 << DEGREE_LOG);
+
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(input, SF::rand());
+    let witness = circuit.generate_witness(partial_witness);
+    (circuit, witness)
+}
+
+fn prove_rescue(c: &mut Criterion) {
+    let (circuit, witness) = rescue_circuit();
+    let mut group = c.benchmark_group(format!("prove_rescue_2_exp_{}", DEGREE_LOG));
+
+    group.bench_function("serial", |b| {
+        let pool = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        b.iter(|| {
+            pool.install(|| circuit.generate_proof::<InnerC>(&witness, &[], true).unwrap())
+        });
+    });
+
+    group.bench_function("parallel", |b| {
+        b.iter(|| circuit.generate_proof::<InnerC>(&witness, &[], true).unwrap());
+    });
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default()
+        .sample_size(10)
+        .warm_up_time(Duration::from_secs(1))
+        .measurement_time(Duration::from_secs(1));
+    targets = prove_rescue
+);
+
+criterion_main!(benches);
@@ -0,0 +1,31 @@
+use criterion::{black_box, Criterion};
+use criterion::criterion_group;
+use criterion::criterion_main;
+
+use plonky::{Curve, Field, Tweedledee, Tweedledum};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let p_dum = (Tweedledum::convert(<Tweedledum as Curve>::ScalarField::rand())
+        * Tweedledum::GENERATOR_PROJECTIVE).to_affine();
+    let p_dee = (Tweedledee::convert(<Tweedledee as Curve>::ScalarField::rand())
+        * Tweedledee::GENERATOR_PROJECTIVE).to_affine();
+
+    c.bench_function("Tweedledum subgroup check", move |b| b.iter(|| {
+        black_box(p_dum).is_in_subgroup()
+    }));
+
+    c.bench_function("Tweedledum generic subgroup check", move |b| b.iter(|| {
+        black_box(p_dum).is_in_subgroup_generic()
+    }));
+
+    c.bench_function("Tweedledee subgroup check", move |b| b.iter(|| {
+        black_box(p_dee).is_in_subgroup()
+    }));
+
+    c.bench_function("Tweedledee generic subgroup check", move |b| b.iter(|| {
+        black_box(p_dee).is_in_subgroup_generic()
+    }));
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
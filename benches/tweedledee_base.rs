@@ -32,6 +32,11 @@ fn criterion_benchmark(c: &mut Criterion) {
         black_box(x).multiplicative_inverse()
     }));
 
+    let xs: Vec<TweedledeeBase> = (0..1 << 10).map(|_| TweedledeeBase::rand()).collect();
+    c.bench_function("TweedledeeBase field batch inversion (2^10 elements)", move |b| b.iter(|| {
+        TweedledeeBase::batch_multiplicative_inverse(black_box(&xs))
+    }));
+
     c.bench_function("TweedledeeBase field exp", move |b| b.iter(|| {
         black_box(x).exp(black_box(y))
     }));
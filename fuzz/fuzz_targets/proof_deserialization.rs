@@ -0,0 +1,39 @@
+//! Feeds arbitrary bytes to `Proof::read_from` and `verify_from_reader`, which must reject
+//! malformed proofs with an error rather than panicking, and must never accept a point which is
+//! off the curve or outside the prime-order subgroup.
+//!
+//! The seed corpus contains a valid proof for `square_circuit` with x = 3, in the format written
+//! by `ToBytes::write`. Run with `cargo fuzz run proof_deserialization`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use once_cell::sync::Lazy;
+use plonky::{verify_from_reader, Circuit, CircuitBuilder, Curve, Field, Proof, Tweedledee, Tweedledum, VerificationKey};
+
+type C = Tweedledee;
+type InnerC = Tweedledum;
+type SF = <C as Curve>::ScalarField;
+
+/// A circuit proving knowledge of x such that x^2 is the public input.
+fn square_circuit() -> Circuit<C> {
+    let mut builder = CircuitBuilder::<C>::new(128);
+    let x = builder.add_virtual_target();
+    let pi = builder.add_public_input();
+    let x_squared = builder.square(x);
+    builder.copy(x_squared, pi);
+    builder.build()
+}
+
+static VK: Lazy<VerificationKey<C>> = Lazy::new(|| square_circuit().to_vk());
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(proof) = Proof::<C>::read_from(data) {
+        for point in proof.all_points() {
+            assert!(point.is_valid() && point.is_in_subgroup());
+        }
+    }
+
+    let public_inputs = [SF::from_canonical_u64(9)];
+    let _ = verify_from_reader::<C, InnerC, _>(data, &public_inputs, &VK);
+});
@@ -1,6 +1,3 @@
-#![allow(incomplete_features)]
-#![feature(const_generics)]
-
 mod openings;
 pub mod plookup;
 mod proof;
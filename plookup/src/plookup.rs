@@ -9,6 +9,7 @@ use plonky::plonk_util::reduce_with_powers;
 use plonky::polynomial::Polynomial;
 use plonky::util::log2_strict;
 use plonky::{blake_hash_usize_to_curve, fft_precompute, msm_precompute, AffinePoint, Field, HaloCurve};
+use rand::thread_rng;
 
 pub const SECURITY_BITS: usize = 128;
 
@@ -147,6 +148,7 @@ pub fn prove<C: HaloCurve>(f: &[C::ScalarField], t: &[C::ScalarField]) -> Result
         2 * n + 2,
         SECURITY_BITS,
         &mut challenger,
+        &mut thread_rng(),
     )?;
 
     Ok(PlookupProof::from((commitments, openings, halo_proof, n)))
@@ -0,0 +1,247 @@
+use anyhow::{bail, ensure, Result};
+use rand::thread_rng;
+
+use crate::halo::{batch_opening_proof, verify_ipa, OpeningProof};
+use crate::plonk_challenger::Challenger;
+use crate::plonk_util::{halo_g, halo_n, halo_n_mul, halo_s, pedersen_hash};
+use crate::util::log2_strict;
+use crate::verifier::pedersen_g_msm_precompute;
+use crate::{blake_hash_usize_to_curve, msm_execute_parallel, msm_precompute, verify_proof, AffinePoint, Field, HaloCurve, OldProof, PolynomialCommitment, Proof, VerificationKey};
+
+/// Aggregates proofs by folding their deferred G point checks into a single IPA opening.
+///
+/// Each absorbed proof is verified with `verify_g` set to `false`, which leaves an accumulator
+/// `(G_i, us_i)` whose remaining check, `G_i = <s(us_i), G>`, is linear in the degree. Rather than
+/// performing `n` such checks, `finalize` proves that a random combination of the `g(X, us_i)`
+/// polynomials opens correctly against the same combination of the `G_i`. The resulting
+/// `AggregateProof` can be verified with `O(n log d)` work plus a single linear time check, which
+/// can itself be deferred.
+pub struct Aggregator<C: HaloCurve> {
+    vk: VerificationKey<C>,
+    accumulators: Vec<OldProof<C>>,
+}
+
+/// A proof that the deferred G point checks of several accumulators all hold.
+#[derive(Debug, Clone)]
+pub struct AggregateProof<C: HaloCurve> {
+    pub accumulators: Vec<OldProof<C>>,
+    pub opening_proof: OpeningProof<C>,
+}
+
+impl<C: HaloCurve> Aggregator<C> {
+    pub fn new(vk: VerificationKey<C>) -> Self {
+        Aggregator {
+            vk,
+            accumulators: Vec::new(),
+        }
+    }
+
+    pub fn num_accumulators(&self) -> usize {
+        self.accumulators.len()
+    }
+
+    /// Verifies a proof, except for the linear time check of its G point, which is deferred to the
+    /// aggregate proof.
+    pub fn absorb<InnerC: HaloCurve<BaseField = C::ScalarField>>(
+        &mut self,
+        public_inputs: &[C::ScalarField],
+        proof: &Proof<C>,
+        old_proofs: &[OldProof<C>],
+    ) -> Result<()> {
+        let accumulator =
+            verify_proof::<C, InnerC>(public_inputs, proof, old_proofs, &self.vk, false)?
+                .expect("verify_proof should return an accumulator when verify_g is false");
+        self.absorb_accumulator(accumulator)
+    }
+
+    /// Absorbs an accumulator directly, such as one returned by `verify_proof` or by
+    /// `AggregateProof::verify` with `verify_g` set to `false`.
+    pub fn absorb_accumulator(&mut self, accumulator: OldProof<C>) -> Result<()> {
+        ensure!(
+            accumulator.halo_us.len() == log2_strict(self.vk.degree),
+            "Accumulator has {} challenges, expected {}",
+            accumulator.halo_us.len(),
+            log2_strict(self.vk.degree)
+        );
+        self.accumulators.push(accumulator);
+        Ok(())
+    }
+
+    /// Proves a single opening of a random combination of the absorbed accumulators' `g`
+    /// polynomials.
+    pub fn finalize(self) -> Result<AggregateProof<C>> {
+        ensure!(
+            !self.accumulators.is_empty(),
+            "Cannot aggregate zero accumulators"
+        );
+        let degree = self.vk.degree;
+        let security_bits = self.vk.security_bits;
+
+        let mut challenger = Challenger::<C::BaseField>::new(security_bits);
+        let challenges = aggregation_challenges(&mut challenger, &self.accumulators)?;
+
+        let pedersen_g: Vec<AffinePoint<C>> = (0..degree).map(blake_hash_usize_to_curve).collect();
+        let pedersen_h = blake_hash_usize_to_curve::<C>(degree);
+        let u_curve = blake_hash_usize_to_curve(degree + 1);
+
+        let all_coeffs: Vec<Vec<C::ScalarField>> =
+            self.accumulators.iter().map(|acc| acc.coeffs()).collect();
+        let commitments: Vec<PolynomialCommitment<C>> = self
+            .accumulators
+            .iter()
+            .map(|acc| acc.halo_g.into())
+            .collect();
+
+        let opening_proof = batch_opening_proof(
+            &all_coeffs.iter().map(|c| &c[..]).collect::<Vec<_>>(),
+            &commitments,
+            &[challenges.z],
+            &pedersen_g,
+            pedersen_h.to_projective(),
+            u_curve,
+            challenges.u,
+            challenges.v,
+            challenges.u_scaling,
+            degree,
+            security_bits,
+            &mut challenger,
+            &mut thread_rng(),
+        )?;
+
+        Ok(AggregateProof {
+            accumulators: self.accumulators,
+            opening_proof,
+        })
+    }
+}
+
+impl<C: HaloCurve> AggregateProof<C> {
+    /// Verifies that the deferred G point checks of all accumulators hold.
+    /// If `verify_g` is `true`, this includes the linear time check of the aggregate's own G point.
+    /// If `verify_g` is `false`, returns an `OldProof` for that check, which can be absorbed into
+    /// another `Aggregator`.
+    pub fn verify(&self, vk: &VerificationKey<C>, verify_g: bool) -> Result<Option<OldProof<C>>> {
+        let degree = vk.degree;
+        let security_bits = vk.security_bits;
+        ensure!(
+            !self.accumulators.is_empty(),
+            "Aggregate proof has no accumulators"
+        );
+        for acc in &self.accumulators {
+            ensure!(
+                acc.halo_us.len() == log2_strict(degree),
+                "Accumulator has the wrong number of challenges"
+            );
+        }
+        let opening_proof = &self.opening_proof;
+        ensure!(
+            opening_proof.halo_l.len() == log2_strict(degree)
+                && opening_proof.halo_r.len() == log2_strict(degree),
+            "Opening proof has the wrong number of L or R points"
+        );
+
+        let mut challenger = Challenger::<C::BaseField>::new(security_bits);
+        let challenges = aggregation_challenges(&mut challenger, &self.accumulators)?;
+
+        // Compute IPA challenges, as in `Proof::get_challenges`.
+        let mut halo_us = Vec::new();
+        for (&l, &r) in opening_proof.halo_l.iter().zip(&opening_proof.halo_r) {
+            challenger.observe_affine_points(&[l, r]);
+            let r_bf = challenger.get_challenge();
+            let r_sf = r_bf.try_convert::<C::ScalarField>()?;
+            let u_j_squared = halo_n::<C>(&r_sf.to_canonical_bool_vec()[..security_bits]);
+            match u_j_squared.square_root() {
+                Some(u_j) => halo_us.push(u_j),
+                None => bail!("Invalid transcript. Prover should have ensured that n(r) is square"),
+            }
+        }
+        challenger.observe_affine_point(opening_proof.schnorr_proof.r);
+        let schnorr_challenge = C::try_convert_b2s(challenger.get_challenge())?;
+
+        // Reduce the accumulators' commitments and evaluations at z, using the same scalars as
+        // `batch_opening_proof`.
+        let actual_scalars: Vec<C::ScalarField> = challenges
+            .u
+            .powers()
+            .take(self.accumulators.len())
+            .map(|u_pow| halo_n::<C>(&u_pow.to_canonical_bool_vec()[..security_bits]))
+            .collect();
+        let c_all: Vec<AffinePoint<C>> = self.accumulators.iter().map(|acc| acc.halo_g).collect();
+        let precomputation = msm_precompute(&AffinePoint::batch_to_projective(&c_all), 8);
+        let c_reduction = msm_execute_parallel(&precomputation, &actual_scalars);
+        let evaluations: Vec<C::ScalarField> = self
+            .accumulators
+            .iter()
+            .map(|acc| acc.evaluate_g(challenges.z))
+            .collect();
+        let reduced_evaluation = C::ScalarField::inner_product(&evaluations, &actual_scalars);
+
+        let pedersen_h = blake_hash_usize_to_curve(degree);
+        let u_curve = blake_hash_usize_to_curve(degree + 1);
+        let u_prime = halo_n_mul(
+            &challenges.u_scaling.to_canonical_bool_vec()[..security_bits],
+            u_curve,
+        )
+        .to_projective();
+
+        ensure!(
+            verify_ipa::<C>(
+                &opening_proof.halo_l,
+                &opening_proof.halo_r,
+                opening_proof.halo_g,
+                c_reduction,
+                reduced_evaluation,
+                halo_g(challenges.z, &halo_us),
+                &halo_us,
+                u_prime,
+                pedersen_h,
+                schnorr_challenge,
+                opening_proof.schnorr_proof,
+            ),
+            "Invalid aggregate IPA proof."
+        );
+
+        if verify_g {
+            let pedersen_g_msm_precomputation = pedersen_g_msm_precompute(degree);
+            if opening_proof.halo_g
+                == pedersen_hash(&halo_s(&halo_us), &pedersen_g_msm_precomputation).to_affine()
+            {
+                Ok(None)
+            } else {
+                bail!("Invalid G point.");
+            }
+        } else {
+            Ok(Some(OldProof {
+                halo_g: opening_proof.halo_g,
+                halo_us,
+            }))
+        }
+    }
+}
+
+struct AggregationChallenges<F: Field> {
+    z: F,
+    u: F,
+    v: F,
+    u_scaling: F,
+}
+
+/// Observes the accumulators and returns the challenges used by the aggregate opening proof. The
+/// IPA continues to use the same challenger afterwards.
+fn aggregation_challenges<C: HaloCurve>(
+    challenger: &mut Challenger<C::BaseField>,
+    accumulators: &[OldProof<C>],
+) -> Result<AggregationChallenges<C::ScalarField>> {
+    for acc in accumulators {
+        challenger.observe_affine_point(acc.halo_g);
+        challenger.observe_elements(&C::try_convert_s2b_slice(&acc.halo_us)?);
+    }
+    let z = C::try_convert_b2s(challenger.get_challenge())?;
+    let (u_bf, v_bf, u_scaling_bf) = challenger.get_3_challenges();
+    Ok(AggregationChallenges {
+        z,
+        u: C::try_convert_b2s(u_bf)?,
+        v: C::try_convert_b2s(v_bf)?,
+        u_scaling: C::try_convert_b2s(u_scaling_bf)?,
+    })
+}
@@ -0,0 +1,317 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+use anyhow::{ensure, Result};
+use rayon::prelude::*;
+
+use crate::{verify_proof, BufferGate, Circuit, CircuitBuilder, HaloCurve, PartialWitness, Proof, PublicInput, Target, VerificationKey, Wire, Witness, GRID_WIDTH, NUM_ROUTED_WIRES, NUM_WIRES};
+
+/// A class of wires of a circuit which are copy constrained to one another, identified by the
+/// `(gate, input)` of its first routed wire.
+type WireClass = (usize, usize);
+
+/// A range of gates of a `Circuit`, made into a circuit of its own by `ChunkedProver`.
+///
+/// The chunk's circuit starts with copies of the gates in `gates`. Since gates may read the wires
+/// of the following rows, either as `right_wire_values` or through `CircuitBuilder::wire_below`,
+/// these are followed by `BufferGate`s holding copies of the next `GRID_WIDTH` rows, if the
+/// circuit has that many more. Gates only read the routed wires of other rows, so only those need
+/// to be consistent with the chunk which owns the rows.
+///
+/// The chunk's public inputs are its boundary values: one for each class of copy constrained wires
+/// which the chunk shares with another chunk, or which holds a public input of the original
+/// circuit. Note that these values are revealed to the verifier.
+pub struct CircuitChunk<C: HaloCurve> {
+    /// The gates of the original circuit which this chunk proves.
+    pub gates: Range<usize>,
+    pub circuit: Circuit<C>,
+    /// The original rows copied into the chunk, i.e. `gates` followed by the context rows. Row `i`
+    /// of the chunk's circuit holds row `rows.start + i` of the original circuit.
+    rows: Range<usize>,
+    /// The wire class of each boundary value, in the order of the chunk's public inputs.
+    boundary_classes: Vec<WireClass>,
+}
+
+/// A proof of a single `CircuitChunk`, along with the boundary values it was generated for.
+#[derive(Debug, Clone)]
+pub struct ChunkProof<C: HaloCurve> {
+    pub boundary_values: Vec<C::ScalarField>,
+    pub proof: Proof<C>,
+}
+
+/// The data needed to check a set of `ChunkProof`s with `stitch`.
+pub struct ChunkedVerificationKey<C: HaloCurve> {
+    /// The verification key of each chunk, along with the wire class of each of its boundary
+    /// values.
+    chunks: Vec<(VerificationKey<C>, Vec<WireClass>)>,
+    /// The wire class holding each public input of the original circuit.
+    public_input_classes: Vec<WireClass>,
+}
+
+/// Splits a single `Circuit` into disjoint ranges of gates, and proves each range as a separate
+/// `CircuitChunk`, in parallel, which lets a large circuit be proven on several machines. The
+/// resulting proofs can be checked with `stitch`, which accepts exactly when each chunk's proof is
+/// valid and the chunks agree on each value they share, i.e. when the original circuit is
+/// satisfied.
+pub struct ChunkedProver<C: HaloCurve> {
+    chunks: Vec<CircuitChunk<C>>,
+    public_input_classes: Vec<WireClass>,
+}
+
+impl<C: HaloCurve> ChunkedProver<C> {
+    /// Splits `circuit` before each of the given gate indices, which must be increasing, so `n`
+    /// boundaries give `n + 1` chunks.
+    pub fn new(circuit: &Circuit<C>, boundaries: &[usize]) -> Result<Self> {
+        let degree = circuit.degree();
+        for (i, &boundary) in boundaries.iter().enumerate() {
+            ensure!(
+                boundary > 0 && boundary < degree,
+                "Boundary {} is outside the circuit's {} gates",
+                boundary,
+                degree
+            );
+            ensure!(i == 0 || boundary > boundaries[i - 1], "Boundaries must be increasing");
+        }
+        let starts = std::iter::once(0).chain(boundaries.iter().copied());
+        let ends = boundaries.iter().copied().chain(std::iter::once(degree));
+        let ranges: Vec<Range<usize>> = starts.zip(ends).map(|(start, end)| start..end).collect();
+        let rows: Vec<Range<usize>> = ranges
+            .iter()
+            .map(|gates| gates.start..(gates.end + GRID_WIDTH).min(degree))
+            .collect();
+
+        let classes = wire_classes(circuit);
+        let public_input_classes: Vec<WireClass> = (0..circuit.num_public_inputs)
+            .map(|i| {
+                match PublicInput::<C::ScalarField>::new(i)
+                    .routable_target(circuit.num_gates_without_pis)
+                {
+                    Target::Wire(wire) => classes[&wire],
+                    _ => unreachable!("Public inputs are routed to wires"),
+                }
+            })
+            .collect();
+
+        // Find the number of chunks containing a wire of each class, so that we know which
+        // classes each chunk shares with another.
+        let mut num_chunks_by_class: HashMap<WireClass, usize> = HashMap::new();
+        for chunk_rows in &rows {
+            let mut chunk_classes: Vec<WireClass> =
+                routed_wires(chunk_rows.clone()).map(|wire| classes[&wire]).collect();
+            chunk_classes.sort_unstable();
+            chunk_classes.dedup();
+            for class in chunk_classes {
+                *num_chunks_by_class.entry(class).or_insert(0) += 1;
+            }
+        }
+        for &class in &public_input_classes {
+            *num_chunks_by_class.entry(class).or_insert(0) += 1;
+        }
+
+        let chunks = ranges
+            .into_iter()
+            .zip(rows)
+            .map(|(gates, rows)| build_chunk(circuit, gates, rows, &classes, &num_chunks_by_class))
+            .collect();
+        Ok(ChunkedProver {
+            chunks,
+            public_input_classes,
+        })
+    }
+
+    pub fn chunks(&self) -> &[CircuitChunk<C>] {
+        &self.chunks
+    }
+
+    pub fn verification_key(&self) -> ChunkedVerificationKey<C> {
+        ChunkedVerificationKey {
+            chunks: self
+                .chunks
+                .iter()
+                .map(|chunk| (chunk.circuit.to_vk(), chunk.boundary_classes.clone()))
+                .collect(),
+            public_input_classes: self.public_input_classes.clone(),
+        }
+    }
+
+    /// Proves each chunk in parallel, given a witness for the whole circuit.
+    pub fn prove<InnerC: HaloCurve<BaseField = C::ScalarField>>(
+        &self,
+        witness: &Witness<C::ScalarField>,
+        blinding_commitments: bool,
+    ) -> Result<Vec<ChunkProof<C>>> {
+        self.chunks
+            .par_iter()
+            .map(|chunk| {
+                let mut inputs = PartialWitness::new();
+                for (gate, row) in chunk.rows.clone().enumerate() {
+                    for input in 0..NUM_WIRES {
+                        inputs.set_wire(
+                            Wire { gate, input },
+                            witness.get(Wire { gate: row, input }),
+                        );
+                    }
+                }
+                let chunk_witness = chunk.circuit.generate_witness(inputs);
+                let proof = chunk.circuit.generate_proof::<InnerC>(
+                    &chunk_witness,
+                    &[],
+                    blinding_commitments,
+                )?;
+                Ok(ChunkProof {
+                    boundary_values: chunk.circuit.get_public_inputs(&chunk_witness),
+                    proof,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Verifies the proof of each chunk of a circuit, and asserts that the boundary values are
+/// consistent, i.e. that the chunks agree on the value of each wire class they share, and that
+/// the original circuit's public inputs are `public_inputs`. This accepts exactly when a proof of
+/// the original circuit for `public_inputs` would.
+pub fn stitch<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>(
+    vk: &ChunkedVerificationKey<C>,
+    public_inputs: &[C::ScalarField],
+    proofs: &[ChunkProof<C>],
+) -> Result<()> {
+    ensure!(
+        proofs.len() == vk.chunks.len(),
+        "Expected {} proofs, got {}",
+        vk.chunks.len(),
+        proofs.len()
+    );
+    ensure!(
+        public_inputs.len() == vk.public_input_classes.len(),
+        "Expected {} public inputs, got {}",
+        vk.public_input_classes.len(),
+        public_inputs.len()
+    );
+
+    let mut class_values = BTreeMap::new();
+    for (i, ((chunk_vk, boundary_classes), chunk_proof)) in vk.chunks.iter().zip(proofs).enumerate()
+    {
+        ensure!(
+            chunk_proof.boundary_values.len() == boundary_classes.len(),
+            "Chunk {} has {} boundary values, but expected {}",
+            i,
+            chunk_proof.boundary_values.len(),
+            boundary_classes.len()
+        );
+        verify_proof::<C, InnerC>(
+            &chunk_proof.boundary_values,
+            &chunk_proof.proof,
+            &[],
+            chunk_vk,
+            true,
+        )?;
+        for (&class, &value) in boundary_classes.iter().zip(&chunk_proof.boundary_values) {
+            if let Some(other_value) = class_values.insert(class, value) {
+                ensure!(
+                    value == other_value,
+                    "Chunk {} disagrees with an earlier chunk about the wires copied from {:?}",
+                    i,
+                    class
+                );
+            }
+        }
+    }
+
+    for (i, (class, &value)) in vk.public_input_classes.iter().zip(public_inputs).enumerate() {
+        ensure!(
+            class_values.get(class) == Some(&value),
+            "Public input {} doesn't match the chunks' boundary values",
+            i
+        );
+    }
+    Ok(())
+}
+
+/// Returns the routed wires of the given rows.
+fn routed_wires(rows: Range<usize>) -> impl Iterator<Item = Wire> {
+    rows.flat_map(|gate| (0..NUM_ROUTED_WIRES).map(move |input| Wire { gate, input }))
+}
+
+/// Returns the class of each routed wire of the circuit.
+fn wire_classes<C: HaloCurve>(circuit: &Circuit<C>) -> HashMap<Wire, WireClass> {
+    let mut classes = HashMap::new();
+    for wire in routed_wires(0..circuit.degree()) {
+        if classes.contains_key(&wire) {
+            continue;
+        }
+        let partition = circuit
+            .routing_target_partitions
+            .get_partition(Target::Wire(wire));
+        let members: Vec<Wire> = partition
+            .iter()
+            .filter_map(|&target| match target {
+                Target::Wire(member) if member.input < NUM_ROUTED_WIRES => Some(member),
+                _ => None,
+            })
+            .collect();
+        let class = members
+            .iter()
+            .map(|member| (member.gate, member.input))
+            .min()
+            .unwrap();
+        for member in members {
+            classes.insert(member, class);
+        }
+    }
+    classes
+}
+
+/// Builds the circuit of the chunk which owns `gates` and copies `rows`, exposing each class it
+/// shares with another chunk, or with a public input, as a boundary value.
+fn build_chunk<C: HaloCurve>(
+    circuit: &Circuit<C>,
+    gates: Range<usize>,
+    rows: Range<usize>,
+    classes: &HashMap<Wire, WireClass>,
+    num_chunks_by_class: &HashMap<WireClass, usize>,
+) -> CircuitChunk<C> {
+    let mut builder = CircuitBuilder::<C>::new(circuit.security_bits);
+    for row in rows.clone() {
+        if gates.contains(&row) {
+            builder.add_gate_with_raw_constants(circuit.gate_constants[row].clone());
+        } else {
+            builder.add_gate_no_constants(BufferGate::new(row - rows.start));
+        }
+    }
+
+    let mut boundary_classes: Vec<WireClass> = routed_wires(rows.clone())
+        .map(|wire| classes[&wire])
+        .filter(|class| num_chunks_by_class[class] > 1)
+        .collect();
+    boundary_classes.sort_unstable();
+    boundary_classes.dedup();
+    let boundary_targets = builder.add_public_inputs(boundary_classes.len());
+
+    // Connect the chunk's wires of each class to one another, and to the class's boundary value if
+    // it has one.
+    let mut class_targets: HashMap<WireClass, Target<C::ScalarField>> = boundary_classes
+        .iter()
+        .copied()
+        .zip(boundary_targets)
+        .collect();
+    for wire in routed_wires(rows.clone()) {
+        let local_wire = Target::Wire(Wire {
+            gate: wire.gate - rows.start,
+            input: wire.input,
+        });
+        match class_targets.get(&classes[&wire]) {
+            Some(&class_target) => builder.copy(class_target, local_wire),
+            None => {
+                class_targets.insert(classes[&wire], local_wire);
+            }
+        }
+    }
+
+    CircuitChunk {
+        gates,
+        circuit: builder.build(),
+        rows,
+        boundary_classes,
+    }
+}
@@ -1,11 +1,12 @@
 #![allow(clippy::type_complexity)]
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::gates::*;
+use crate::partition::TargetUnionFind;
 use crate::plonk_util::{commit_polynomials, polynomials_to_values_padded, sigma_polynomials, values_to_polynomials};
 use crate::util::{ceil_div_usize, log2_strict, transpose};
-use crate::{blake_hash_usize_to_curve, fft_precompute, generate_rescue_constants, msm_precompute, AffinePoint, AffinePointTarget, BoundedTarget, Circuit, Curve, Field, HaloCurve, PartialWitness, PublicInput, Target, TargetPartitions, VirtualTarget, Wire, WitnessGenerator, NUM_CONSTANTS, NUM_WIRES};
+use crate::{blake_hash_usize_to_curve, fft_precompute, generate_rescue_constants_rounds, msm_precompute, recommended_rounds, AffinePoint, AffinePointTarget, BoundedTarget, Circuit, Curve, Field, HaloCurve, PartialWitness, PublicInput, SimpleGenerator, Target, TargetPartitions, VirtualTarget, Wire, WitnessGenerator, GRID_WIDTH, NUM_CONSTANTS, NUM_ROUTED_WIRES, NUM_WIRES};
 use num::{BigUint, Zero};
 
 pub struct CircuitBuilder<C: HaloCurve> {
@@ -13,10 +14,29 @@ pub struct CircuitBuilder<C: HaloCurve> {
     public_input_index: usize,
     virtual_target_index: usize,
     gate_counts: BTreeMap<&'static str, usize>,
+    max_gate_degree: usize,
     gate_constants: Vec<Vec<C::ScalarField>>,
     copy_constraints: Vec<(Target<C::ScalarField>, Target<C::ScalarField>)>,
     generators: Vec<Box<dyn WitnessGenerator<C::ScalarField>>>,
-    constant_wires: HashMap<C::ScalarField, Target<C::ScalarField>>,
+    /// Constant wires created so far, keyed on the canonical representation of their values, so
+    /// that deduplication doesn't depend on how a field stores its elements internally.
+    constant_wires: HashMap<Vec<u64>, Target<C::ScalarField>>,
+    /// Wires covered by the permutation argument which their gates declare as advice wires, i.e.
+    /// which are excluded from `Gate::routed_wires`. These must not be copy constrained.
+    unrouted_wires: HashSet<Wire>,
+    /// Targets which are known to be binary, either because they are the constants 0 or 1, or
+    /// because `assert_binary` was already called on them. `assert_binary` adds no gates for these.
+    binary_targets: HashSet<Target<C::ScalarField>>,
+    /// The number of gates which must precede the blinding and public input gates, so that each
+    /// row referenced by `wire_below` holds a regular gate. `build` pads with `BufferGate`s to
+    /// reach it.
+    min_num_gates: usize,
+    /// The label given to the most recent `debug_annotate` call, if any.
+    #[cfg(feature = "debug")]
+    current_label: Option<String>,
+    /// The label which was active when each gate was added.
+    #[cfg(feature = "debug")]
+    gate_labels: Vec<Option<String>>,
 }
 
 impl<C: HaloCurve> CircuitBuilder<C> {
@@ -26,10 +46,18 @@ impl<C: HaloCurve> CircuitBuilder<C> {
             public_input_index: 0,
             virtual_target_index: 0,
             gate_counts: BTreeMap::new(),
+            max_gate_degree: 0,
             gate_constants: Vec::new(),
             copy_constraints: Vec::new(),
             generators: Vec::new(),
             constant_wires: HashMap::new(),
+            unrouted_wires: HashSet::new(),
+            binary_targets: HashSet::new(),
+            min_num_gates: 0,
+            #[cfg(feature = "debug")]
+            current_label: None,
+            #[cfg(feature = "debug")]
+            gate_labels: Vec::new(),
         }
     }
 
@@ -43,6 +71,39 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         (0..n).map(|_i| self.add_public_input()).collect()
     }
 
+    /// Asserts that `computed` is equal to the public input with the given index, by adding a copy
+    /// constraint between them. This is the canonical way to expose a computed value as an output.
+    pub fn assert_equal_to_public_input(
+        &mut self,
+        computed: Target<C::ScalarField>,
+        public_input_index: usize,
+    ) {
+        assert!(
+            public_input_index < self.public_input_index,
+            "Public input {} has not been added; there are {} public inputs",
+            public_input_index,
+            self.public_input_index
+        );
+        self.copy(
+            computed,
+            Target::PublicInput(PublicInput::new(public_input_index)),
+        );
+    }
+
+    /// Returns the number of public inputs added so far.
+    pub fn num_public_inputs(&self) -> usize {
+        self.public_input_index
+    }
+
+    /// Computes a Rescue hash of all public inputs added so far, in order. This matches the native
+    /// `VerificationKey::hash_public_inputs`, provided that no public inputs are added afterward.
+    pub fn hash_public_inputs(&mut self) -> Target<C::ScalarField> {
+        let public_inputs: Vec<Target<C::ScalarField>> = (0..self.public_input_index)
+            .map(|index| Target::PublicInput(PublicInput::new(index)))
+            .collect();
+        self.rescue_hash_n_to_1(&public_inputs)
+    }
+
     pub fn add_virtual_target(&mut self) -> Target<C::ScalarField> {
         let index = self.virtual_target_index;
         self.virtual_target_index += 1;
@@ -68,6 +129,22 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         (0..n).map(|_i| self.add_virtual_point_target()).collect()
     }
 
+    /// Returns the wire which gate `gate` sees as its "below" wire `input`. Gates are laid out on
+    /// a grid of width `GRID_WIDTH`, so this is the wire `input` of gate `gate + GRID_WIDTH`,
+    /// which is what gate constraints receive as `below_wire_values`.
+    ///
+    /// That gate may not have been added yet. This reserves its row, so that if the circuit has
+    /// fewer gates when it is built, it is padded with `BufferGate`s, rather than the row being
+    /// taken by a blinding or public input gate.
+    pub fn wire_below(&mut self, gate: usize, input: usize) -> Target<C::ScalarField> {
+        assert!(input < NUM_WIRES, "Wire index {} is out of range", input);
+        self.min_num_gates = self.min_num_gates.max(gate + GRID_WIDTH + 1);
+        Target::Wire(Wire {
+            gate: gate + GRID_WIDTH,
+            input,
+        })
+    }
+
     pub fn zero_wire(&mut self) -> Target<C::ScalarField> {
         self.constant_wire(C::ScalarField::ZERO)
     }
@@ -94,15 +171,25 @@ impl<C: HaloCurve> CircuitBuilder<C> {
 
     #[allow(clippy::map_entry)]
     pub fn constant_wire(&mut self, c: C::ScalarField) -> Target<C::ScalarField> {
-        if self.constant_wires.contains_key(&c) {
-            self.constant_wires[&c]
+        let key = c.to_canonical_u64_vec();
+        if self.constant_wires.contains_key(&key) {
+            self.constant_wires[&key]
         } else {
             let result = self.create_constant_wire(c);
-            self.constant_wires.insert(c, result);
+            self.constant_wires.insert(key, result);
+            if c == C::ScalarField::ZERO || c == C::ScalarField::ONE {
+                self.binary_targets.insert(result);
+            }
             result
         }
     }
 
+    /// Returns the constant wire for 0 or 1. Like any such constant, it is known to be binary, so
+    /// `assert_binary` adds no constraints for it.
+    pub fn bool_constant(&mut self, b: bool) -> Target<C::ScalarField> {
+        self.constant_wire(C::ScalarField::from_canonical_bool(b))
+    }
+
     pub fn constant_wires(&mut self, constants: &[C::ScalarField]) -> Vec<Target<C::ScalarField>> {
         constants.iter().map(|&c| self.constant_wire(c)).collect()
     }
@@ -111,6 +198,56 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         self.constant_wire(C::ScalarField::from_canonical_u32(c))
     }
 
+    /// Returns a target holding constant `const_index` of the given gate, which must be a `G`.
+    /// Indices count the gate's prefix bits, as with the `local_constant_values` seen by
+    /// `Gate::evaluate_unfiltered`, so they must be at least `G::PREFIX.len()`. For example, the
+    /// `const_mul` of an `ArithmeticGate` has index `ArithmeticGate::PREFIX.len()`.
+    ///
+    /// Since a constraint can only read the constants of its own gate, this adds a `ConstantGate`
+    /// which reads the same constant into its output wire, and copies the result into that wire.
+    /// The result is read-only: copying other targets into it will only constrain those targets,
+    /// and never changes the gate's constant.
+    pub fn constant_as_target<G: Gate<C>>(
+        &mut self,
+        gate: usize,
+        const_index: usize,
+    ) -> Target<C::ScalarField> {
+        assert!(gate < self.num_gates(), "Gate {} does not exist", gate);
+        let prefix = self.gate_constants[gate][..G::PREFIX.len()].to_vec();
+        assert!(
+            prefix
+                .iter()
+                .zip(G::PREFIX)
+                .all(|(&bit, &expected)| bit == C::ScalarField::from_canonical_bool(expected)),
+            "Gate {} is not a {}",
+            gate,
+            G::NAME
+        );
+        assert!(
+            const_index >= G::PREFIX.len(),
+            "Constant index {} is part of the {} prefix",
+            const_index,
+            G::NAME
+        );
+        assert!(
+            const_index < NUM_CONSTANTS,
+            "Constant index {} out of range",
+            const_index
+        );
+
+        let c = self.gate_constants[gate][const_index];
+        let reader_gate = self.num_gates();
+        self.add_gate(ConstantGate::new(reader_gate), vec![c]);
+        let reader_output = Target::Wire(Wire {
+            gate: reader_gate,
+            input: ConstantGate::<C>::WIRE_OUTPUT,
+        });
+
+        let result = self.add_virtual_target();
+        self.copy(reader_output, result);
+        result
+    }
+
     fn create_constant_wire(&mut self, c: C::ScalarField) -> Target<C::ScalarField> {
         // We will create a ConstantGate and pass c as its first (and only) constant, which will
         // cause it to populate its output wire with the same value c.
@@ -153,16 +290,57 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         self.copy(x, zero);
     }
 
+    /// Asserts that `x == 0` if `flag == 1`, by enforcing `flag * x == 0` with a single gate. If
+    /// `flag == 0`, the constraint is vacuous. `flag` is assumed to be binary; callers should
+    /// assert this if it is not already enforced. Combined with `select`, this allows constraints
+    /// which only apply in some branches of a circuit.
+    pub fn conditional_assert_zero(
+        &mut self,
+        flag: Target<C::ScalarField>,
+        x: Target<C::ScalarField>,
+    ) {
+        let product = self.mul(flag, x);
+        self.assert_zero(product);
+    }
+
+    /// Asserts that `x == 1`, by copying it into the constant one wire.
     pub fn assert_one(&mut self, x: Target<C::ScalarField>) {
         let one = self.one_wire();
         self.copy(x, one);
     }
 
+    /// Asserts that `a * b == c`, where all three are existing targets, by constraining the output
+    /// of a single `ArithmeticGate` computing `a * b - c` to be zero.
+    pub fn assert_mul(
+        &mut self,
+        a: Target<C::ScalarField>,
+        b: Target<C::ScalarField>,
+        c: Target<C::ScalarField>,
+    ) {
+        let diff = self.mul_sub(a, b, c);
+        self.assert_zero(diff);
+    }
+
+    /// Asserts that `x == c`. Rather than creating a `ConstantGate` for `c`, this folds `c` into
+    /// the constants of a single `ArithmeticGate` computing `x * 1 - c * 1`, which is then
+    /// constrained to be zero.
+    pub fn assert_equal_const(&mut self, x: Target<C::ScalarField>, c: C::ScalarField) {
+        let one = self.one_wire();
+        let diff = self.arithmetic(C::ScalarField::ONE, x, one, -c, one);
+        self.assert_zero(diff);
+    }
+
+    /// Asserts that `x` is 0 or 1. This adds no gates if `x` is already known to be binary, such as
+    /// a `bool_constant` or a target which was previously asserted to be binary.
     pub fn assert_binary(&mut self, x: Target<C::ScalarField>) {
+        if self.binary_targets.contains(&x) {
+            return;
+        }
         // This is typically implemented with a constraint like x * (x - 1) = 0.
         // We rewrite this as x * x - x = 0, which requires just one gate in our model.
         let lhs = self.mul_sub(x, x, x);
         self.assert_zero(lhs);
+        self.binary_targets.insert(x);
     }
 
     /// Assert that each of the given targets is less than 4.
@@ -172,15 +350,31 @@ impl<C: HaloCurve> CircuitBuilder<C> {
             let gate = self.num_gates();
             self.add_gate_no_constants(Base4SumGate::new(gate));
 
-            // We don't care about Base4SumGate's accumulator wires, but we need to pass some
-            // (arbitrary) value to the old accumulator wire in order for the generator to run.
-            self.generate_constant(
+            // We don't care about Base4SumGate's accumulator wires, but they must still satisfy
+            // its sum constraint. We set the old accumulator to an arbitrary value, and generate
+            // the new one from it and the limbs. Any unrouted limbs are left as zero.
+            let acc_old = Target::Wire(Wire {
+                gate,
+                input: Base4SumGate::<C>::WIRE_ACC_OLD,
+            });
+            let acc_new = Target::Wire(Wire {
+                gate,
+                input: Base4SumGate::<C>::WIRE_ACC_NEW,
+            });
+            self.generate_constant(acc_old, C::ScalarField::ZERO); // This value is arbitrary.
+            let mut inputs = vec![acc_old];
+            inputs.extend((0..chunk.len()).map(|i| {
                 Target::Wire(Wire {
                     gate,
-                    input: Base4SumGate::<C>::WIRE_ACC_OLD,
-                }),
-                C::ScalarField::ZERO, // This value is arbitrary.
-            );
+                    input: Base4SumGate::<C>::wire_limb(i),
+                })
+            }));
+            self.add_generator(SimpleGenerator::new(inputs, acc_new, |values| {
+                let limbs = &values[1..];
+                (0..Base4SumGate::<C>::NUM_LIMBS).fold(values[0], |acc, i| {
+                    acc.quadruple() + limbs.get(i).copied().unwrap_or(C::ScalarField::ZERO)
+                })
+            }));
 
             // Route each limb to one of Base4SumGate's routed limb wires.
             for (i, &limb) in chunk.iter().enumerate() {
@@ -200,6 +394,85 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         self.inv(x);
     }
 
+    /// Assert that `x` is one of the given constants, by enforcing `prod_i (x - allowed_i) = 0`.
+    ///
+    /// The product is a degree-`allowed.len()` polynomial in `x`, which we evaluate with one gate
+    /// per allowed value, so this is only suitable for small sets.
+    pub fn assert_in_set(&mut self, x: Target<C::ScalarField>, allowed: &[C::ScalarField]) {
+        assert!(!allowed.is_empty(), "The set of allowed values must be nonempty");
+
+        // We compute each partial product as acc * x - a * acc, which fits in a single
+        // ArithmeticGate.
+        let mut acc = self.one_wire();
+        for &a in allowed {
+            acc = self.arithmetic(C::ScalarField::ONE, acc, x, -a, acc);
+        }
+        self.assert_zero(acc);
+    }
+
+    /// Asserts that `x < 2^num_bits`, by splitting `x` into `num_bits` bits, asserting that each is
+    /// binary, and copying their weighted sum into `x`. This costs about two gates per bit.
+    pub fn assert_range(&mut self, x: Target<C::ScalarField>, num_bits: usize) {
+        assert!(
+            num_bits < C::ScalarField::BITS,
+            "A range of {} bits is too large for a {}-bit field",
+            num_bits,
+            C::ScalarField::BITS
+        );
+        let bits = self.split_binary(x, num_bits);
+        for &bit in &bits {
+            self.assert_binary(bit);
+        }
+        let sum = self.le_sum(&bits);
+        self.copy(sum, x);
+    }
+
+    /// Asserts that `x <= bound`, by computing `bound - x` with a single `ArithmeticGate`, with
+    /// `bound` folded into its constants, and asserting that it is in range with `assert_range`.
+    ///
+    /// Viewing field elements as integers, this enforces `bound - 2^num_bits < x <= bound`, so it is
+    /// equivalent to `x <= bound` if `x` is already known to be less than `2^num_bits`, and
+    /// `bound < 2^num_bits`. `num_bits` must be less than `BITS - 1`.
+    pub fn assert_le_const(
+        &mut self,
+        x: Target<C::ScalarField>,
+        bound: C::ScalarField,
+        num_bits: usize,
+    ) {
+        assert!(
+            num_bits < C::ScalarField::BITS - 1,
+            "A range of {} bits is too large to compare elements of a {}-bit field",
+            num_bits,
+            C::ScalarField::BITS
+        );
+        let one = self.one_wire();
+        let diff = self.arithmetic(C::ScalarField::NEG_ONE, x, one, bound, one);
+        self.assert_range(diff, num_bits);
+    }
+
+    /// Asserts that `x >= bound`, by computing `x - bound` with a single `ArithmeticGate`, with
+    /// `bound` folded into its constants, and asserting that it is in range with `assert_range`.
+    ///
+    /// Viewing field elements as integers, this enforces `bound <= x < bound + 2^num_bits`, so it is
+    /// equivalent to `x >= bound` if `x` is already known to be less than `2^num_bits`, and
+    /// `bound < 2^num_bits`. `num_bits` must be less than `BITS - 1`.
+    pub fn assert_ge_const(
+        &mut self,
+        x: Target<C::ScalarField>,
+        bound: C::ScalarField,
+        num_bits: usize,
+    ) {
+        assert!(
+            num_bits < C::ScalarField::BITS - 1,
+            "A range of {} bits is too large to compare elements of a {}-bit field",
+            num_bits,
+            C::ScalarField::BITS
+        );
+        let one = self.one_wire();
+        let diff = self.arithmetic(C::ScalarField::ONE, x, one, -bound, one);
+        self.assert_range(diff, num_bits);
+    }
+
     /// Returns `if x == 0 { 1 } else { 0 }`.
     pub fn is_zero(&mut self, x: Target<C::ScalarField>) -> Target<C::ScalarField> {
         // This is similar to the technique described in
@@ -307,6 +580,94 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         self.sub(one, b)
     }
 
+    /// Like `not`, but also asserts that `b` is binary.
+    pub fn not_checked(&mut self, b: Target<C::ScalarField>) -> Target<C::ScalarField> {
+        self.assert_binary(b);
+        self.not(b)
+    }
+
+    /// Returns the logical OR of the given bits, computed as `1 - prod(1 - b_i)`. Each bit is
+    /// asserted to be binary.
+    pub fn or_many(&mut self, bits: &[Target<C::ScalarField>]) -> Target<C::ScalarField> {
+        let mut none_set = self.one_wire();
+        for &b in bits {
+            self.assert_binary(b);
+            let not_b = self.not(b);
+            none_set = self.mul(none_set, not_b);
+        }
+        self.not(none_set)
+    }
+
+    /// Returns the logical AND of the given bits, computed as `prod(b_i)`. Each bit is asserted to
+    /// be binary.
+    pub fn and_many(&mut self, bits: &[Target<C::ScalarField>]) -> Target<C::ScalarField> {
+        let mut all_set = self.one_wire();
+        for &b in bits {
+            self.assert_binary(b);
+            all_set = self.mul(all_set, b);
+        }
+        all_set
+    }
+
+    /// Returns the little-endian weighted sum `sum_i 2^i bits[i]`, using one gate per bit after
+    /// the first. The bits are not asserted to be binary.
+    pub fn le_sum(&mut self, bits: &[Target<C::ScalarField>]) -> Target<C::ScalarField> {
+        let (&msb, rest) = match bits.split_last() {
+            Some(split) => split,
+            None => return self.zero_wire(),
+        };
+        let one = self.one_wire();
+        rest.iter().rev().fold(msb, |acc, &b| {
+            self.arithmetic(C::ScalarField::TWO, acc, one, C::ScalarField::ONE, b)
+        })
+    }
+
+    /// Rotates a little-endian bit vector left by `n` positions, i.e. towards the more significant
+    /// end, so that `result[i] = bits[(i - n) mod len]`. This only reindexes the targets, so it adds
+    /// no gates.
+    pub fn rotate_left(
+        &mut self,
+        bits: &[Target<C::ScalarField>],
+        n: usize,
+    ) -> Vec<Target<C::ScalarField>> {
+        if bits.is_empty() {
+            return Vec::new();
+        }
+        let mut result = bits.to_vec();
+        result.rotate_right(n % bits.len());
+        result
+    }
+
+    /// Shifts a little-endian bit vector left by `n` positions, i.e. towards the more significant
+    /// end, filling the vacated low positions with `zero_wire()`. Bits shifted past the end are
+    /// discarded, so the result has the same length as the input.
+    pub fn shift_left(
+        &mut self,
+        bits: &[Target<C::ScalarField>],
+        n: usize,
+    ) -> Vec<Target<C::ScalarField>> {
+        let zero = self.zero_wire();
+        let n = n.min(bits.len());
+        let mut result = vec![zero; n];
+        result.extend_from_slice(&bits[..bits.len() - n]);
+        result
+    }
+
+    /// Shifts a little-endian bit vector right by `n` positions, i.e. towards the less significant
+    /// end, filling the vacated high positions with `zero_wire()`. Bits shifted past the start are
+    /// discarded, so the result has the same length as the input.
+    pub fn shift_right(
+        &mut self,
+        bits: &[Target<C::ScalarField>],
+        n: usize,
+    ) -> Vec<Target<C::ScalarField>> {
+        let zero = self.zero_wire();
+        let n = n.min(bits.len());
+        let mut result = bits[n..].to_vec();
+        result.resize(bits.len(), zero);
+        result
+    }
+
     pub fn add(
         &mut self,
         x: Target<C::ScalarField>,
@@ -321,36 +682,7 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         }
 
         let one = self.one_wire();
-        let index = self.num_gates();
-        self.add_gate(
-            ArithmeticGate::new(index),
-            vec![C::ScalarField::ONE, C::ScalarField::ONE],
-        );
-        self.copy(
-            x,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_MULTIPLICAND_0,
-            }),
-        );
-        self.copy(
-            one,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_MULTIPLICAND_1,
-            }),
-        );
-        self.copy(
-            y,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_ADDEND,
-            }),
-        );
-        Target::Wire(Wire {
-            gate: index,
-            input: ArithmeticGate::<C>::WIRE_OUTPUT,
-        })
+        self.arithmetic(C::ScalarField::ONE, x, one, C::ScalarField::ONE, y)
     }
 
     pub fn add_many(&mut self, terms: &[Target<C::ScalarField>]) -> Target<C::ScalarField> {
@@ -361,6 +693,42 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         sum
     }
 
+    /// Computes the sum of the given terms, like `add_many`, but using a chain of `RunningSumGate`s.
+    /// This also uses one gate per term, plus a `BufferGate` to receive the sum, but each gate
+    /// only adds one routed wire to the permutation argument.
+    pub fn running_sum(&mut self, terms: &[Target<C::ScalarField>]) -> Target<C::ScalarField> {
+        if terms.is_empty() {
+            return self.zero_wire();
+        }
+
+        let zero = self.zero_wire();
+        self.copy(
+            zero,
+            Target::Wire(Wire {
+                gate: self.num_gates(),
+                input: RunningSumGate::<C>::WIRE_ACC,
+            }),
+        );
+        for &term in terms {
+            let gate = self.num_gates();
+            self.add_gate_no_constants(RunningSumGate::new(gate));
+            self.copy(
+                term,
+                Target::Wire(Wire {
+                    gate,
+                    input: RunningSumGate::<C>::WIRE_VALUE,
+                }),
+            );
+        }
+
+        let gate = self.num_gates();
+        self.add_gate_no_constants(BufferGate::new(gate));
+        Target::Wire(Wire {
+            gate,
+            input: RunningSumGate::<C>::WIRE_ACC,
+        })
+    }
+
     pub fn double(&mut self, x: Target<C::ScalarField>) -> Target<C::ScalarField> {
         self.add(x, x)
     }
@@ -376,36 +744,7 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         }
 
         let one = self.one_wire();
-        let index = self.num_gates();
-        self.add_gate(
-            ArithmeticGate::new(index),
-            vec![C::ScalarField::ONE, C::ScalarField::NEG_ONE],
-        );
-        self.copy(
-            x,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_MULTIPLICAND_0,
-            }),
-        );
-        self.copy(
-            one,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_MULTIPLICAND_1,
-            }),
-        );
-        self.copy(
-            y,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_ADDEND,
-            }),
-        );
-        Target::Wire(Wire {
-            gate: index,
-            input: ArithmeticGate::<C>::WIRE_OUTPUT,
-        })
+        self.arithmetic(C::ScalarField::ONE, x, one, C::ScalarField::NEG_ONE, y)
     }
 
     pub fn mul(
@@ -422,36 +761,7 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         }
 
         let zero = self.zero_wire();
-        let index = self.num_gates();
-        self.add_gate(
-            ArithmeticGate::new(index),
-            vec![C::ScalarField::ONE, C::ScalarField::ZERO],
-        );
-        self.copy(
-            x,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_MULTIPLICAND_0,
-            }),
-        );
-        self.copy(
-            y,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_MULTIPLICAND_1,
-            }),
-        );
-        self.copy(
-            zero,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_ADDEND,
-            }),
-        );
-        Target::Wire(Wire {
-            gate: index,
-            input: ArithmeticGate::<C>::WIRE_OUTPUT,
-        })
+        self.arithmetic(C::ScalarField::ONE, x, y, C::ScalarField::ZERO, zero)
     }
 
     pub fn mul_many(&mut self, terms: &[Target<C::ScalarField>]) -> Target<C::ScalarField> {
@@ -462,6 +772,61 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         product
     }
 
+    /// Computes `sum_i terms[i] challenge^i` using Horner's method, with one `mul_add` per term
+    /// after the first. This is the usual way of batching several checks with a challenge from
+    /// the transcript.
+    pub fn random_linear_combination(
+        &mut self,
+        terms: &[Target<C::ScalarField>],
+        challenge: Target<C::ScalarField>,
+    ) -> Target<C::ScalarField> {
+        let (&last, rest) = match terms.split_last() {
+            Some(split) => split,
+            None => return self.zero_wire(),
+        };
+        let mut acc = last;
+        for &term in rest.iter().rev() {
+            acc = self.mul_add(acc, challenge, term);
+        }
+        acc
+    }
+
+    /// Computes `prod_i numerator[i] / prod_i denominator[i]`, the final value of the grand product
+    /// accumulated by permutation and lookup style arguments. The denominators must be nonzero.
+    pub fn grand_product(
+        &mut self,
+        numerator: &[Target<C::ScalarField>],
+        denominator: &[Target<C::ScalarField>],
+    ) -> Target<C::ScalarField> {
+        let numerator_product = self.mul_many(numerator);
+        let denominator_product = self.mul_many(denominator);
+        self.div(numerator_product, denominator_product)
+    }
+
+    /// Asserts that `b` is a permutation of `a`, by checking that the grand product
+    /// `prod_i (a[i] + challenge) / (b[i] + challenge)` is one. This is sound only if `challenge`
+    /// is random and independent of `a` and `b`, e.g. if it is derived from a hash of them.
+    ///
+    /// Returns the grand product target, which is always one in a satisfying witness, so that
+    /// custom arguments can build on it.
+    pub fn assert_permutation_with_challenge(
+        &mut self,
+        a: &[Target<C::ScalarField>],
+        b: &[Target<C::ScalarField>],
+        challenge: Target<C::ScalarField>,
+    ) -> Target<C::ScalarField> {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "Cannot check a permutation between vectors of different lengths"
+        );
+        let numerator: Vec<_> = a.iter().map(|&x| self.add(x, challenge)).collect();
+        let denominator: Vec<_> = b.iter().map(|&x| self.add(x, challenge)).collect();
+        let product = self.grand_product(&numerator, &denominator);
+        self.assert_equal_const(product, C::ScalarField::ONE);
+        product
+    }
+
     pub fn square(&mut self, x: Target<C::ScalarField>) -> Target<C::ScalarField> {
         self.mul(x, x)
     }
@@ -602,6 +967,28 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         self.exp_constant(x, C::ScalarField::from_canonical_usize(power))
     }
 
+    /// Compute `base^e`, where `base` is a constant and `e` is given by its little-endian bits,
+    /// which are assumed to be binary. Since the base is known, the powers `base^(2^i)` are
+    /// precomputed as constants, so no squaring constraints are needed.
+    pub fn pow_const_base(
+        &mut self,
+        base: C::ScalarField,
+        exponent_bits: &[Target<C::ScalarField>],
+    ) -> Target<C::ScalarField> {
+        let one = self.one_wire();
+        let mut product = one;
+        let mut current = base;
+
+        for &bit in exponent_bits {
+            let current_wire = self.constant_wire(current);
+            let factor = self.select(bit, current_wire, one);
+            product = self.mul(product, factor);
+            current = current.square();
+        }
+
+        product
+    }
+
     pub fn inv(&mut self, x: Target<C::ScalarField>) -> Target<C::ScalarField> {
         struct InverseGenerator<F: Field> {
             x: Target<F>,
@@ -631,9 +1018,8 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         self.add_generator(InverseGenerator { x, x_inv });
 
         // Enforce that x * x_inv = 1.
-        let product = self.mul(x, x_inv);
         let one = self.one_wire();
-        self.copy(product, one);
+        self.assert_mul(x, x_inv, one);
 
         x_inv
     }
@@ -647,34 +1033,35 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         self.mul(x, y_inv)
     }
 
-    /// Multiply and add; i.e. computes `x * y + z`.
-    pub fn mul_add(
+    /// Computes `const_mul * a * b + const_add * c`, using a single `ArithmeticGate`. Gadgets can
+    /// use this to fold constants into an expression which would otherwise need separate gates for
+    /// the constants, the products and the sum.
+    pub fn arithmetic(
         &mut self,
-        x: Target<C::ScalarField>,
-        y: Target<C::ScalarField>,
-        z: Target<C::ScalarField>,
+        const_mul: C::ScalarField,
+        a: Target<C::ScalarField>,
+        b: Target<C::ScalarField>,
+        const_add: C::ScalarField,
+        c: Target<C::ScalarField>,
     ) -> Target<C::ScalarField> {
         let index = self.num_gates();
-        self.add_gate(
-            ArithmeticGate::new(index),
-            vec![C::ScalarField::ONE, C::ScalarField::ONE],
-        );
+        self.add_gate(ArithmeticGate::new(index), vec![const_mul, const_add]);
         self.copy(
-            x,
+            a,
             Target::Wire(Wire {
                 gate: index,
                 input: ArithmeticGate::<C>::WIRE_MULTIPLICAND_0,
             }),
         );
         self.copy(
-            y,
+            b,
             Target::Wire(Wire {
                 gate: index,
                 input: ArithmeticGate::<C>::WIRE_MULTIPLICAND_1,
             }),
         );
         self.copy(
-            z,
+            c,
             Target::Wire(Wire {
                 gate: index,
                 input: ArithmeticGate::<C>::WIRE_ADDEND,
@@ -686,6 +1073,54 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         })
     }
 
+    /// Multiply and add; i.e. computes `x * y + z`.
+    pub fn mul_add(
+        &mut self,
+        x: Target<C::ScalarField>,
+        y: Target<C::ScalarField>,
+        z: Target<C::ScalarField>,
+    ) -> Target<C::ScalarField> {
+        self.arithmetic(C::ScalarField::ONE, x, y, C::ScalarField::ONE, z)
+    }
+
+    /// Computes `x * y + const_add`. The constant is folded into the `ArithmeticGate`'s addend
+    /// coefficient, with the shared `one_wire` as the addend, so unlike
+    /// `mul_add(x, y, constant_wire(const_add))`, this never needs a new constant gate.
+    pub fn mul_add_const(
+        &mut self,
+        x: Target<C::ScalarField>,
+        y: Target<C::ScalarField>,
+        const_add: C::ScalarField,
+    ) -> Target<C::ScalarField> {
+        let one = self.one_wire();
+        self.arithmetic(C::ScalarField::ONE, x, y, const_add, one)
+    }
+
+    /// Computes `x - c`. The constant is folded into the `ArithmeticGate`'s addend coefficient, as
+    /// in `mul_add_const`, so this never needs a new constant gate.
+    pub fn sub_const(
+        &mut self,
+        x: Target<C::ScalarField>,
+        c: C::ScalarField,
+    ) -> Target<C::ScalarField> {
+        if c == C::ScalarField::ZERO {
+            return x;
+        }
+
+        let one = self.one_wire();
+        self.arithmetic(C::ScalarField::ONE, x, one, -c, one)
+    }
+
+    /// Computes `c - x`, folding the constant into an `ArithmeticGate` as in `sub_const`.
+    pub fn const_sub(
+        &mut self,
+        c: C::ScalarField,
+        x: Target<C::ScalarField>,
+    ) -> Target<C::ScalarField> {
+        let one = self.one_wire();
+        self.arithmetic(C::ScalarField::NEG_ONE, x, one, c, one)
+    }
+
     pub(crate) fn bounded_mul_add(
         &mut self,
         x: &BoundedTarget<C::ScalarField>,
@@ -704,36 +1139,7 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         y: Target<C::ScalarField>,
         z: Target<C::ScalarField>,
     ) -> Target<C::ScalarField> {
-        let index = self.num_gates();
-        self.add_gate(
-            ArithmeticGate::new(index),
-            vec![C::ScalarField::ONE, C::ScalarField::NEG_ONE],
-        );
-        self.copy(
-            x,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_MULTIPLICAND_0,
-            }),
-        );
-        self.copy(
-            y,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_MULTIPLICAND_1,
-            }),
-        );
-        self.copy(
-            z,
-            Target::Wire(Wire {
-                gate: index,
-                input: ArithmeticGate::<C>::WIRE_ADDEND,
-            }),
-        );
-        Target::Wire(Wire {
-            gate: index,
-            input: ArithmeticGate::<C>::WIRE_OUTPUT,
-        })
+        self.arithmetic(C::ScalarField::ONE, x, y, C::ScalarField::NEG_ONE, z)
     }
 
     /// Computes `-x`.
@@ -817,6 +1223,52 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         (bits, dibits)
     }
 
+    /// Asserts that the little-endian bits `bits` encode a value less than the field order, so that
+    /// they are the canonical binary representation of some field element. The bits are assumed to
+    /// be binary; callers should assert this if it is not already enforced.
+    ///
+    /// A decomposition into fewer than `BITS` bits is always canonical, so this only adds
+    /// constraints when `bits.len() == BITS`. In that case, we compute the borrow chain of the
+    /// subtraction `(p - 1) - x`, and constrain the final borrow to be zero, i.e. `x <= p - 1`.
+    pub fn assert_boolean_decomposition_is_canonical(&mut self, bits: &[Target<C::ScalarField>]) {
+        let max_bits = C::ScalarField::NEG_ONE.to_canonical_bool_vec();
+        assert!(
+            bits.len() <= C::ScalarField::BITS,
+            "A decomposition of {} bits is too long for a {}-bit field",
+            bits.len(),
+            C::ScalarField::BITS
+        );
+        if bits.len() < C::ScalarField::BITS {
+            return;
+        }
+
+        let zero = self.zero_wire();
+        let mut borrow = zero;
+        for (&x_i, &max_i) in bits.iter().zip(max_bits.iter()) {
+            borrow = match (max_i, borrow == zero) {
+                // 1 - x_i - 0 never borrows.
+                (true, true) => zero,
+                // 1 - x_i - borrow borrows iff x_i = borrow = 1.
+                (true, false) => self.mul(x_i, borrow),
+                // 0 - x_i - 0 borrows iff x_i = 1.
+                (false, true) => x_i,
+                // 0 - x_i - borrow borrows iff x_i = 1 or borrow = 1, i.e. iff
+                // x_i + borrow - x_i borrow = 1.
+                (false, false) => {
+                    let sum = self.add(x_i, borrow);
+                    self.arithmetic(
+                        C::ScalarField::NEG_ONE,
+                        x_i,
+                        borrow,
+                        C::ScalarField::ONE,
+                        sum,
+                    )
+                }
+            };
+        }
+        self.assert_zero(borrow);
+    }
+
     /// Asserts that the given target's value is small enough to fit in the given number of dibits.
     ///
     /// Note: This is most efficient when `num_dibits` is a multiple of `Base4SumGate::NUM_LIMBS`.
@@ -899,20 +1351,63 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         (outputs[0], outputs[1], outputs[2])
     }
 
+    pub fn rescue_hash_n_to_1_with_domain(
+        &mut self,
+        inputs: &[Target<C::ScalarField>],
+        domain: u64,
+    ) -> Target<C::ScalarField> {
+        self.rescue_sponge_with_domain(inputs, 1, domain)[0]
+    }
+
     pub fn rescue_sponge(
         &mut self,
         inputs: &[Target<C::ScalarField>],
         num_outputs: usize,
+    ) -> Vec<Target<C::ScalarField>> {
+        self.rescue_sponge_with_domain(inputs, num_outputs, 0)
+    }
+
+    /// Like `rescue_sponge`, but with the capacity element initialized to the given domain tag.
+    /// See the native `rescue_sponge_with_domain`.
+    pub fn rescue_sponge_with_domain(
+        &mut self,
+        inputs: &[Target<C::ScalarField>],
+        num_outputs: usize,
+        domain: u64,
+    ) -> Vec<Target<C::ScalarField>> {
+        let rounds = self.rescue_rounds();
+        self.rescue_sponge_with_domain_rounds(inputs, num_outputs, domain, rounds)
+    }
+
+    /// Like `rescue_sponge`, but with an explicit number of rounds per permutation. This computes
+    /// the same outputs as the native `rescue_sponge_rounds`.
+    pub fn rescue_hash_rounds(
+        &mut self,
+        inputs: &[Target<C::ScalarField>],
+        num_outputs: usize,
+        rounds: usize,
+    ) -> Vec<Target<C::ScalarField>> {
+        self.rescue_sponge_with_domain_rounds(inputs, num_outputs, 0, rounds)
+    }
+
+    fn rescue_sponge_with_domain_rounds(
+        &mut self,
+        inputs: &[Target<C::ScalarField>],
+        num_outputs: usize,
+        domain: u64,
+        rounds: usize,
     ) -> Vec<Target<C::ScalarField>> {
         let zero = self.zero_wire();
         let mut state = vec![zero; RESCUE_SPONGE_WIDTH];
+        state[RESCUE_SPONGE_WIDTH - 1] =
+            self.constant_wire(C::ScalarField::from_canonical_u64(domain));
 
         // Absorb all input chunks.
         for input_chunk in inputs.chunks(RESCUE_SPONGE_WIDTH - 1) {
             for i in 0..input_chunk.len() {
                 state[i] = self.add(state[i], input_chunk[i]);
             }
-            state = self.rescue_permutation(&state);
+            state = self.rescue_permutation_rounds(&state, rounds);
         }
 
         // Squeeze until we have the desired number of outputs.
@@ -924,15 +1419,31 @@ impl<C: HaloCurve> CircuitBuilder<C> {
                     return outputs;
                 }
             }
-            state = self.rescue_permutation(&state);
+            state = self.rescue_permutation_rounds(&state, rounds);
         }
     }
 
+    /// Computes the same permutation as the native `rescue_permutation`, with the recommended
+    /// number of rounds. Each round is laid out as a `RescueStepAGate`, for the `x^(1/5)` half,
+    /// followed by a `RescueStepBGate`, for the `x^5` half, with that round's constants as gate
+    /// constants. A final `BufferGate` receives the output state.
     pub fn rescue_permutation(
         &mut self,
         inputs: &[Target<C::ScalarField>],
+    ) -> Vec<Target<C::ScalarField>> {
+        let rounds = self.rescue_rounds();
+        self.rescue_permutation_rounds(inputs, rounds)
+    }
+
+    /// Like `rescue_permutation`, but with an explicit number of rounds, each of which costs two
+    /// gates.
+    pub fn rescue_permutation_rounds(
+        &mut self,
+        inputs: &[Target<C::ScalarField>],
+        rounds: usize,
     ) -> Vec<Target<C::ScalarField>> {
         assert_eq!(inputs.len(), RESCUE_SPONGE_WIDTH);
+        assert!(rounds > 0, "A Rescue permutation needs at least one round");
 
         // Route the input wires.
         for i in 0..RESCUE_SPONGE_WIDTH {
@@ -945,7 +1456,7 @@ impl<C: HaloCurve> CircuitBuilder<C> {
             );
         }
 
-        let all_constants = generate_rescue_constants(RESCUE_SPONGE_WIDTH, self.security_bits);
+        let all_constants = generate_rescue_constants_rounds(RESCUE_SPONGE_WIDTH, rounds);
         for (a_constants, b_constants) in all_constants.into_iter() {
             let a_index = self.num_gates();
             let a_gate = RescueStepAGate::new(a_index);
@@ -969,11 +1480,75 @@ impl<C: HaloCurve> CircuitBuilder<C> {
             .collect()
     }
 
+    /// The number of rounds recommended for Rescue permutations at this builder's security level.
+    fn rescue_rounds(&self) -> usize {
+        recommended_rounds::<C::ScalarField>(RESCUE_SPONGE_WIDTH, self.security_bits)
+    }
+
+    /// Multiplies the given state vector by the Rescue MDS matrix, using a single two-row
+    /// `MdsGate`.
+    pub fn mds_layer(&mut self, inputs: &[Target<C::ScalarField>]) -> Vec<Target<C::ScalarField>> {
+        assert_eq!(inputs.len(), RESCUE_SPONGE_WIDTH);
+
+        let gate = self.num_gates();
+        self.add_gate_no_constants(MdsGate::new(gate));
+        for i in 0..RESCUE_SPONGE_WIDTH {
+            self.copy(
+                inputs[i],
+                Target::Wire(Wire {
+                    gate,
+                    input: MdsGate::<C>::wire_input(i),
+                }),
+            );
+        }
+
+        // The outputs are in the gate's second row.
+        (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| {
+                Target::Wire(Wire {
+                    gate: gate + 1,
+                    input: MdsGate::<C>::wire_output(i),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns `T::TABLE[input]`, where `input` is assumed to fit in `T::BITS` bits (this is
+    /// enforced by the gate). The table must be registered in `evaluate_all_constraints`.
+    pub fn small_table_lookup<T: SmallTable>(
+        &mut self,
+        input: Target<C::ScalarField>,
+    ) -> Target<C::ScalarField> {
+        let gate = self.num_gates();
+        self.add_gate_no_constants(SmallTableGate::<C, T>::new(gate));
+        self.copy(
+            input,
+            Target::Wire(Wire {
+                gate,
+                input: SmallTableGate::<C, T>::WIRE_INPUT,
+            }),
+        );
+        Target::Wire(Wire {
+            gate,
+            input: SmallTableGate::<C, T>::WIRE_OUTPUT,
+        })
+    }
+
     /// Adds a gate to the circuit, without doing any routing.
     pub fn add_gate_no_constants<G: Gate<C>>(&mut self, gate: G) {
         self.add_gate(gate, Vec::new());
     }
 
+    /// Adds a gate given its full row of constants, including its prefix bits, such as a row of
+    /// `Circuit::gate_constants`. Unlike `add_gate`, this adds no generator, so the gate's wires
+    /// must be populated by the caller's inputs.
+    pub(crate) fn add_gate_with_raw_constants(&mut self, constants: Vec<C::ScalarField>) {
+        assert_eq!(constants.len(), NUM_CONSTANTS, "Expected a full row of constants");
+        self.gate_constants.push(constants);
+        #[cfg(feature = "debug")]
+        self.gate_labels.push(self.current_label.clone());
+    }
+
     /// Adds a gate to the circuit, without doing any routing.
     pub fn add_gate<G: Gate<C>>(&mut self, gate: G, gate_constants: Vec<C::ScalarField>) {
         trace!("{} {}", self.num_gates(), G::NAME);
@@ -991,24 +1566,103 @@ impl<C: HaloCurve> CircuitBuilder<C> {
             all_constants.push(C::ScalarField::ZERO);
         }
 
+        let index = self.gate_constants.len();
+        let routed_wires = gate.routed_wires();
+        for input in 0..NUM_ROUTED_WIRES {
+            if !routed_wires.contains(&input) {
+                self.unrouted_wires.insert(Wire { gate: index, input });
+            }
+        }
+
+        let num_rows = gate.num_rows();
+        assert!(
+            (1..=2).contains(&num_rows),
+            "{} spans {} rows, but gates must span one or two rows",
+            G::NAME,
+            num_rows
+        );
+
         self.gate_constants.push(all_constants);
+        #[cfg(feature = "debug")]
+        self.gate_labels.push(self.current_label.clone());
         self.add_generator(gate);
         *self.gate_counts.entry(G::NAME).or_insert(0) += 1;
+        self.max_gate_degree = self.max_gate_degree.max(G::DEGREE);
+
+        // Reserve the remaining rows of a multi-row gate, so that no other gate's wires overlap.
+        for row in 1..num_rows {
+            self.add_gate_no_constants(BufferGate::new(index + row));
+        }
     }
 
     pub fn add_generator<G: WitnessGenerator<C::ScalarField>>(&mut self, generator: G) {
-        self.generators.push(Box::new(generator));
+        self.register_generator(Box::new(generator));
+    }
+
+    /// Registers a witness generator, e.g. for a custom gadget which needs to compute advice
+    /// values.
+    ///
+    /// Generators are not run in the order they are registered. Instead, a generator runs once all
+    /// of its `dependencies()` have been populated, whether by the initial inputs, copy
+    /// constraints, or other generators. A generator may therefore depend on values produced by
+    /// any other generator, regardless of which was registered first.
+    pub fn register_generator(&mut self, generator: Box<dyn WitnessGenerator<C::ScalarField>>) {
+        self.generators.push(generator);
     }
 
     pub fn num_gates(&self) -> usize {
         self.gate_constants.len()
     }
 
+    /// Labels all subsequently added gates with the given label, until the next call, so that
+    /// constraint violations reported by `MockProver` identify the gadget which produced them.
+    ///
+    /// Labels are only recorded with the `debug` feature enabled; otherwise this does nothing.
+    #[allow(unused_variables)]
+    pub fn debug_annotate(&mut self, label: &str) {
+        #[cfg(feature = "debug")]
+        {
+            self.current_label = Some(label.to_string());
+        }
+    }
+
+    /// Returns the maximum unfiltered constraint degree among all gates added so far. See
+    /// `Gate::DEGREE`.
+    pub fn max_gate_degree(&self) -> usize {
+        self.max_gate_degree
+    }
+
     /// Add a copy constraint between two routing targets.
     pub fn copy(&mut self, target_1: Target<C::ScalarField>, target_2: Target<C::ScalarField>) {
         self.copy_constraints.push((target_1, target_2));
     }
 
+    /// Add a copy constraint between each pair of corresponding targets. Panics if the two slices
+    /// have different lengths.
+    pub fn connect_vec(
+        &mut self,
+        targets_1: &[Target<C::ScalarField>],
+        targets_2: &[Target<C::ScalarField>],
+    ) {
+        assert_eq!(
+            targets_1.len(),
+            targets_2.len(),
+            "Cannot connect vectors of different lengths"
+        );
+        for (&target_1, &target_2) in targets_1.iter().zip(targets_2.iter()) {
+            self.copy(target_1, target_2);
+        }
+    }
+
+    /// Asserts that `a[i] == b[i]` for each `i`. Panics if the two slices have different lengths.
+    pub fn assert_equal_vec(
+        &mut self,
+        a: &[Target<C::ScalarField>],
+        b: &[Target<C::ScalarField>],
+    ) {
+        self.connect_vec(a, b);
+    }
+
     /// Enforces a copy constraint between the two targets if the condition is non-zero.
     pub fn conditional_copy(
         &mut self,
@@ -1062,6 +1716,23 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         }
     }
 
+    /// Replaces the copy constraints added so far with an equivalent minimal set, by computing the
+    /// classes of targets they connect using union-find, and connecting each target to a single
+    /// representative of its class. Redundant constraints, such as the third of `copy(a, b)`,
+    /// `copy(b, c)` and `copy(c, a)`, are dropped. Returns the number of classes.
+    ///
+    /// This is done automatically by `build`, but may also be called earlier to release memory used
+    /// by redundant constraints.
+    pub fn finalize_copy_constraints(&mut self) -> usize {
+        let mut union_find = TargetUnionFind::new();
+        for &(a, b) in &self.copy_constraints {
+            union_find.union(a, b);
+        }
+        let num_classes = union_find.num_classes();
+        self.copy_constraints = union_find.into_pairs();
+        num_classes
+    }
+
     // Replace all `PublicInput`-type targets by their corresponding `Wire`-type targets
     // in the copy constraints pairs.
     pub fn route_public_inputs(&mut self, offset: usize) {
@@ -1075,7 +1746,27 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         });
     }
 
+    /// Appends `BufferGate`s, which have no constraints or constants, until the number of gates is
+    /// a power of two. `build` calls this after adding its blinding and public input gates, so
+    /// there is no need to call it before building.
+    pub fn pad_to_power_of_two(&mut self) {
+        while !self.num_gates().is_power_of_two() {
+            self.add_gate_no_constants(BufferGate::new(self.num_gates()));
+        }
+    }
+
     pub fn build(mut self) -> Circuit<C> {
+        // The gates added below aren't part of any annotated gadget.
+        #[cfg(feature = "debug")]
+        {
+            self.current_label = None;
+        }
+
+        // Make sure that each row referenced by `wire_below` holds a regular gate.
+        while self.num_gates() < self.min_num_gates {
+            self.add_gate_no_constants(BufferGate::new(self.num_gates()));
+        }
+
         // Since we will open each polynomial at three points outside of H, we need three random
         // values to ensure nothing is learned from the out-of-H openings.
         for _i in 0..3 {
@@ -1086,6 +1777,7 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         let num_gates_without_pis = self.num_gates();
         self.append_public_input_gates();
         self.route_public_inputs(num_gates_without_pis);
+        self.finalize_copy_constraints();
 
         // Print gate counts.
         info!("Gate counts:");
@@ -1093,18 +1785,15 @@ impl<C: HaloCurve> CircuitBuilder<C> {
             info!("{}: {}", gate, count);
         }
 
-        // Pad to a power of two.
         info!("Total gates before padding: {}", self.num_gates());
-        while !self.num_gates().is_power_of_two() {
-            // Add an empty gate.
-            self.add_gate_no_constants(BufferGate::new(self.num_gates()));
-        }
+        self.pad_to_power_of_two();
         info!("Total gates after padding: {}", self.num_gates());
 
         let degree = self.num_gates();
         let degree_pow = log2_strict(degree);
         let routing_target_partitions = self.get_routing_partitions();
         let wire_partitions = routing_target_partitions.to_wire_partitions();
+        wire_partitions.assert_isolated(&self.unrouted_wires);
         let sigma = wire_partitions.to_sigma();
 
         let CircuitBuilder {
@@ -1112,6 +1801,8 @@ impl<C: HaloCurve> CircuitBuilder<C> {
             public_input_index: num_public_inputs,
             gate_constants,
             generators,
+            #[cfg(feature = "debug")]
+            gate_labels,
             ..
         } = self;
 
@@ -1182,6 +1873,8 @@ impl<C: HaloCurve> CircuitBuilder<C> {
             pedersen_g_msm_precomputation,
             fft_precomputation_n,
             fft_precomputation_8n,
+            #[cfg(feature = "debug")]
+            gate_labels,
         }
     }
 
@@ -1204,3 +1897,628 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         partitions
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::gates::Gate;
+    use crate::{generate_satisfying_witness, ArithmeticGate, BufferGate, CircuitBuilder, ColumnSumGate, Curve, Field, MdsGate, MockProver, PartialWitness, RescueStepAGate, Target, Tweedledee, Tweedledum, Wire, Witness, WitnessGenerator, GRID_WIDTH, NUM_ROUTED_WIRES, NUM_WIRES, RESCUE_SPONGE_WIDTH};
+
+    #[test]
+    fn test_finalize_copy_constraints() {
+        type C = Tweedledum;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let a = builder.add_virtual_target();
+        let b = builder.add_virtual_target();
+        let c = builder.add_virtual_target();
+        let d = builder.add_virtual_target();
+        let e = builder.add_virtual_target();
+        builder.copy(a, b);
+        builder.copy(b, c);
+        builder.copy(c, a);
+        builder.copy(a, a);
+        builder.copy(d, e);
+        builder.copy(e, d);
+
+        assert_eq!(builder.finalize_copy_constraints(), 2);
+        assert_eq!(builder.copy_constraints.len(), 3);
+        // Finalizing again should be a no-op.
+        assert_eq!(builder.finalize_copy_constraints(), 2);
+        assert_eq!(builder.copy_constraints.len(), 3);
+
+        let partitions = builder.get_routing_partitions();
+        let class: HashSet<_> = partitions.get_partition(a).iter().copied().collect();
+        assert_eq!(class, [a, b, c].iter().copied().collect());
+        assert_eq!(partitions.get_partition(d).len(), 2);
+    }
+
+    #[test]
+    fn test_pad_to_power_of_two() {
+        let mut builder = CircuitBuilder::<Tweedledum>::new(128);
+        for _ in 0..100 {
+            let gate = builder.num_gates();
+            builder.add_gate_no_constants(BufferGate::new(gate));
+        }
+        builder.pad_to_power_of_two();
+        assert_eq!(builder.num_gates(), 128);
+        builder.pad_to_power_of_two();
+        assert_eq!(builder.num_gates(), 128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Advice wire")]
+    fn test_copy_unrouted_wire() {
+        type C = Tweedledum;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let x = builder.one_wire();
+        let gate = builder.num_gates();
+        builder.add_gate_no_constants(RescueStepAGate::new(gate));
+        // The root wires which would otherwise be routed should be recorded as unrouted.
+        for i in 0..RESCUE_SPONGE_WIDTH {
+            let root = Wire { gate, input: RescueStepAGate::<C>::wire_root(i) };
+            assert_eq!(builder.unrouted_wires.contains(&root), root.input < NUM_ROUTED_WIRES);
+        }
+
+        let root = Wire { gate, input: RescueStepAGate::<C>::wire_root(0) };
+        builder.copy(Target::Wire(root), x);
+        builder.build();
+    }
+
+    #[test]
+    fn test_register_chained_generators() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        struct SquareGenerator {
+            input: Target<SF>,
+            output: Target<SF>,
+        }
+
+        impl WitnessGenerator<SF> for SquareGenerator {
+            fn dependencies(&self) -> Vec<Target<SF>> {
+                vec![self.input]
+            }
+
+            fn generate(
+                &self,
+                _constants: &[Vec<SF>],
+                witness: &PartialWitness<SF>,
+            ) -> PartialWitness<SF> {
+                let mut result = PartialWitness::new();
+                result.set_target(self.output, witness.get_target(self.input).square());
+                result
+            }
+        }
+
+        let x = SF::rand();
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let ts = builder.add_virtual_targets(3);
+        // Register the second generator first, so that it can only run after the first one.
+        builder.register_generator(Box::new(SquareGenerator {
+            input: ts[1],
+            output: ts[2],
+        }));
+        builder.register_generator(Box::new(SquareGenerator {
+            input: ts[0],
+            output: ts[1],
+        }));
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_target(ts[0], x);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(witness.get_target(ts[2]), x.exp_usize(4));
+    }
+
+    #[test]
+    fn test_connect_vec() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let values = (0..4).map(|_| SF::rand()).collect::<Vec<_>>();
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let a = builder.add_virtual_targets(4);
+        let b = builder.add_virtual_targets(4);
+        builder.connect_vec(&a, &b);
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&a, &values);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(witness.get_targets(&b), values);
+    }
+
+    #[test]
+    fn test_pow_const_base() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let base = SF::rand();
+        let exponent = 0b1011_0110_1001usize;
+        let num_bits = 16;
+        let exponent_bits = (0..num_bits)
+            .map(|i| SF::from_canonical_usize(exponent >> i & 1))
+            .collect::<Vec<_>>();
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let exponent_bit_targets = builder.add_virtual_targets(num_bits);
+        let result = builder.pow_const_base(base, &exponent_bit_targets);
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&exponent_bit_targets, &exponent_bits);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(witness.get_target(result), base.exp_usize(exponent));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let const_mul = SF::rand();
+        let const_add = SF::rand();
+        let values = [SF::rand(), SF::rand(), SF::rand()];
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let [a, b, c] = [
+            builder.add_virtual_target(),
+            builder.add_virtual_target(),
+            builder.add_virtual_target(),
+        ];
+
+        let gates_before = builder.num_gates();
+        let naive = {
+            let const_mul_target = builder.constant_wire(const_mul);
+            let const_add_target = builder.constant_wire(const_add);
+            let product = builder.mul_many(&[const_mul_target, a, b]);
+            let scaled_c = builder.mul(const_add_target, c);
+            builder.add(product, scaled_c)
+        };
+        let naive_gates = builder.num_gates() - gates_before;
+
+        let gates_before = builder.num_gates();
+        let fused = builder.arithmetic(const_mul, a, b, const_add, c);
+        assert_eq!(builder.num_gates() - gates_before, 1);
+        assert!(naive_gates > 1);
+
+        let circuit = builder.build();
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&[a, b, c], &values);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        let expected = const_mul * values[0] * values[1] + const_add * values[2];
+        assert_eq!(witness.get_target(naive), expected);
+        assert_eq!(witness.get_target(fused), expected);
+    }
+
+    #[test]
+    fn test_mul_add_const() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let values = [SF::rand(), SF::rand()];
+        let const_add = SF::rand();
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let a = builder.add_virtual_target();
+        let b = builder.add_virtual_target();
+        builder.one_wire();
+
+        let gates_before = builder.num_gates();
+        let fused = builder.mul_add_const(a, b, const_add);
+        let fused_gates = builder.num_gates() - gates_before;
+
+        let gates_before = builder.num_gates();
+        let const_add_target = builder.constant_wire(const_add);
+        let unfused = builder.mul_add(a, b, const_add_target);
+        let unfused_gates = builder.num_gates() - gates_before;
+
+        assert_eq!(fused_gates, 1);
+        assert_eq!(unfused_gates, 2);
+
+        let circuit = builder.build();
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&[a, b], &values);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        let expected = values[0] * values[1] + const_add;
+        assert_eq!(witness.get_target(fused), expected);
+        assert_eq!(witness.get_target(unfused), expected);
+    }
+
+    #[test]
+    fn test_sub_const_and_const_sub() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let x_value = SF::rand();
+        let c = SF::rand();
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let x = builder.add_virtual_target();
+        builder.one_wire();
+        builder.zero_wire();
+
+        let gates_before = builder.num_gates();
+        let x_minus_c = builder.sub_const(x, c);
+        let c_minus_x = builder.const_sub(c, x);
+        let fused_gates = builder.num_gates() - gates_before;
+
+        let gates_before = builder.num_gates();
+        let c_target = builder.constant_wire(c);
+        let x_minus_c_unfused = builder.sub(x, c_target);
+        let c_minus_x_unfused = builder.sub(c_target, x);
+        let unfused_gates = builder.num_gates() - gates_before;
+
+        assert_eq!(fused_gates, 2);
+        assert_eq!(unfused_gates, 3);
+        assert_eq!(builder.sub_const(x, SF::ZERO), x);
+
+        let circuit = builder.build();
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_target(x, x_value);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(witness.get_target(x_minus_c), x_value - c);
+        assert_eq!(witness.get_target(x_minus_c_unfused), x_value - c);
+        assert_eq!(witness.get_target(c_minus_x), c - x_value);
+        assert_eq!(witness.get_target(c_minus_x_unfused), c - x_value);
+    }
+
+    #[test]
+    fn test_random_linear_combination() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let values = (0..5).map(|_| SF::rand()).collect::<Vec<_>>();
+        let challenge_value = SF::rand();
+        let expected = values
+            .iter()
+            .rev()
+            .fold(SF::ZERO, |acc, &value| acc * challenge_value + value);
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let terms = builder.add_virtual_targets(values.len());
+        let challenge = builder.add_virtual_target();
+        let gates_before = builder.num_gates();
+        let combination = builder.random_linear_combination(&terms, challenge);
+        assert_eq!(builder.num_gates() - gates_before, values.len() - 1);
+        let empty = builder.random_linear_combination(&[], challenge);
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&terms, &values);
+        partial_witness.set_target(challenge, challenge_value);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(witness.get_target(combination), expected);
+        assert_eq!(witness.get_target(empty), SF::ZERO);
+    }
+
+    #[test]
+    fn test_constant_wire_dedup() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let one = builder.one_wire();
+        let num_gates = builder.num_gates();
+        assert_eq!(builder.constant_wire(SF::from_canonical_usize(1)), one);
+        assert_eq!(builder.constant_wire(SF::ZERO - SF::NEG_ONE), one);
+        assert_eq!(builder.constant_wire(SF::from_canonical_u64(1)), one);
+        assert_eq!(builder.num_gates(), num_gates);
+
+        let two = builder.constant_wire(SF::TWO);
+        assert_ne!(two, one);
+        assert_eq!(builder.constant_wire(SF::ONE + SF::ONE), two);
+    }
+
+    #[test]
+    fn test_constant_as_target() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let const_mul = SF::rand();
+        let const_add = SF::rand();
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let x = builder.add_virtual_target();
+        let gate = builder.num_gates();
+        builder.arithmetic(const_mul, x, x, const_add, x);
+        let prefix_len = ArithmeticGate::<C>::PREFIX.len();
+        let const_mul_target = builder.constant_as_target::<ArithmeticGate<C>>(gate, prefix_len);
+        let const_add_target = builder.constant_as_target::<ArithmeticGate<C>>(gate, prefix_len + 1);
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_target(x, SF::rand());
+        let witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(witness.get_target(const_mul_target), const_mul);
+        assert_eq!(witness.get_target(const_add_target), const_add);
+    }
+
+    #[test]
+    #[should_panic(expected = "is part of the ArithmeticGate prefix")]
+    fn test_constant_as_target_rejects_prefix() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let x = builder.add_virtual_target();
+        let gate = builder.num_gates();
+        builder.arithmetic(SF::ONE, x, x, SF::ONE, x);
+        builder.constant_as_target::<ArithmeticGate<C>>(gate, 0);
+    }
+
+    #[test]
+    fn test_multi_row_gate() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let values = (0..RESCUE_SPONGE_WIDTH)
+            .map(|_| SF::rand())
+            .collect::<Vec<_>>();
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let inputs = builder.add_virtual_targets(RESCUE_SPONGE_WIDTH);
+        // Adding the gate also reserves its second row, which holds the outputs.
+        let gate = builder.num_gates();
+        let outputs = builder.mds_layer(&inputs);
+        assert_eq!(builder.num_gates(), gate + 2);
+        assert_eq!(builder.gate_counts["BufferGate"], 1);
+        for (i, &output) in outputs.iter().enumerate() {
+            let input = MdsGate::<C>::wire_output(i);
+            assert_eq!(output, Target::Wire(Wire { gate: gate + 1, input }));
+        }
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&inputs, &values);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(witness.get_targets(&outputs), MdsGate::<C>::mix(&values));
+    }
+
+    #[test]
+    fn test_max_gate_degree() {
+        let mut builder = CircuitBuilder::<Tweedledum>::new(128);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        builder.mul(x, y);
+        assert_eq!(builder.max_gate_degree(), 3);
+
+        builder.rescue_hash_n_to_1(&[x, y]);
+        assert_eq!(builder.max_gate_degree(), 5);
+    }
+
+    #[test]
+    fn test_wire_below() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        // A ColumnSumGate outputs its sum to the row below, which hasn't been added yet, so
+        // wire_below must reserve it.
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let acc = builder.add_virtual_target();
+        let value = builder.add_virtual_target();
+        builder.add_gate_no_constants(ColumnSumGate::new(0));
+        let acc_old = Target::Wire(Wire { gate: 0, input: ColumnSumGate::<C>::WIRE_ACC });
+        let value_wire = Target::Wire(Wire { gate: 0, input: ColumnSumGate::<C>::WIRE_VALUE });
+        builder.copy(acc, acc_old);
+        builder.copy(value, value_wire);
+        let sum = builder.wire_below(0, ColumnSumGate::<C>::WIRE_ACC);
+        assert_eq!(sum, Target::Wire(Wire { gate: GRID_WIDTH, input: 0 }));
+        let circuit = builder.build();
+        assert!(circuit.num_gates_without_pis > GRID_WIDTH);
+
+        let (acc_value, value_value) = (SF::rand(), SF::rand());
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_target(acc, acc_value);
+        partial_witness.set_target(value, value_value);
+        let partial_witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(partial_witness.get_target(sum), acc_value + value_value);
+
+        let witness = Witness::from_partial(&partial_witness, circuit.degree());
+        let mock_prover = MockProver::new(&circuit);
+        assert_eq!(mock_prover.check_witness::<Tweedledee>(&witness), vec![]);
+
+        // The constraint reads the accumulator from the row below, not the row to the right.
+        let tamper = |gate: usize| {
+            let values = (0..circuit.degree())
+                .map(|g| {
+                    (0..NUM_WIRES)
+                        .map(|i| {
+                            let v = witness.get_indices(g, i);
+                            if (g, i) == (gate, ColumnSumGate::<C>::WIRE_ACC) {
+                                v + SF::ONE
+                            } else {
+                                v
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            Witness::new(values)
+        };
+        let violations = mock_prover.check_witness::<Tweedledee>(&tamper(GRID_WIDTH));
+        assert_eq!(violations.len(), 1);
+        assert_eq!((violations[0].gate, violations[0].gate_name), (0, "ColumnSumGate"));
+        assert_eq!(mock_prover.check_witness::<Tweedledee>(&tamper(1)), vec![]);
+
+        assert!(circuit
+            .to_debug_string::<Tweedledee>()
+            .contains("b[0] = w[0] + w[1]"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_assert_equal_vec_length_mismatch() {
+        let mut builder = CircuitBuilder::<Tweedledum>::new(128);
+        let a = builder.add_virtual_targets(4);
+        let b = builder.add_virtual_targets(3);
+        builder.assert_equal_vec(&a, &b);
+    }
+
+    #[test]
+    fn test_rotate_and_shift() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let num_bits = 8;
+        let pattern = 0b1001_0110u64;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let bits = builder.add_virtual_targets(num_bits);
+        builder.zero_wire();
+        let gates_before = builder.num_gates();
+        let rotated = builder.rotate_left(&bits, 3);
+        let rotated_fully = builder.rotate_left(&bits, num_bits + 3);
+        let shifted_left = builder.shift_left(&bits, 3);
+        let shifted_right = builder.shift_right(&bits, 3);
+        let shifted_out = builder.shift_right(&bits, num_bits + 1);
+        assert_eq!(builder.num_gates(), gates_before);
+        assert_eq!(rotated, rotated_fully);
+
+        let sums = [
+            builder.le_sum(&bits),
+            builder.le_sum(&rotated),
+            builder.le_sum(&shifted_left),
+            builder.le_sum(&shifted_right),
+            builder.le_sum(&shifted_out),
+        ];
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        for (i, &bit) in bits.iter().enumerate() {
+            partial_witness.set_target(bit, SF::from_canonical_u64(pattern >> i & 1));
+        }
+        let witness = circuit.generate_partial_witness(partial_witness);
+        let expected = [
+            pattern,
+            (pattern << 3 | pattern >> (num_bits - 3)) & 0xff,
+            (pattern << 3) & 0xff,
+            pattern >> 3,
+            0,
+        ];
+        for (&sum, &value) in sums.iter().zip(&expected) {
+            assert_eq!(witness.get_target(sum), SF::from_canonical_u64(value));
+        }
+    }
+
+    #[test]
+    fn test_grand_product_permutation() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let a_values: Vec<SF> = (0..5).map(|_| SF::rand()).collect();
+        let permuted = vec![a_values[3], a_values[0], a_values[4], a_values[1], a_values[2]];
+        let mut not_permuted = permuted.clone();
+        not_permuted[2] = SF::rand();
+
+        for (b_values, is_permutation) in vec![(permuted, true), (not_permuted, false)] {
+            let mut builder = CircuitBuilder::<C>::new(128);
+            let a = builder.add_virtual_targets(a_values.len());
+            let b = builder.add_virtual_targets(b_values.len());
+            let challenge = builder.add_virtual_target();
+            let numerator: Vec<_> = a.iter().map(|&x| builder.add(x, challenge)).collect();
+            let denominator: Vec<_> = b.iter().map(|&x| builder.add(x, challenge)).collect();
+            let product = builder.grand_product(&numerator, &denominator);
+            let circuit = builder.build();
+
+            let mut partial_witness = PartialWitness::new();
+            partial_witness.set_targets(&a, &a_values);
+            partial_witness.set_targets(&b, &b_values);
+            partial_witness.set_target(challenge, SF::rand());
+            let witness = circuit.generate_partial_witness(partial_witness);
+            assert_eq!(witness.get_target(product) == SF::ONE, is_permutation);
+        }
+    }
+
+    #[test]
+    fn test_assert_permutation_with_challenge() {
+        type C = Tweedledee;
+        type SF = <C as Curve>::ScalarField;
+
+        let a_values: Vec<SF> = (0..4).map(|_| SF::rand()).collect();
+        let b_values = vec![a_values[2], a_values[3], a_values[1], a_values[0]];
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let a = builder.add_virtual_targets(a_values.len());
+        let b = builder.add_virtual_targets(b_values.len());
+        let challenge = builder.add_virtual_target();
+        let product = builder.assert_permutation_with_challenge(&a, &b, challenge);
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&a, &a_values);
+        partial_witness.set_targets(&b, &b_values);
+        partial_witness.set_target(challenge, SF::rand());
+        let witness = generate_satisfying_witness::<C, Tweedledum>(&circuit, partial_witness);
+        assert_eq!(witness.get_target(product), SF::ONE);
+    }
+
+    #[test]
+    fn test_bool_constant_in_select() {
+        type C = Tweedledee;
+        type SF = <C as Curve>::ScalarField;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let flag = builder.add_virtual_target();
+        let constant_flag = builder.bool_constant(true);
+        assert_eq!(constant_flag, builder.one_wire());
+        builder.zero_wire();
+
+        // A general flag must be asserted to be binary, which costs a gate.
+        let gates_before = builder.num_gates();
+        builder.assert_binary(flag);
+        let general = builder.select(flag, x, y);
+        let general_gates = builder.num_gates() - gates_before;
+        // Asserting it again is free.
+        let gates_before = builder.num_gates();
+        builder.assert_binary(flag);
+        assert_eq!(builder.num_gates(), gates_before);
+
+        let gates_before = builder.num_gates();
+        builder.assert_binary(constant_flag);
+        let selected = builder.select(constant_flag, x, y);
+        let constant_gates = builder.num_gates() - gates_before;
+        assert!(constant_gates < general_gates);
+        builder.copy(selected, x);
+
+        let circuit = builder.build();
+        let (x_value, y_value) = (SF::rand(), SF::rand());
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_target(x, x_value);
+        partial_witness.set_target(y, y_value);
+        partial_witness.set_target(flag, SF::ZERO);
+        let witness = generate_satisfying_witness::<C, Tweedledum>(&circuit, partial_witness);
+        assert_eq!(witness.get_target(general), y_value);
+    }
+
+    #[test]
+    fn test_to_debug_string() {
+        type C = Tweedledee;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let x = builder.add_virtual_target();
+        let x_squared = builder.square(x);
+        builder.rescue_hash_n_to_1(&[x_squared]);
+        let circuit = builder.build();
+
+        let debug_string = circuit.to_debug_string::<Tweedledum>();
+        assert!(debug_string.contains("gate 0: "));
+        assert!(debug_string.contains("ArithmeticGate"));
+        assert!(debug_string.contains("w[3] = c[5] * w[0] * w[1] + c[6] * w[2]"));
+        assert!(debug_string.contains("RescueStepAGate"));
+        assert!(debug_string.contains("w[4]^5 = w[0]"));
+        assert!(debug_string.contains("RescueStepBGate"));
+        assert!(debug_string.contains(" ~ "));
+        assert_eq!(
+            debug_string.matches("gate ").count(),
+            circuit.gate_constants.len()
+        );
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::type_complexity)]
 use crate::plonk_util::halo_n;
-use crate::{blake_hash_base_field_to_curve, AffinePoint, Base4SumGate, BufferGate, CircuitBuilder, Curve, CurveAddGate, CurveDblGate, CurveEndoGate, Field, HaloCurve, PartialWitness, Target, Wire, WitnessGenerator};
+use crate::{blake_hash_base_field_to_curve, field_to_biguint, AffinePoint, Base4SumGate, BufferGate, CircuitBuilder, Curve, CurveAddGate, CurveDblGate, CurveEndoGate, Field, HaloCurve, PartialWitness, Target, Wire, WitnessGenerator};
+use num::{BigUint, Integer, One, Zero};
 use std::marker::PhantomData;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -198,6 +199,47 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         self.curve_msm::<InnerC>(&[mul])
     }
 
+    /// Computes `[scalar] p` for a constant scalar, using double-and-add over the non-adjacent
+    /// form of the scalar, so that each nonzero digit costs one addition or subtraction and zero
+    /// digits cost nothing beyond a doubling.
+    ///
+    /// Since `curve_add` uses incomplete addition, `p` must have prime order. If `scalar` exceeds
+    /// half the order, we use `-scalar` and negate the result instead; each intermediate multiple
+    /// is then far from `[±1] p`, so no addition is exceptional.
+    pub fn scalar_mul_by_constant<InnerC: Curve<BaseField = C::ScalarField>>(
+        &mut self,
+        p: AffinePointTarget<InnerC>,
+        scalar: InnerC::ScalarField,
+    ) -> AffinePointTarget<InnerC> {
+        assert!(scalar.is_nonzero(), "Cannot multiply by zero");
+
+        let negate = field_to_biguint(scalar) > InnerC::ScalarField::modulus() >> 1;
+        let abs_scalar = if negate { -scalar } else { scalar };
+        let digits = non_adjacent_form(&field_to_biguint(abs_scalar));
+
+        let neg_p = if digits.contains(&-1) {
+            Some(self.curve_neg::<InnerC>(p))
+        } else {
+            None
+        };
+        // The most significant digit is always 1, so we start the accumulator at p.
+        let mut acc = p;
+        for &digit in digits.iter().rev().skip(1) {
+            acc = self.curve_double::<InnerC>(acc);
+            match digit {
+                1 => acc = self.curve_add::<InnerC>(acc, p),
+                -1 => acc = self.curve_add::<InnerC>(acc, neg_p.unwrap()),
+                _ => (),
+            }
+        }
+
+        if negate {
+            self.curve_neg::<InnerC>(acc)
+        } else {
+            acc
+        }
+    }
+
     /// Computes `[n(s)] p`.
     pub fn curve_mul_endo<InnerC: HaloCurve<BaseField = C::ScalarField>>(
         &mut self,
@@ -281,6 +323,16 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         }
     }
 
+    /// Computes `sum_i [s_i] P_i` for variable scalars and points, using a single double-and-add
+    /// loop shared by all terms. This is the `<s, G>` computation needed to reduce an IPA opening
+    /// in a recursive verifier.
+    ///
+    /// Each of the `BITS - 1` iterations uses one `CurveAddGate` per term and one `CurveDblGate`,
+    /// so an MSM of `n` terms costs about `254 (n + 1)` gates with a 255-bit field, plus a handful
+    /// of gates to subtract the filler point at the end; see `msm` for the exact count. The
+    /// scalars' bits are constrained by the addition gates' scalar accumulators, so the binary
+    /// decompositions cost no extra gates.
+    ///
     /// Note: This assumes the most significant bit of each scalar is unset. This occurs with high
     /// probability if the field size is slightly larger than a power of two and the scalars are
     /// uniformly random.
@@ -409,6 +461,32 @@ impl<C: HaloCurve> CircuitBuilder<C> {
         acc
     }
 
+    /// Computes `sum_i [s_i] P_i` like `curve_msm`, but takes the scalars and points as separate
+    /// slices, with each point given by its `(x, y)` coordinates, and returns the `(x, y)`
+    /// coordinates of the result.
+    ///
+    /// This costs exactly `(BITS - 1) (n + 1) + 7` gates for `n` terms: the double-and-add loop,
+    /// then four `ConstantGate`s for the filler point's coordinates, an `ArithmeticGate` to negate
+    /// it and a `CurveAddGate` and `BufferGate` to subtract it. If the circuit has no constant
+    /// wires for 0, 1 or -1 yet, subtracting the filler adds up to three more `ConstantGate`s.
+    pub fn msm<InnerC: Curve<BaseField = C::ScalarField>>(
+        &mut self,
+        scalars: &[Target<C::ScalarField>],
+        points: &[(Target<C::ScalarField>, Target<C::ScalarField>)],
+    ) -> (Target<C::ScalarField>, Target<C::ScalarField>) {
+        assert_eq!(scalars.len(), points.len(), "Expected one scalar per point");
+        let parts = scalars
+            .iter()
+            .zip(points)
+            .map(|(&scalar, &(x, y))| CurveMulOp {
+                scalar,
+                point: AffinePointTarget { x, y },
+            })
+            .collect::<Vec<_>>();
+        let result = self.curve_msm::<InnerC>(&parts);
+        (result.x, result.y)
+    }
+
     /// Like `curve_msm`, but uses the endomorphism described in the Halo paper.
     pub fn curve_msm_endo<InnerC: HaloCurve<BaseField = C::ScalarField>>(
         &mut self,
@@ -628,11 +706,36 @@ impl<C: HaloCurve> CircuitBuilder<C> {
     }
 }
 
+/// Returns the little-endian non-adjacent form of `k`, i.e. digits in `{-1, 0, 1}` such that no
+/// two adjacent digits are nonzero. The most significant digit is 1 if `k` is nonzero.
+fn non_adjacent_form(k: &BigUint) -> Vec<i8> {
+    let mut k = k.clone();
+    let mut digits = Vec::new();
+    while !k.is_zero() {
+        if k.is_odd() {
+            // Pick the digit which makes k - digit divisible by 4.
+            if (&k % 4u32).is_one() {
+                digits.push(1);
+                k -= 1u32;
+            } else {
+                digits.push(-1);
+                k += 1u32;
+            }
+        } else {
+            digits.push(0);
+        }
+        k >>= 1;
+    }
+    digits
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
+    use num::BigUint;
 
-    use crate::{verify_proof, CircuitBuilder, Curve, CurveMulOp, Field, PartialWitness, Tweedledee, Tweedledum};
+    use crate::circuit_curve::non_adjacent_form;
+    use crate::{blake_hash_usize_to_curve, generate_satisfying_witness, verify_proof, CircuitBuilder, Curve, CurveMulOp, Field, PartialWitness, Tweedledee, Tweedledum};
 
     #[test]
     // TODO: This fails because curve_mul_endo has a flaw.
@@ -668,4 +771,77 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_non_adjacent_form() {
+        // 119 = 128 - 8 - 1.
+        assert_eq!(
+            non_adjacent_form(&BigUint::from(119u32)),
+            vec![-1, 0, 0, -1, 0, 0, 0, 1]
+        );
+        assert_eq!(non_adjacent_form(&BigUint::from(0u32)), Vec::<i8>::new());
+
+        for k in 1u32..1000 {
+            let digits = non_adjacent_form(&BigUint::from(k));
+            assert_eq!(digits.last(), Some(&1));
+            assert!(digits.windows(2).all(|w| w[0] == 0 || w[1] == 0));
+            let value: i64 = digits
+                .iter()
+                .enumerate()
+                .map(|(i, &d)| (d as i64) << i)
+                .sum();
+            assert_eq!(value, k as i64);
+        }
+    }
+
+    #[test]
+    fn test_scalar_mul_by_constant_matches_native() {
+        type C = Tweedledee;
+        type InnerC = Tweedledum;
+        type ISF = <InnerC as Curve>::ScalarField;
+
+        // Include scalars near the order, which are handled by negating the result.
+        let scalars = [
+            ISF::ONE,
+            ISF::from_canonical_u64(119),
+            -ISF::ONE,
+            -ISF::TWO,
+            ISF::rand(),
+        ];
+
+        for &scalar in &scalars {
+            for i in 0..2 {
+                let point = blake_hash_usize_to_curve::<InnerC>(i);
+                let expected = (InnerC::convert(scalar) * point.to_projective()).to_affine();
+
+                let mut builder = CircuitBuilder::<C>::new(128);
+                let p = builder.add_virtual_point_target::<InnerC>();
+                let result = builder.scalar_mul_by_constant::<InnerC>(p, scalar);
+                let circuit = builder.build();
+
+                let mut partial_witness = PartialWitness::new();
+                partial_witness.set_point_target(p, point);
+                let witness = generate_satisfying_witness::<C, InnerC>(&circuit, partial_witness);
+                assert_eq!(witness.get_point_target(result), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_scalar_mul_by_constant_skips_zero_digits() {
+        type C = Tweedledee;
+        type InnerC = Tweedledum;
+        type ISF = <InnerC as Curve>::ScalarField;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        builder.zero_wire();
+        builder.one_wire();
+        builder.neg_one_wire();
+        let p = builder.add_virtual_point_target::<InnerC>();
+        let gates_before = builder.num_gates();
+        builder.scalar_mul_by_constant::<InnerC>(p, ISF::from_canonical_u64(119));
+        // 119 = 128 - 8 - 1, so we need 7 doublings, 2 additions of two gates each, and one gate
+        // to negate p. Plain double-and-add would need 5 additions.
+        assert_eq!(builder.num_gates() - gates_before, 7 + 2 * 2 + 1);
+    }
 }
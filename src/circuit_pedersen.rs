@@ -0,0 +1,141 @@
+use crate::pedersen::{pedersen_chunk_base, pedersen_chunks, pedersen_num_bases, PEDERSEN_CHUNKS_PER_BASE, PEDERSEN_CHUNK_BITS};
+use crate::{AffinePoint, AffinePointTarget, CircuitBuilder, Curve, Field, HaloCurve, ProjectivePoint, Target};
+
+impl<C: HaloCurve> CircuitBuilder<C> {
+    /// Computes the same Pedersen hash as `pedersen_hash_bits`, asserting that each input is
+    /// binary.
+    ///
+    /// Since the bases are fixed, the multiples of each base which a chunk can select are
+    /// precomputed, so each chunk costs a few arithmetic gates to select its point, plus one curve
+    /// addition.
+    pub fn pedersen_hash<InnerC: Curve<BaseField = C::ScalarField>>(
+        &mut self,
+        bits: &[Target<C::ScalarField>],
+        bases: &[AffinePoint<InnerC>],
+    ) -> AffinePointTarget<InnerC> {
+        assert!(!bits.is_empty(), "Cannot hash an empty message");
+        assert!(
+            bases.len() >= pedersen_num_bases(bits.len()),
+            "Not enough bases for a message of {} bits",
+            bits.len()
+        );
+        for &bit in bits {
+            self.assert_binary(bit);
+        }
+
+        let zero = self.zero_wire();
+        let mut acc = None;
+        for (t, chunk) in pedersen_chunks(bits, zero).into_iter().enumerate() {
+            let base = bases[t / PEDERSEN_CHUNKS_PER_BASE];
+            let point = self.pedersen_select_multiple(
+                chunk,
+                pedersen_chunk_base(base, t % PEDERSEN_CHUNKS_PER_BASE),
+            );
+            acc = Some(match acc {
+                None => point,
+                Some(acc) => self.curve_add::<InnerC>(acc, point),
+            });
+        }
+        acc.unwrap()
+    }
+
+    /// Returns `[e] point`, where `e` is the signed multiple in `{-4, ..., -1, 1, ..., 4}` encoded
+    /// by the given chunk, as in `pedersen_chunk_value`.
+    fn pedersen_select_multiple<InnerC: Curve<BaseField = C::ScalarField>>(
+        &mut self,
+        chunk: [Target<C::ScalarField>; PEDERSEN_CHUNK_BITS],
+        point: ProjectivePoint<InnerC>,
+    ) -> AffinePointTarget<InnerC> {
+        let [b_0, b_1, b_sign] = chunk;
+        let multiples: Vec<ProjectivePoint<InnerC>> = (1..=4u64)
+            .map(|k| InnerC::convert(InnerC::ScalarField::from_canonical_u64(k)) * point)
+            .collect();
+        let multiples = ProjectivePoint::batch_to_affine(&multiples);
+        let xs = [
+            multiples[0].x,
+            multiples[1].x,
+            multiples[2].x,
+            multiples[3].x,
+        ];
+        let ys = [
+            multiples[0].y,
+            multiples[1].y,
+            multiples[2].y,
+            multiples[3].y,
+        ];
+
+        let b_0_b_1 = self.mul(b_0, b_1);
+        let x = self.pedersen_lookup(b_0, b_1, b_0_b_1, xs);
+        let y_abs = self.pedersen_lookup(b_0, b_1, b_0_b_1, ys);
+        // y = y_abs (1 - 2 b_sign) = -2 y_abs b_sign + y_abs.
+        let y = self.arithmetic(
+            -C::ScalarField::TWO,
+            y_abs,
+            b_sign,
+            C::ScalarField::ONE,
+            y_abs,
+        );
+        AffinePointTarget { x, y }
+    }
+
+    /// Returns `values[b_0 + 2 b_1]`, using the multilinear interpolation
+    /// `v_00 + (v_10 - v_00) b_0 + (v_01 - v_00) b_1 + (v_11 - v_10 - v_01 + v_00) b_0 b_1`.
+    fn pedersen_lookup(
+        &mut self,
+        b_0: Target<C::ScalarField>,
+        b_1: Target<C::ScalarField>,
+        b_0_b_1: Target<C::ScalarField>,
+        values: [C::ScalarField; 4],
+    ) -> Target<C::ScalarField> {
+        let [v_00, v_10, v_01, v_11] = values;
+        let one = self.one_wire();
+        let b_0_terms = self.arithmetic(v_11 - v_10 - v_01 + v_00, b_0_b_1, one, v_10 - v_00, b_0);
+        let b_terms = self.arithmetic(v_01 - v_00, b_1, one, C::ScalarField::ONE, b_0_terms);
+        self.arithmetic(C::ScalarField::ONE, b_terms, one, v_00, one)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{blake_hash_usize_to_curve, generate_satisfying_witness, pedersen_hash_bits, AffinePoint, CircuitBuilder, Curve, Field, PartialWitness, Tweedledee, Tweedledum, PEDERSEN_CHUNKS_PER_BASE, PEDERSEN_CHUNK_BITS};
+
+    #[test]
+    fn test_pedersen_hash_matches_native() {
+        type C = Tweedledee;
+        type InnerC = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let bases: Vec<AffinePoint<InnerC>> = (0..2).map(blake_hash_usize_to_curve).collect();
+        // Cover every chunk value, a partial final chunk, and a message spanning two bases.
+        let mut messages: Vec<Vec<bool>> = (0..8)
+            .map(|i| {
+                (0..PEDERSEN_CHUNK_BITS)
+                    .map(|j| (i >> j) & 1 == 1)
+                    .collect()
+            })
+            .collect();
+        messages.push(vec![true]);
+        messages.push(vec![true, true, false, true, false]);
+        messages.push(
+            (0..PEDERSEN_CHUNK_BITS * PEDERSEN_CHUNKS_PER_BASE + 4)
+                .map(|i| i % 5 < 2)
+                .collect(),
+        );
+
+        for message in messages {
+            let expected = pedersen_hash_bits(&message, &bases);
+
+            let mut builder = CircuitBuilder::<C>::new(128);
+            let bits = builder.add_virtual_targets(message.len());
+            let hash = builder.pedersen_hash::<InnerC>(&bits, &bases);
+            let circuit = builder.build();
+
+            let mut partial_witness = PartialWitness::new();
+            for (&bit, &value) in bits.iter().zip(&message) {
+                partial_witness.set_target(bit, SF::from_canonical_bool(value));
+            }
+            let witness = generate_satisfying_witness::<C, InnerC>(&circuit, partial_witness);
+            assert_eq!(witness.get_point_target(hash), expected);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{CircuitBuilder, Field, HaloCurve, Target, RESCUE_SPONGE_RATE, RESCUE_SPONGE_WIDTH};
+
+/// An in-circuit Rescue sponge, for protocols which need more control than the one-shot
+/// `rescue_sponge`, e.g. access to the state between absorbing and squeezing.
+///
+/// A sponge which absorbs some inputs and then squeezes `n` outputs produces the same outputs as
+/// `rescue_sponge(inputs, n)`. Note that each call to `absorb` pads its inputs to a multiple of the
+/// rate, so two calls to `absorb` are only equivalent to a single call with the concatenated inputs
+/// if the first call's inputs fill whole chunks. Similarly, squeezing part of a chunk discards
+/// the rest of it.
+pub struct RescueSponge<C: HaloCurve> {
+    state: Vec<Target<C::ScalarField>>,
+    /// Whether the rate portion of `state` has already been squeezed, in which case the state must
+    /// be permuted before it is used again.
+    squeezed: bool,
+}
+
+impl<C: HaloCurve> RescueSponge<C> {
+    pub fn new(builder: &mut CircuitBuilder<C>) -> Self {
+        Self::new_with_domain(builder, 0)
+    }
+
+    /// Returns a new sponge whose capacity element is initialized to the given domain tag, as in
+    /// `rescue_sponge_with_domain`.
+    pub fn new_with_domain(builder: &mut CircuitBuilder<C>, domain: u64) -> Self {
+        let zero = builder.zero_wire();
+        let mut state = vec![zero; RESCUE_SPONGE_WIDTH];
+        state[RESCUE_SPONGE_WIDTH - 1] =
+            builder.constant_wire(C::ScalarField::from_canonical_u64(domain));
+        RescueSponge {
+            state,
+            squeezed: false,
+        }
+    }
+
+    /// Returns the current sponge state.
+    pub fn state(&self) -> Vec<Target<C::ScalarField>> {
+        self.state.clone()
+    }
+
+    pub fn absorb(&mut self, builder: &mut CircuitBuilder<C>, inputs: &[Target<C::ScalarField>]) {
+        self.permute_if_squeezed(builder);
+        for input_chunk in inputs.chunks(RESCUE_SPONGE_RATE) {
+            for i in 0..input_chunk.len() {
+                self.state[i] = builder.add(self.state[i], input_chunk[i]);
+            }
+            self.state = builder.rescue_permutation(&self.state);
+        }
+    }
+
+    pub fn squeeze(
+        &mut self,
+        builder: &mut CircuitBuilder<C>,
+        num_outputs: usize,
+    ) -> Vec<Target<C::ScalarField>> {
+        let mut outputs = Vec::with_capacity(num_outputs);
+        while outputs.len() < num_outputs {
+            self.permute_if_squeezed(builder);
+            let remaining = num_outputs - outputs.len();
+            outputs.extend_from_slice(&self.state[..remaining.min(RESCUE_SPONGE_RATE)]);
+            self.squeezed = true;
+        }
+        outputs
+    }
+
+    fn permute_if_squeezed(&mut self, builder: &mut CircuitBuilder<C>) {
+        if self.squeezed {
+            self.state = builder.rescue_permutation(&self.state);
+            self.squeezed = false;
+        }
+    }
+}
+
+impl<C: HaloCurve> CircuitBuilder<C> {
+    /// Returns a new `RescueSponge` with an all-zero state.
+    pub fn rescue_sponge_state(&mut self) -> RescueSponge<C> {
+        RescueSponge::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_satisfying_witness, recommended_rounds, rescue_sponge, rescue_sponge_rounds, rescue_sponge_with_domain, CircuitBuilder, Curve, Field, PartialWitness, RescueSponge, Tweedledee, Tweedledum, RESCUE_SPONGE_WIDTH};
+
+    #[test]
+    fn test_absorb_squeeze_matches_rescue_sponge() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let inputs = (0..5).map(|_| SF::rand()).collect::<Vec<_>>();
+        let expected_outputs = rescue_sponge(inputs.clone(), 4, 128);
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let input_targets = builder.add_virtual_targets(inputs.len());
+        let mut sponge = builder.rescue_sponge_state();
+        sponge.absorb(&mut builder, &input_targets);
+        let state = sponge.state();
+        let outputs = sponge.squeeze(&mut builder, 4);
+
+        let mut chunked_sponge = builder.rescue_sponge_state();
+        chunked_sponge.absorb(&mut builder, &input_targets);
+        let mut chunked_outputs = chunked_sponge.squeeze(&mut builder, 3);
+        chunked_outputs.extend(chunked_sponge.squeeze(&mut builder, 3));
+        let hash_outputs = builder.rescue_sponge(&input_targets, 6);
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&input_targets, &inputs);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(witness.get_targets(&outputs), expected_outputs);
+        assert_eq!(witness.get_targets(&state[..3]), expected_outputs[..3]);
+        assert_eq!(
+            witness.get_targets(&chunked_outputs),
+            witness.get_targets(&hash_outputs)
+        );
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let inputs = (0..4).map(|_| SF::rand()).collect::<Vec<_>>();
+        let expected_0 = rescue_sponge_with_domain(inputs.clone(), 2, 0, 128);
+        let expected_1 = rescue_sponge_with_domain(inputs.clone(), 2, 1, 128);
+        let expected_2 = rescue_sponge_with_domain(inputs.clone(), 2, 2, 128);
+        assert_eq!(expected_0, rescue_sponge(inputs.clone(), 2, 128));
+        assert_ne!(expected_0, expected_1);
+        assert_ne!(expected_1, expected_2);
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let input_targets = builder.add_virtual_targets(inputs.len());
+        let outputs_1 = builder.rescue_sponge_with_domain(&input_targets, 2, 1);
+        let mut sponge = RescueSponge::new_with_domain(&mut builder, 2);
+        sponge.absorb(&mut builder, &input_targets);
+        let outputs_2 = sponge.squeeze(&mut builder, 2);
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&input_targets, &inputs);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(witness.get_targets(&outputs_1), expected_1);
+        assert_eq!(witness.get_targets(&outputs_2), expected_2);
+    }
+
+    #[test]
+    fn test_rescue_hash_rounds() {
+        type C = Tweedledee;
+        type InnerC = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let inputs = (0..5).map(|_| SF::rand()).collect::<Vec<_>>();
+        let rounds = 2;
+        let expected = rescue_sponge_rounds(inputs.clone(), 4, rounds);
+        assert_ne!(expected, rescue_sponge(inputs.clone(), 4, 128));
+        assert_eq!(
+            rescue_sponge_rounds(
+                inputs.clone(),
+                4,
+                recommended_rounds::<SF>(RESCUE_SPONGE_WIDTH, 128)
+            ),
+            rescue_sponge(inputs.clone(), 4, 128)
+        );
+
+        // The inputs span two chunks, and squeezing four outputs needs one more permutation, so each
+        // extra round should cost two gates in each of three permutations.
+        let num_gates = |rounds| {
+            let mut builder = CircuitBuilder::<C>::new(128);
+            let input_targets = builder.add_virtual_targets(inputs.len());
+            builder.rescue_hash_rounds(&input_targets, 4, rounds);
+            builder.num_gates()
+        };
+        assert_eq!(num_gates(rounds + 1), num_gates(rounds) + 3 * 2);
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let input_targets = builder.add_virtual_targets(inputs.len());
+        let outputs = builder.rescue_hash_rounds(&input_targets, 4, rounds);
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&input_targets, &inputs);
+        let witness = generate_satisfying_witness::<C, InnerC>(&circuit, partial_witness);
+        assert_eq!(witness.get_targets(&outputs), expected);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{AffinePoint, Bls12377Base, Bls12377Scalar, Curve};
+use crate::{AffinePoint, Bls12377Base, Bls12377Scalar, Curve, ProjectivePoint};
 use crate::field::Field;
 
 // Parameters taken from the implementation of Bls12-377 in Zexe found here:
@@ -18,6 +18,18 @@ impl Curve for Bls12377 {
         y: BLS12_377_GENERATOR_Y,
         zero: false,
     };
+
+    const ORDER: &'static [u64] = &Bls12377Scalar::ORDER;
+
+    /// The cofactor of G1: 30631250834960419227450344600217059328.
+    const COFACTOR: &'static [u64] = &[0, 0x170b5d4430000000, 0, 0];
+
+    fn clear_cofactor(point: ProjectivePoint<Self>) -> ProjectivePoint<Self> {
+        // The cofactor is smaller than the scalar field order, so it can be represented as a
+        // scalar, which lets us use the faster windowed multiplication.
+        let cofactor = Bls12377Scalar::from_canonical_u64_vec(Self::COFACTOR.to_vec());
+        Self::convert(cofactor) * point
+    }
 }
 
 /// 81937999373150964239938255573465948239988671502647976594219695644855304257327692006745978603320413799295628339695
@@ -34,7 +46,10 @@ const BLS12_377_GENERATOR_Y: Bls12377Base = Bls12377Base {
 
 #[cfg(test)]
 mod tests {
-    use crate::{blake_hash_usize_to_curve, Bls12377, Bls12377Scalar, Curve, Field, ProjectivePoint};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use crate::{blake_hash_usize_to_curve, AffinePoint, Bls12377, Bls12377Scalar, Curve, Field, ProjectivePoint};
 
     #[test]
     fn test_double_affine() {
@@ -46,6 +61,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_random_in_subgroup() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for _ in 0..5 {
+            let p = AffinePoint::<Bls12377>::random_from_rng(&mut rng);
+            assert!(p.is_valid());
+            assert!(p.is_in_subgroup());
+            assert!(!p.zero);
+        }
+        // Hashed points are almost never in the subgroup, so this checks that the cofactor is
+        // really cleared.
+        assert!(!blake_hash_usize_to_curve::<Bls12377>(0).is_in_subgroup());
+    }
+
     #[test]
     fn test_naive_multiplication() {
         let g = Bls12377::GENERATOR_PROJECTIVE;
@@ -1,10 +1,12 @@
 #![allow(clippy::many_single_char_names)]
 
+use std::hash::{Hash, Hasher};
 use std::ops::Neg;
 
 use anyhow::Result;
+use rand::RngCore;
 
-use crate::Field;
+use crate::{blake_hash_base_field_to_curve, Field};
 use std::fmt::Debug;
 
 // To avoid implementation conflicts from associated types,
@@ -56,6 +58,40 @@ pub trait Curve: 'static + Sync + Sized + Copy + Debug {
         Ok(res)
     }
 
+    /// The order of the subgroup generated by `GENERATOR_AFFINE`, i.e. `|ScalarField|`, as
+    /// little-endian `u64` limbs.
+    const ORDER: &'static [u64];
+
+    /// The number of points on the curve divided by `ORDER`, as little-endian `u64` limbs.
+    const COFACTOR: &'static [u64];
+
+    /// Returns whether the curve has prime order, i.e. whether `COFACTOR` is one.
+    fn has_prime_order() -> bool {
+        Self::COFACTOR.iter().enumerate().all(|(i, &limb)| limb == (i == 0) as u64)
+    }
+
+    /// Returns whether `point` lies in the subgroup of order `|ScalarField|`. If the curve has
+    /// prime order, every point on the curve is in the subgroup, so it suffices to check the curve
+    /// equation. Otherwise the default implementation multiplies by the subgroup order; curves may
+    /// override it with a cheaper check.
+    fn is_in_subgroup(point: &AffinePoint<Self>) -> bool {
+        if Self::has_prime_order() {
+            point.is_valid()
+        } else {
+            point.is_in_subgroup_generic()
+        }
+    }
+
+    /// Maps a point on the curve into the subgroup of order `|ScalarField|`, by multiplying it by
+    /// `COFACTOR`.
+    fn clear_cofactor(point: ProjectivePoint<Self>) -> ProjectivePoint<Self> {
+        if Self::has_prime_order() {
+            point
+        } else {
+            point.mul_u64_limbs(Self::COFACTOR)
+        }
+    }
+
     fn is_safe_curve() -> bool{
         // Added additional check to prevent using vulnerabilties in case a discriminant is equal to 0.
         (Self::A.cube().double().double() + Self::B.square().triple().triple().triple()).is_nonzero()
@@ -90,11 +126,54 @@ impl<C: Curve> AffinePoint<C> {
         point
     }
 
+    /// Returns `-self` if `negate` is true, otherwise `self`.
+    pub fn conditional_negate(&self, negate: bool) -> Self {
+        if negate {
+            -*self
+        } else {
+            *self
+        }
+    }
+
+    /// Returns a random point in the subgroup of order `|ScalarField|`. See `random_from_rng`.
+    pub fn random() -> Self {
+        Self::random_from_rng(&mut rand::thread_rng())
+    }
+
+    /// Returns a random point in the subgroup of order `|ScalarField|`, by hashing a random base
+    /// field element to the curve and clearing the cofactor. Unlike `[s] G` for a random `s`, the
+    /// discrete logarithm of the result with respect to the generator is unknown, so these points
+    /// are suitable as independent generators in tests and nothing-up-my-sleeve setups.
+    pub fn random_from_rng<R: RngCore>(rng: &mut R) -> Self {
+        let seed = C::BaseField::rand_from_rng(rng);
+        let point = blake_hash_base_field_to_curve::<C>(seed);
+        C::clear_cofactor(point.to_projective()).to_affine()
+    }
+
     pub fn is_valid(&self) -> bool {
         let Self { x, y, zero } = *self;
         zero || y.square() == x.cube() + C::A * x + C::B
     }
 
+    /// Returns whether this point is on the curve and lies in the subgroup of order
+    /// `|ScalarField|`.
+    pub fn is_in_subgroup(&self) -> bool {
+        C::is_in_subgroup(self)
+    }
+
+    /// Checks subgroup membership by testing that `[r] P = 0`, where `r = |ScalarField|`. Since
+    /// `r` itself is not representable as a scalar, we check the equivalent `[r - 1] P = -P`.
+    pub fn is_in_subgroup_generic(&self) -> bool {
+        if !self.is_valid() {
+            return false;
+        }
+        if self.zero {
+            return true;
+        }
+        let r_minus_1_p = C::convert(C::ScalarField::NEG_ONE) * self.to_projective();
+        r_minus_1_p.to_affine() == -*self
+    }
+
     pub fn to_projective(&self) -> ProjectivePoint<C> {
         let Self { x, y, zero } = *self;
         ProjectivePoint {
@@ -171,6 +250,18 @@ impl<C: Curve> PartialEq for AffinePoint<C> {
 
 impl<C: Curve> Eq for AffinePoint<C> {}
 
+/// Consistent with `PartialEq`: every representation of the identity hashes the same, regardless
+/// of its coordinates.
+impl<C: Curve> Hash for AffinePoint<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.zero.hash(state);
+        if !self.zero {
+            self.x.hash(state);
+            self.y.hash(state);
+        }
+    }
+}
+
 /// A point on a short Weierstrass curve, represented in projective coordinates.
 #[derive(Copy, Clone, Debug)]
 pub struct ProjectivePoint<C: Curve> {
@@ -241,7 +332,7 @@ impl<C: Curve> ProjectivePoint<C> {
         let zz = z.square();
         let mut w = xx.triple();
         if C::A.is_nonzero() {
-            w = w + C::A * zz;
+            w += C::A * zz;
         }
         let s = y.double() * z;
         let r = y * s;
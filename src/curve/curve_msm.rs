@@ -1,8 +1,11 @@
+use std::fmt;
+use std::mem::size_of;
 use std::time::Instant;
 
 use rayon::prelude::*;
 
-use crate::{affine_multisummation_best, AffinePoint, Curve, Field, ProjectivePoint};
+use crate::util::ceil_div_usize;
+use crate::{affine_multisummation_batch_inversion, affine_multisummation_best, AffinePoint, Curve, Field, ProjectivePoint};
 use serde::{Deserialize, Serialize};
 
 /// In Yao's method, we compute an affine summation for each digit. In a parallel setting, it would
@@ -11,17 +14,98 @@ use serde::{Deserialize, Serialize};
 /// `affine_multisummation_best`). Thus we divide digits into large chunks, and assign chunks of
 /// digits to threads. Note that there is a delicate balance here, as large chunks can result in
 /// uneven distributions of work among threads.
-const DIGITS_PER_CHUNK: usize = 80;
+pub(crate) const DIGITS_PER_CHUNK: usize = 80;
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct MsmPrecomputation<C: Curve> {
     /// For each generator (in the order they were passed to `msm_precompute`), contains a vector
-    /// of powers, i.e. [(2^w)^i] for i < DIGITS.
+    /// of powers, i.e. [(2^w)^i] for i < DIGITS. These are indexed by the position of a digit in
+    /// the little-endian order of `to_digits`.
     // TODO: Use compressed coordinates here.
     powers_per_generator: Vec<Vec<AffinePoint<C>>>,
 
     /// The window size.
     w: usize,
+
+    /// The maximum number of nonempty buckets for which the bucket sums are combined directly,
+    /// rather than with a running sum. See `bucket_reduction_threshold`.
+    #[serde(default)]
+    bucket_reduction_threshold: Option<usize>,
+}
+
+impl<C: Curve> MsmPrecomputation<C> {
+    /// Returns the number of generators this precomputation covers, i.e. the number of scalars an
+    /// MSM with it expects.
+    pub fn generator_count(&self) -> usize {
+        self.powers_per_generator.len()
+    }
+
+    /// Returns the window size `w` this precomputation was computed with.
+    pub fn window_size(&self) -> usize {
+        self.w
+    }
+
+    /// Returns an estimate of the memory used by this precomputation, in bytes. This counts the
+    /// stored powers of each generator, but not any excess capacity of their vectors.
+    pub fn memory_footprint(&self) -> usize {
+        let num_powers: usize = self.powers_per_generator.iter().map(Vec::len).sum();
+        size_of::<Self>()
+            + self.powers_per_generator.len() * size_of::<Vec<AffinePoint<C>>>()
+            + num_powers * size_of::<AffinePoint<C>>()
+    }
+
+    /// Appends the powers of some additional generators, computed with this precomputation's
+    /// window size, without recomputing those of the existing generators. The result is the same
+    /// as a precomputation of the concatenated list of generators.
+    pub fn extend(&mut self, extra_generators: &[ProjectivePoint<C>]) {
+        let w = self.w;
+        self.powers_per_generator.par_extend(
+            extra_generators
+                .par_iter()
+                .map(|&g| precompute_single_generator(g, w)),
+        );
+    }
+
+    /// Returns the maximum number of nonempty buckets, i.e. distinct nonzero digits occurring in
+    /// the scalars, for which an MSM combines the bucket sums directly, multiplying each by its
+    /// digit, rather than with the usual running sum.
+    ///
+    /// The running sum costs about `2^(w + 1)` additions regardless of how many buckets are
+    /// occupied, whereas the direct method costs about `3w/2` group operations per nonempty
+    /// bucket, so the direct method is cheaper when few buckets are occupied, as with few or
+    /// sparse scalars. The default, `default_bucket_reduction_threshold(w)`, is the point at
+    /// which the estimated costs are equal.
+    pub fn bucket_reduction_threshold(&self) -> usize {
+        self.bucket_reduction_threshold
+            .unwrap_or_else(|| default_bucket_reduction_threshold(self.w))
+    }
+
+    /// Overrides the default bucket reduction threshold. A threshold of 0 always uses the running
+    /// sum, and `usize::MAX` always uses the direct method. The result of an MSM doesn't depend on
+    /// the threshold.
+    pub fn with_bucket_reduction_threshold(mut self, threshold: usize) -> Self {
+        self.bucket_reduction_threshold = Some(threshold);
+        self
+    }
+
+    /// Asserts that each generator has as many powers as the window size `w` calls for. The
+    /// powers are only meaningful for the `w` they were computed with, so a precomputation whose
+    /// `w` was altered, e.g. in a serialized verification key, would otherwise silently produce
+    /// incorrect results.
+    fn assert_consistent_window_size(&self) {
+        let digits = ceil_div_usize(C::ScalarField::modulus_bits(), self.w);
+        for powers in &self.powers_per_generator {
+            assert_eq!(
+                powers.len(),
+                digits,
+                "MSM precomputation has {} powers per generator, but its window size of {} \
+                 requires {}; it must be used with the window size it was computed with",
+                powers.len(),
+                self.w,
+                digits
+            );
+        }
+    }
 }
 
 pub fn msm_precompute<C: Curve>(
@@ -34,14 +118,117 @@ pub fn msm_precompute<C: Curve>(
             .map(|&g| precompute_single_generator(g, w))
             .collect(),
         w,
+        bucket_reduction_threshold: None,
+    }
+}
+
+/// Returns the number of nonempty buckets at which the direct bucket reduction and the running
+/// sum reduction have roughly equal estimated costs, for a window size of `w`. See
+/// `MsmPrecomputation::bucket_reduction_threshold`.
+pub fn default_bucket_reduction_threshold(w: usize) -> usize {
+    (1 << (w + 2)) / (3 * w).max(1)
+}
+
+/// An error returned by `msm_precompute_validated` when a generator is invalid.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MsmSetupError {
+    /// The generator at this index does not satisfy the curve equation.
+    NotOnCurve { index: usize },
+    /// The generator at this index is on the curve, but not in the subgroup of order
+    /// `|ScalarField|`.
+    NotInSubgroup { index: usize },
+}
+
+impl fmt::Display for MsmSetupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MsmSetupError::NotOnCurve { index } => {
+                write!(f, "MSM generator {} is not on the curve", index)
+            }
+            MsmSetupError::NotInSubgroup { index } => {
+                write!(f, "MSM generator {} is not in the prime-order subgroup", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MsmSetupError {}
+
+/// Like `msm_precompute`, but first checks that each generator is on the curve and in the
+/// prime-order subgroup, returning the first offending index otherwise. An MSM over invalid
+/// generators is meaningless, so this should be used for generators which come from an untrusted
+/// source, such as a deserialized setup.
+pub fn msm_precompute_validated<C: Curve>(
+    generators: &[ProjectivePoint<C>],
+    w: usize,
+) -> Result<MsmPrecomputation<C>, MsmSetupError> {
+    for (index, generator) in generators.iter().enumerate() {
+        // We can't use `ProjectivePoint::is_valid` here, since converting an invalid point to
+        // affine coordinates fails a debug assertion.
+        if !is_on_curve_projective(generator) {
+            return Err(MsmSetupError::NotOnCurve { index });
+        }
+        if !generator.to_affine().is_in_subgroup() {
+            return Err(MsmSetupError::NotInSubgroup { index });
+        }
+    }
+    Ok(msm_precompute(generators, w))
+}
+
+/// Checks the homogeneous curve equation `y^2 z = x^3 + a x z^2 + b z^3`.
+fn is_on_curve_projective<C: Curve>(point: &ProjectivePoint<C>) -> bool {
+    let ProjectivePoint { x, y, z, zero } = *point;
+    if zero {
+        return true;
+    }
+    let zz = z.square();
+    z.is_nonzero() && y.square() * z == x.cube() + C::A * x * zz + C::B * zz * z
+}
+
+/// The number of progress updates reported by `msm_precompute_with_progress`, not counting the
+/// initial update.
+const PRECOMPUTE_PROGRESS_STEPS: usize = 100;
+
+/// Like `msm_precompute`, but invokes `progress` with the fraction of generators processed so
+/// far, starting with 0 and ending with 1. Generators are processed in parallel batches, and the
+/// callback is invoked (on the calling thread) after each batch.
+pub fn msm_precompute_with_progress<C: Curve>(
+    generators: &[ProjectivePoint<C>],
+    w: usize,
+    mut progress: impl FnMut(f64),
+) -> MsmPrecomputation<C> {
+    progress(0.0);
+    let batch_size = ceil_div_usize(generators.len(), PRECOMPUTE_PROGRESS_STEPS).max(1);
+    let mut powers_per_generator = Vec::with_capacity(generators.len());
+    for batch in generators.chunks(batch_size) {
+        powers_per_generator.par_extend(batch.par_iter().map(|&g| precompute_single_generator(g, w)));
+        progress(powers_per_generator.len() as f64 / generators.len() as f64);
+    }
+    if generators.is_empty() {
+        progress(1.0);
+    }
+
+    MsmPrecomputation {
+        powers_per_generator,
+        w,
+        bucket_reduction_threshold: None,
     }
 }
 
 fn precompute_single_generator<C: Curve>(g: ProjectivePoint<C>, w: usize) -> Vec<AffinePoint<C>> {
-    let digits = (C::ScalarField::BITS + w - 1) / w;
-    let mut powers: Vec<ProjectivePoint<C>> = Vec::with_capacity(digits);
+    let digits = ceil_div_usize(C::ScalarField::modulus_bits(), w);
+    precompute_powers(g, w, digits)
+}
+
+/// Returns `[(2^w)^i] g` for `i < num_powers`.
+pub(crate) fn precompute_powers<C: Curve>(
+    g: ProjectivePoint<C>,
+    w: usize,
+    num_powers: usize,
+) -> Vec<AffinePoint<C>> {
+    let mut powers: Vec<ProjectivePoint<C>> = Vec::with_capacity(num_powers);
     powers.push(g);
-    for i in 1..digits {
+    for i in 1..num_powers {
         let mut power_i_proj = powers[i - 1];
         for _j in 0..w {
             power_i_proj = power_i_proj.double();
@@ -60,70 +247,197 @@ pub fn msm_parallel<C: Curve>(
     msm_execute_parallel(&precomputation, scalars)
 }
 
+/// How `msm_execute_with_accumulation` adds up the points associated with each digit.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MsmAccumulation {
+    /// Adds each point to a projective accumulator, using a mixed affine + projective formula.
+    Projective,
+    /// Adds up the points for all digits at once using affine formulas, batching the divisions
+    /// via Montgomery's trick (see `affine_multisummation_batch_inversion`), so that each round
+    /// of pairwise additions costs a single inversion. This can be faster when the number of
+    /// points per digit is large.
+    AffineBatch,
+}
+
+/// Computes an MSM using the given precomputation.
+///
+/// The scalars must be canonical (see `Field::is_canonical`), since the windowed decomposition
+/// assumes a reduced representation; this is checked only in debug builds, and the result is
+/// unspecified otherwise. Scalars from an untrusted source should be passed to
+/// `msm_execute_checked` instead.
 pub fn msm_execute<C: Curve>(
     precomputation: &MsmPrecomputation<C>,
     scalars: &[C::ScalarField],
 ) -> ProjectivePoint<C> {
-    assert_eq!(precomputation.powers_per_generator.len(), scalars.len());
-    let w = precomputation.w;
-    let digits = (C::ScalarField::BITS + w - 1) / w;
-    let base = 1 << w;
+    msm_execute_with_accumulation(precomputation, scalars, MsmAccumulation::Projective)
+}
 
-    // This is a variant of Yao's method, adapted to the multi-scalar setting. Because we use
-    // extremely large windows, the repeated scans in Yao's method could be more expensive than the
-    // actual group operations. To avoid this, we store a multimap from each possible digit to the
-    // positions in which that digit occurs in the scalars. These positions have the form (i, j),
-    // where i is the index of the generator and j is an index into the digits of the scalar
-    // associated with that generator.
-    let mut digit_occurrences: Vec<Vec<(usize, usize)>> = Vec::with_capacity(digits);
-    for _i in 0..base {
-        digit_occurrences.push(Vec::new());
-    }
-    for (i, scalar) in scalars.iter().enumerate() {
-        let digits = to_digits::<C>(scalar, w);
-        for (j, &digit) in digits.iter().enumerate() {
-            digit_occurrences[digit].push((i, j));
+/// An error returned by `msm_execute_checked` when a scalar is invalid.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MsmScalarError {
+    /// The scalar at this index is not canonical, i.e. its representation is not reduced modulo
+    /// the order of the scalar field.
+    NonCanonical { index: usize },
+}
+
+impl fmt::Display for MsmScalarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MsmScalarError::NonCanonical { index } => {
+                write!(f, "MSM scalar {} is not canonical", index)
+            }
         }
     }
+}
 
-    let mut y = ProjectivePoint::ZERO;
-    let mut u = ProjectivePoint::ZERO;
+impl std::error::Error for MsmScalarError {}
 
-    for digit in (1..base).rev() {
-        for &(i, j) in &digit_occurrences[digit] {
-            u = u + precomputation.powers_per_generator[i][j];
-        }
-        y = y + u;
+/// Like `msm_execute`, but first checks that each scalar is canonical, returning the first
+/// offending index otherwise.
+pub fn msm_execute_checked<C: Curve>(
+    precomputation: &MsmPrecomputation<C>,
+    scalars: &[C::ScalarField],
+) -> Result<ProjectivePoint<C>, MsmScalarError> {
+    if let Some(index) = scalars.iter().position(|s| !s.is_canonical()) {
+        return Err(MsmScalarError::NonCanonical { index });
     }
-
-    y
+    Ok(msm_execute(precomputation, scalars))
 }
 
-pub fn msm_execute_parallel<C: Curve>(
+/// Computes the same MSM as `msm_execute`, using the given method to add up the points associated
+/// with each digit. The result represents the same point for either method, although its
+/// projective coordinates may differ.
+pub fn msm_execute_with_accumulation<C: Curve>(
     precomputation: &MsmPrecomputation<C>,
     scalars: &[C::ScalarField],
+    accumulation: MsmAccumulation,
 ) -> ProjectivePoint<C> {
     assert_eq!(precomputation.powers_per_generator.len(), scalars.len());
+    precomputation.assert_consistent_window_size();
+    if scalars.iter().all(Field::is_zero) {
+        return ProjectivePoint::ZERO;
+    }
     let w = precomputation.w;
-    let digits = (C::ScalarField::BITS + w - 1) / w;
     let base = 1 << w;
+    let digit_occurrences = digit_occurrences::<C>(scalars, w);
+
+    let digit_acc: Vec<ProjectivePoint<C>> = match accumulation {
+        MsmAccumulation::Projective => digit_occurrences
+            .iter()
+            .map(|occurrences| {
+                occurrences
+                    .iter()
+                    .fold(ProjectivePoint::ZERO, |acc, &(i, j)| {
+                        acc + precomputation.powers_per_generator[i][j]
+                    })
+            })
+            .collect(),
+        MsmAccumulation::AffineBatch => {
+            let summations: Vec<Vec<AffinePoint<C>>> = digit_occurrences
+                .iter()
+                .map(|occurrences| {
+                    occurrences
+                        .iter()
+                        .map(|&(i, j)| precomputation.powers_per_generator[i][j])
+                        .collect()
+                })
+                .collect();
+            affine_multisummation_batch_inversion(summations)
+        }
+    };
+    debug_assert_eq!(digit_acc.len(), base);
+
+    reduce_buckets(
+        &digit_acc,
+        &digit_occurrences,
+        precomputation.bucket_reduction_threshold(),
+    )
+}
+
+/// Computes `sum_d [d] digit_acc[d]`, where `digit_acc[d]` is the sum of the powers associated
+/// with each occurrence of the digit `d`. If at most `threshold` digits occur, each nonempty
+/// bucket is multiplied by its digit directly. Otherwise, we use the running sum
+/// `sum_d u_d`, where `u_d = sum_{d' >= d} digit_acc[d']`.
+fn reduce_buckets<C: Curve>(
+    digit_acc: &[ProjectivePoint<C>],
+    digit_occurrences: &[Vec<(usize, usize)>],
+    threshold: usize,
+) -> ProjectivePoint<C> {
+    let nonempty_digits: Vec<usize> = (1..digit_acc.len())
+        .filter(|&digit| !digit_occurrences[digit].is_empty())
+        .collect();
 
-    // This is a variant of Yao's method, adapted to the multi-scalar setting. Because we use
-    // extremely large windows, the repeated scans in Yao's method could be more expensive than the
-    // actual group operations. To avoid this, we store a multimap from each possible digit to the
-    // positions in which that digit occurs in the scalars. These positions have the form (i, j),
-    // where i is the index of the generator and j is an index into the digits of the scalar
-    // associated with that generator.
-    let mut digit_occurrences: Vec<Vec<(usize, usize)>> = Vec::with_capacity(digits);
+    if nonempty_digits.len() <= threshold {
+        nonempty_digits
+            .into_iter()
+            .fold(ProjectivePoint::ZERO, |y, digit| {
+                y + digit_acc[digit].mul_u64_limbs(&[digit as u64])
+            })
+    } else {
+        let mut y = ProjectivePoint::ZERO;
+        let mut u = ProjectivePoint::ZERO;
+        for digit in (1..digit_acc.len()).rev() {
+            u = u + digit_acc[digit];
+            y = y + u;
+        }
+        y
+    }
+}
+
+/// This is a variant of Yao's method, adapted to the multi-scalar setting. Because we use
+/// extremely large windows, the repeated scans in Yao's method could be more expensive than the
+/// actual group operations. To avoid this, we store a multimap from each possible digit to the
+/// positions in which that digit occurs in the scalars. These positions have the form (i, j),
+/// where i is the index of the generator and j is an index into the digits of the scalar
+/// associated with that generator.
+///
+/// Zero digits contribute nothing to the result, so their occurrences are not recorded, and zero
+/// scalars are skipped before being split into digits. This makes sparse inputs, such as
+/// commitments to polynomials with many zero coefficients, cheaper: the generators of zero scalars
+/// are never touched. The check costs one comparison per scalar, so there is no break-even
+/// density below which it hurts; the savings are roughly proportional to the fraction of zero
+/// scalars, up to the fixed cost of the final `2^w` step accumulation.
+fn digit_occurrences<C: Curve>(scalars: &[C::ScalarField], w: usize) -> Vec<Vec<(usize, usize)>> {
+    let base = 1 << w;
+    let mut digit_occurrences: Vec<Vec<(usize, usize)>> = Vec::with_capacity(base);
     for _i in 0..base {
         digit_occurrences.push(Vec::new());
     }
     for (i, scalar) in scalars.iter().enumerate() {
+        debug_assert!(scalar.is_canonical(), "MSM scalar {} is not canonical", i);
+        if scalar.is_zero() {
+            continue;
+        }
         let digits = to_digits::<C>(scalar, w);
         for (j, &digit) in digits.iter().enumerate() {
-            digit_occurrences[digit].push((i, j));
+            if digit != 0 {
+                digit_occurrences[digit].push((i, j));
+            }
         }
     }
+    digit_occurrences
+}
+
+/// Computes the same MSM as `msm_execute`, using multiple threads for the per-digit summations.
+///
+/// The result is deterministic: the digits are split into fixed chunks of `DIGITS_PER_CHUNK`, and
+/// the per-chunk results are combined in digit order, so the same projective coordinates are
+/// returned regardless of the number of threads. The coordinates may differ from those returned
+/// by `msm_execute`, since the summations are grouped differently, but the two results always
+/// represent the same point, i.e. they are equal as `ProjectivePoint`s and their `to_affine`
+/// conversions are identical.
+pub fn msm_execute_parallel<C: Curve>(
+    precomputation: &MsmPrecomputation<C>,
+    scalars: &[C::ScalarField],
+) -> ProjectivePoint<C> {
+    assert_eq!(precomputation.powers_per_generator.len(), scalars.len());
+    precomputation.assert_consistent_window_size();
+    if scalars.iter().all(Field::is_zero) {
+        return ProjectivePoint::ZERO;
+    }
+    let w = precomputation.w;
+    let base = 1 << w;
+    let digit_occurrences = digit_occurrences::<C>(scalars, w);
 
     // For each digit, we add up the powers associated with all occurrences that digit.
     let digits: Vec<usize> = (0..base).collect();
@@ -146,19 +460,25 @@ pub fn msm_execute_parallel<C: Curve>(
     // println!("Computing the per-digit summations (in parallel) took {}s", start.elapsed().as_secs_f64());
 
     let _start = Instant::now();
-    let mut y = ProjectivePoint::ZERO;
-    let mut u = ProjectivePoint::ZERO;
-    for digit in (1..base).rev() {
-        u = u + digit_acc[digit];
-        y = y + u;
-    }
+    let y = reduce_buckets(
+        &digit_acc,
+        &digit_occurrences,
+        precomputation.bucket_reduction_threshold(),
+    );
     // println!("Final summation (sequential) {}s", start.elapsed().as_secs_f64());
     y
 }
 
+/// Splits the canonical representation of `x` into `ceil(BITS / w)` digits of `w` bits each, in
+/// little-endian order, i.e. such that `x = sum_i digits[i] * 2^(w i)`. The bits within each
+/// digit are also little-endian, so the least significant bit of `x` is the least significant bit
+/// of `digits[0]`.
+///
+/// This ordering must agree with `MsmPrecomputation`, whose `j`th power of each generator is
+/// `[(2^w)^j] g`, as the MSM pairs `digits[j]` with that power.
 pub(crate) fn to_digits<C: Curve>(x: &C::ScalarField, w: usize) -> Vec<usize> {
-    let scalar_bits = C::ScalarField::BITS;
-    let num_digits = (scalar_bits + w - 1) / w;
+    let scalar_bits = C::ScalarField::modulus_bits();
+    let num_digits = ceil_div_usize(scalar_bits, w);
 
     // Convert x to a bool array.
     let x_canonical = x.to_canonical_u64_vec();
@@ -181,7 +501,18 @@ pub(crate) fn to_digits<C: Curve>(x: &C::ScalarField, w: usize) -> Vec<usize> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{msm_execute, msm_precompute, to_digits, Bls12377, Bls12377Scalar, Curve};
+    use num::{BigUint, Zero};
+    use rayon::ThreadPoolBuilder;
+
+    use crate::util::ceil_div_usize;
+    use crate::{
+        blake_hash_usize_to_curve, default_bucket_reduction_threshold, field_to_biguint,
+        msm_execute, msm_execute_checked,
+        msm_execute_parallel, msm_execute_with_accumulation, msm_precompute,
+        msm_precompute_validated, msm_precompute_with_progress, to_digits, AffinePoint, Bls12377,
+        Bls12377Scalar, Curve, Field, MsmAccumulation, MsmScalarError, MsmSetupError,
+        ProjectivePoint, TweedledeeBase, Tweedledum,
+    };
 
     #[test]
     fn test_to_digits() {
@@ -215,6 +546,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_to_digits_recombines() {
+        type SF = <Tweedledum as Curve>::ScalarField;
+
+        let mut scalars = vec![
+            SF::ZERO,
+            SF::ONE,
+            SF::TWO,
+            SF::NEG_ONE,
+            SF::from_canonical_u64(0x0123_4567_89ab_cdef),
+        ];
+        scalars.extend((0..5).map(|_| SF::rand()));
+
+        for w in 1..=20 {
+            for &x in &scalars {
+                let digits = to_digits::<Tweedledum>(&x, w);
+                assert_eq!(digits.len(), ceil_div_usize(SF::BITS, w));
+                assert!(digits.iter().all(|&digit| digit < 1 << w));
+
+                let recombined = digits
+                    .iter()
+                    .enumerate()
+                    .fold(BigUint::zero(), |acc, (i, &digit)| {
+                        acc + (BigUint::from(digit) << (w * i))
+                    });
+                assert_eq!(recombined, field_to_biguint(x), "w = {}", w);
+            }
+        }
+
+        // The least significant digit holds the least significant bits.
+        assert_eq!(to_digits::<Tweedledum>(&SF::from_canonical_u64(0b1101), 2)[..3], [1, 3, 0]);
+    }
+
     #[test]
     fn test_msm() {
         let w = 5;
@@ -239,4 +603,340 @@ mod tests {
 
         assert_eq!(result_msm, result_naive);
     }
+
+    #[test]
+    #[should_panic(expected = "it must be used with the window size it was computed with")]
+    fn test_msm_mismatched_window_size() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let generators: Vec<ProjectivePoint<C>> =
+            (0..3).map(|i| blake_hash_usize_to_curve(i).to_projective()).collect();
+        let mut precomputation = msm_precompute(&generators, 4);
+        // With w = 8, the powers for w = 4 would only cover half of each scalar's digits.
+        precomputation.w = 8;
+        msm_execute(&precomputation, &[SF::ONE, SF::TWO, SF::THREE]);
+    }
+
+    #[test]
+    fn test_msm_matches_naive_sum() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let generators: Vec<ProjectivePoint<C>> = (0..5)
+            .map(|_| C::convert(SF::rand()) * C::GENERATOR_PROJECTIVE)
+            .collect();
+        let scalars = vec![SF::ZERO, SF::ONE, SF::NEG_ONE, SF::rand(), SF::rand()];
+
+        let mut result_naive = ProjectivePoint::ZERO;
+        for (&s, &g) in scalars.iter().zip(generators.iter()) {
+            result_naive = result_naive + C::convert(s) * g;
+        }
+
+        for w in 1..=20 {
+            let precomputation = msm_precompute(&generators, w);
+            assert_eq!(
+                msm_execute(&precomputation, &scalars),
+                result_naive,
+                "msm_execute disagrees with the naive sum for w = {}",
+                w
+            );
+            assert_eq!(
+                msm_execute_parallel(&precomputation, &scalars),
+                result_naive,
+                "msm_execute_parallel disagrees with the naive sum for w = {}",
+                w
+            );
+        }
+    }
+
+    #[test]
+    fn test_msm_parallel_deterministic() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let generators: Vec<AffinePoint<C>> = (0..50).map(|_| AffinePoint::random()).collect();
+        let generators = AffinePoint::batch_to_projective(&generators);
+        let scalars: Vec<SF> = (0..50).map(|_| SF::rand()).collect();
+        let precomputation = msm_precompute(&generators, 8);
+
+        let serial = msm_execute(&precomputation, &scalars).to_affine();
+        let run_with_threads = |num_threads| {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            pool.install(|| msm_execute_parallel(&precomputation, &scalars))
+        };
+        let parallel_1 = run_with_threads(1);
+        let parallel_4 = run_with_threads(4);
+
+        // The affine coordinates agree with the serial MSM, not just the projective points.
+        let parallel_affine = parallel_1.to_affine();
+        assert!(!serial.zero && !parallel_affine.zero);
+        assert_eq!((parallel_affine.x, parallel_affine.y), (serial.x, serial.y));
+
+        // The projective coordinates don't depend on the number of threads.
+        assert_eq!(
+            (parallel_1.x, parallel_1.y, parallel_1.z),
+            (parallel_4.x, parallel_4.y, parallel_4.z)
+        );
+    }
+
+    #[test]
+    fn test_msm_precompute_with_progress() {
+        type C = Tweedledum;
+
+        let generators: Vec<ProjectivePoint<C>> = (0..250)
+            .map(|i| {
+                C::convert(<C as Curve>::ScalarField::from_canonical_usize(i))
+                    * C::GENERATOR_PROJECTIVE
+            })
+            .collect();
+
+        let mut updates = Vec::new();
+        let precomputation = msm_precompute_with_progress(&generators, 4, |p| updates.push(p));
+        assert_eq!(precomputation, msm_precompute(&generators, 4));
+
+        assert_eq!(updates.first(), Some(&0.0));
+        assert_eq!(updates.last(), Some(&1.0));
+        assert!(updates.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_msm_precompute_validated() {
+        type C = Bls12377;
+
+        let mut generators: Vec<ProjectivePoint<C>> =
+            (0..5).map(|_| AffinePoint::<C>::random().to_projective()).collect();
+        generators.push(ProjectivePoint::ZERO);
+        assert!(msm_precompute_validated(&generators, 4).is_ok());
+
+        // An unreduced hash to the curve is on the curve, but not in the subgroup.
+        let mut bad_generators = generators.clone();
+        bad_generators[2] = blake_hash_usize_to_curve::<C>(0).to_projective();
+        assert_eq!(
+            msm_precompute_validated(&bad_generators, 4).err(),
+            Some(MsmSetupError::NotInSubgroup { index: 2 })
+        );
+
+        let mut bad_generators = generators.clone();
+        bad_generators[3].y = bad_generators[3].y.double();
+        assert_eq!(
+            msm_precompute_validated(&bad_generators, 4).err(),
+            Some(MsmSetupError::NotOnCurve { index: 3 })
+        );
+    }
+
+    #[test]
+    fn test_msm_affine_batch_accumulation() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        // Repeated and negated generators, along with equal scalars, cause the same or opposite
+        // points to be added within a digit, and the zero generator and scalar add identities.
+        let g = AffinePoint::<C>::random().to_projective();
+        let h = AffinePoint::<C>::random().to_projective();
+        let generators = vec![g, g, -g, h, ProjectivePoint::ZERO, h, g];
+        let x = SF::rand();
+        let scalars = vec![x, x, x, SF::rand(), SF::rand(), SF::ZERO, SF::NEG_ONE];
+
+        for &w in &[1, 4, 8] {
+            let precomputation = msm_precompute(&generators, w);
+            let expected = msm_execute(&precomputation, &scalars);
+            let affine_batch = msm_execute_with_accumulation(
+                &precomputation,
+                &scalars,
+                MsmAccumulation::AffineBatch,
+            );
+            assert_eq!(affine_batch, expected);
+            assert_eq!(affine_batch.to_affine(), expected.to_affine());
+
+            let random_scalars: Vec<SF> = (0..generators.len()).map(|_| SF::rand()).collect();
+            assert_eq!(
+                msm_execute_with_accumulation(
+                    &precomputation,
+                    &random_scalars,
+                    MsmAccumulation::AffineBatch
+                ),
+                msm_execute(&precomputation, &random_scalars)
+            );
+        }
+    }
+
+    #[test]
+    fn test_msm_sparse_scalars() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let generators: Vec<ProjectivePoint<C>> =
+            (0..100).map(|i| blake_hash_usize_to_curve(i).to_projective()).collect();
+        // 90% of the scalars are zero.
+        let scalars: Vec<SF> = (0..100)
+            .map(|i| if i % 10 == 3 { SF::rand() } else { SF::ZERO })
+            .collect();
+
+        // The dense MSM over only the generators with nonzero scalars.
+        let (dense_generators, dense_scalars): (Vec<ProjectivePoint<C>>, Vec<SF>) = generators
+            .iter()
+            .zip(&scalars)
+            .filter(|(_g, s)| s.is_nonzero())
+            .map(|(&g, &s)| (g, s))
+            .unzip();
+        assert_eq!(dense_scalars.len(), 10);
+
+        for &w in &[1, 4, 8] {
+            let precomputation = msm_precompute(&generators, w);
+            let dense_precomputation = msm_precompute(&dense_generators, w);
+            let expected = msm_execute(&dense_precomputation, &dense_scalars);
+            assert_eq!(msm_execute(&precomputation, &scalars), expected);
+            assert_eq!(msm_execute_parallel(&precomputation, &scalars), expected);
+            assert_eq!(
+                msm_execute_with_accumulation(
+                    &precomputation,
+                    &scalars,
+                    MsmAccumulation::AffineBatch
+                ),
+                expected
+            );
+
+            let zeros = vec![SF::ZERO; generators.len()];
+            assert_eq!(msm_execute(&precomputation, &zeros), ProjectivePoint::ZERO);
+            assert_eq!(
+                msm_execute_parallel(&precomputation, &zeros),
+                ProjectivePoint::ZERO
+            );
+            assert_eq!(
+                msm_execute_with_accumulation(
+                    &precomputation,
+                    &zeros,
+                    MsmAccumulation::AffineBatch
+                ),
+                ProjectivePoint::ZERO
+            );
+        }
+    }
+
+    #[test]
+    fn test_msm_execute_checked() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let generators: Vec<ProjectivePoint<C>> = (0..3)
+            .map(|i| blake_hash_usize_to_curve::<C>(i).to_projective())
+            .collect();
+        let precomputation = msm_precompute(&generators, 5);
+
+        let mut scalars: Vec<SF> = (0..3).map(|_| SF::rand()).collect();
+        assert_eq!(
+            msm_execute_checked(&precomputation, &scalars),
+            Ok(msm_execute(&precomputation, &scalars))
+        );
+
+        // A representation equal to the field order is a non-canonical encoding of zero.
+        let non_canonical = TweedledeeBase {
+            limbs: <TweedledeeBase as crate::MontyRepr>::ORDER,
+        };
+        assert!(!non_canonical.is_canonical());
+        scalars[1] = non_canonical;
+        assert_eq!(
+            msm_execute_checked(&precomputation, &scalars),
+            Err(MsmScalarError::NonCanonical { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_msm_bucket_reduction_threshold() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let generators: Vec<ProjectivePoint<C>> = (0..20)
+            .map(|_| AffinePoint::<C>::random().to_projective())
+            .collect();
+        // Dense scalars occupy most buckets, while small scalars occupy only a few.
+        let dense: Vec<SF> = (0..generators.len()).map(|_| SF::rand()).collect();
+        let small: Vec<SF> = (0..generators.len())
+            .map(|i| SF::from_canonical_usize(i % 3))
+            .collect();
+        let naive_msm = |scalars: &[SF]| {
+            scalars
+                .iter()
+                .zip(&generators)
+                .fold(ProjectivePoint::ZERO, |acc, (&s, &g)| acc + C::convert(s) * g)
+        };
+        let expected_dense = naive_msm(&dense);
+        let expected_small = naive_msm(&small);
+
+        for &w in &[1, 5, 9] {
+            let precomputation = msm_precompute(&generators, w);
+            assert_eq!(
+                precomputation.bucket_reduction_threshold(),
+                default_bucket_reduction_threshold(w)
+            );
+            for &threshold in &[0, 1, 2, default_bucket_reduction_threshold(w), usize::MAX] {
+                let precomputation = precomputation
+                    .clone()
+                    .with_bucket_reduction_threshold(threshold);
+                assert_eq!(precomputation.bucket_reduction_threshold(), threshold);
+                for (scalars, expected) in &[(&dense, expected_dense), (&small, expected_small)] {
+                    assert_eq!(msm_execute(&precomputation, scalars), *expected);
+                    assert_eq!(
+                        msm_execute_with_accumulation(
+                            &precomputation,
+                            scalars,
+                            MsmAccumulation::AffineBatch
+                        ),
+                        *expected
+                    );
+                    assert_eq!(msm_execute_parallel(&precomputation, scalars), *expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_msm_precomputation_introspection() {
+        type C = Tweedledum;
+
+        for &(n, w) in &[(0, 5), (1, 1), (20, 5), (7, 9)] {
+            let generators: Vec<ProjectivePoint<C>> = (0..n)
+                .map(|_| AffinePoint::<C>::random().to_projective())
+                .collect();
+            let precomputation = msm_precompute(&generators, w);
+            assert_eq!(precomputation.generator_count(), n);
+            assert_eq!(precomputation.window_size(), w);
+
+            let digits = ceil_div_usize(<C as Curve>::ScalarField::BITS, w);
+            let powers_size = n * digits * std::mem::size_of::<AffinePoint<C>>();
+            assert!(precomputation.memory_footprint() > powers_size);
+            assert!(precomputation.memory_footprint() < powers_size + 1024 + 64 * n);
+        }
+    }
+
+    #[test]
+    fn test_msm_precomputation_extend() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let generators: Vec<ProjectivePoint<C>> = (0..30)
+            .map(|_| AffinePoint::<C>::random().to_projective())
+            .collect();
+        let scalars: Vec<SF> = (0..30).map(|_| SF::rand()).collect();
+        let full = msm_precompute(&generators, 5);
+
+        let mut extended = msm_precompute(&generators[..20], 5);
+        extended.extend(&generators[20..25]);
+        extended.extend(&[]);
+        extended.extend(&generators[25..]);
+        assert_eq!(extended, full);
+        assert_eq!(msm_execute(&extended, &scalars), msm_execute(&full, &scalars));
+        assert_eq!(
+            msm_execute_parallel(&extended, &scalars).to_affine(),
+            msm_execute(&full, &scalars).to_affine()
+        );
+
+        let mut from_empty = msm_precompute(&[], 5);
+        from_empty.extend(&generators);
+        assert_eq!(from_empty, full);
+    }
 }
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::{msm_execute_parallel, Curve, MsmPrecomputation, ProjectivePoint};
+
+/// A memoizing wrapper around `msm_execute_parallel`, for provers which may compute the same MSM
+/// several times, such as when recommitting to an unchanged polynomial.
+///
+/// Results are keyed on the scalar vector itself. A lookup hashes the scalars to find a candidate
+/// entry, but a hit is only reported if the stored scalars are equal to the given ones, so a hash
+/// collision can never cause an incorrect result to be returned. The cache is purely a performance
+/// hint, and has no bearing on soundness. Note that each entry holds a copy of its scalar vector,
+/// and entries are never evicted, except by `clear`.
+pub struct CachedMsm<C: Curve> {
+    precomputation: MsmPrecomputation<C>,
+    cache: Mutex<HashMap<Vec<C::ScalarField>, ProjectivePoint<C>>>,
+    stats: Mutex<CachedMsmStats>,
+}
+
+/// The number of hits and misses seen by a `CachedMsm`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CachedMsmStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl<C: Curve> CachedMsm<C> {
+    pub fn new(precomputation: MsmPrecomputation<C>) -> Self {
+        CachedMsm {
+            precomputation,
+            cache: Mutex::new(HashMap::new()),
+            stats: Mutex::new(CachedMsmStats::default()),
+        }
+    }
+
+    pub fn precomputation(&self) -> &MsmPrecomputation<C> {
+        &self.precomputation
+    }
+
+    /// Computes the same MSM as `msm_execute_parallel`, returning a cached result if this MSM has
+    /// already been computed with the same scalars.
+    pub fn execute(&self, scalars: &[C::ScalarField]) -> ProjectivePoint<C> {
+        if let Some(&result) = self.cache.lock().unwrap().get(scalars) {
+            self.stats.lock().unwrap().hits += 1;
+            return result;
+        }
+
+        // We don't hold the lock while computing the MSM, so other threads may compute the same
+        // result concurrently, in which case they will just overwrite each other's entries.
+        let result = msm_execute_parallel(&self.precomputation, scalars);
+        self.cache.lock().unwrap().insert(scalars.to_vec(), result);
+        self.stats.lock().unwrap().misses += 1;
+        result
+    }
+
+    pub fn stats(&self) -> CachedMsmStats {
+        *self.stats.lock().unwrap()
+    }
+
+    /// Removes all cached results.
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{blake_hash_usize_to_curve, msm_execute_parallel, msm_precompute, AffinePoint, CachedMsm, CachedMsmStats, Curve, Field, Tweedledee};
+
+    #[test]
+    fn test_cached_msm() {
+        type C = Tweedledee;
+        type SF = <C as Curve>::ScalarField;
+
+        let generators: Vec<AffinePoint<C>> = (0..10).map(blake_hash_usize_to_curve).collect();
+        let precomputation = msm_precompute(&AffinePoint::batch_to_projective(&generators), 4);
+        let cached_msm = CachedMsm::new(precomputation.clone());
+
+        let mut scalars: Vec<SF> = (0..10).map(|_| SF::rand()).collect();
+        let expected = msm_execute_parallel(&precomputation, &scalars);
+        assert_eq!(cached_msm.execute(&scalars), expected);
+        assert_eq!(cached_msm.stats(), CachedMsmStats { hits: 0, misses: 1 });
+        assert_eq!(cached_msm.execute(&scalars), expected);
+        assert_eq!(cached_msm.stats(), CachedMsmStats { hits: 1, misses: 1 });
+
+        scalars[3] = scalars[3] + SF::ONE;
+        let expected = msm_execute_parallel(&precomputation, &scalars);
+        assert_eq!(cached_msm.execute(&scalars), expected);
+        assert_eq!(cached_msm.stats(), CachedMsmStats { hits: 1, misses: 2 });
+
+        cached_msm.clear();
+        assert_eq!(cached_msm.execute(&scalars), expected);
+        assert_eq!(cached_msm.stats(), CachedMsmStats { hits: 1, misses: 3 });
+    }
+}
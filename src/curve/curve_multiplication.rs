@@ -1,5 +1,6 @@
 use std::ops::Mul;
 
+use super::curve_msm::to_digits as to_window_digits;
 use crate::{affine_summation_batch_inversion, AffinePoint, Curve, CurveScalar, Field, ProjectivePoint};
 
 const WINDOW_BITS: usize = 4;
@@ -60,6 +61,78 @@ impl<C: Curve> ProjectivePoint<C> {
     }
 }
 
+/// A table of the multiples `[0] p, [1] p, ..., [2^w - 1] p` of a fixed point `p`, used for
+/// fixed-window scalar multiplication. Building the table costs `2^w` additions, after which each
+/// multiplication costs roughly `BITS` doublings and `BITS / w` mixed additions.
+#[derive(Clone, Debug)]
+pub struct WindowTable<C: Curve> {
+    window: usize,
+    multiples: Vec<AffinePoint<C>>,
+}
+
+impl<C: Curve> WindowTable<C> {
+    pub fn new(point: AffinePoint<C>, window: usize) -> Self {
+        assert!(window > 0 && window < 32, "Unsupported window size {}", window);
+        let mut multiples_proj = Vec::with_capacity(1 << window);
+        multiples_proj.push(ProjectivePoint::ZERO);
+        for i in 1..(1 << window) {
+            multiples_proj.push(multiples_proj[i - 1] + point);
+        }
+        let multiples = ProjectivePoint::batch_to_affine(&multiples_proj);
+        WindowTable { window, multiples }
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Computes `[scalar] p`, where `p` is the point this table was built from.
+    pub fn mul(&self, scalar: C::ScalarField) -> ProjectivePoint<C> {
+        let mut result = ProjectivePoint::ZERO;
+        for &digit in to_window_digits::<C>(&scalar, self.window).iter().rev() {
+            for _ in 0..self.window {
+                result = result.double();
+            }
+            if digit != 0 {
+                result = result + self.multiples[digit];
+            }
+        }
+        result
+    }
+}
+
+impl<C: Curve> AffinePoint<C> {
+    /// Returns a `WindowTable` for this point, which can be reused to compute several multiples of
+    /// it.
+    pub fn window_table(&self, window: usize) -> WindowTable<C> {
+        WindowTable::new(*self, window)
+    }
+
+    /// Computes `[scalar] self` using a fixed-window method. When multiplying the same point by
+    /// several scalars, it is cheaper to build a `WindowTable` once with `window_table`.
+    pub fn scalar_mul_windowed(&self, scalar: C::ScalarField, window: usize) -> ProjectivePoint<C> {
+        self.window_table(window).mul(scalar)
+    }
+}
+
+impl<C: Curve> ProjectivePoint<C> {
+    /// Computes `[n] self`, where `n` is given by little-endian `u64` limbs. Unlike a
+    /// `CurveScalar`, `n` need not be less than the scalar field order, so this can multiply by
+    /// constants such as `C::ORDER` or `C::COFACTOR`. This uses plain double-and-add.
+    pub fn mul_u64_limbs(&self, limbs: &[u64]) -> Self {
+        let mut result = ProjectivePoint::ZERO;
+        for &limb in limbs.iter().rev() {
+            for i in (0..64).rev() {
+                result = result.double();
+                if (limb >> i) & 1 != 0 {
+                    result = result + *self;
+                }
+            }
+        }
+        result
+    }
+}
+
 impl<C: Curve> Mul<ProjectivePoint<C>> for CurveScalar<C> {
     type Output = ProjectivePoint<C>;
 
@@ -69,6 +142,7 @@ impl<C: Curve> Mul<ProjectivePoint<C>> for CurveScalar<C> {
     }
 }
 
+/// Splits `x` into `WINDOW_BITS`-bit digits in little-endian order, as in `curve_msm::to_digits`.
 #[allow(clippy::assertions_on_constants)]
 fn to_digits<C: Curve>(x: &C::ScalarField) -> Vec<u64> {
     debug_assert!(64 % WINDOW_BITS == 0,
@@ -83,3 +157,78 @@ fn to_digits<C: Curve>(x: &C::ScalarField) -> Vec<u64> {
 
     digits
 }
+
+#[cfg(test)]
+mod tests {
+    use num::{BigUint, Zero};
+
+    use crate::{blake_hash_usize_to_curve, AffinePoint, Bls12377, Curve, Field, ProjectivePoint, Tweedledee, Tweedledum};
+
+    /// Naive double-and-add, used as a reference.
+    fn mul_naive<C: Curve>(scalar: C::ScalarField, point: AffinePoint<C>) -> ProjectivePoint<C> {
+        let mut result = ProjectivePoint::ZERO;
+        for bit in scalar.to_canonical_bool_vec().into_iter().rev() {
+            result = result.double();
+            if bit {
+                result = result + point;
+            }
+        }
+        result
+    }
+
+    fn check_scalar_mul_windowed<C: Curve>() {
+        let point = AffinePoint::<C>::random();
+        let scalars = [
+            C::ScalarField::ZERO,
+            C::ScalarField::ONE,
+            C::ScalarField::NEG_ONE,
+            C::ScalarField::rand(),
+            C::ScalarField::rand(),
+        ];
+        for window in 1..=6 {
+            let table = point.window_table(window);
+            for &scalar in scalars.iter() {
+                let expected = mul_naive(scalar, point);
+                assert_eq!(table.mul(scalar), expected, "window = {}", window);
+                assert_eq!(point.scalar_mul_windowed(scalar, window), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_scalar_mul_windowed() {
+        check_scalar_mul_windowed::<Tweedledum>();
+        check_scalar_mul_windowed::<Bls12377>();
+    }
+
+    fn check_order_and_cofactor<C: Curve>() {
+        let order = C::ORDER
+            .iter()
+            .rev()
+            .fold(BigUint::zero(), |acc, &limb| (acc << 64) + limb);
+        assert_eq!(order, C::ScalarField::modulus());
+        assert_eq!(
+            C::GENERATOR_PROJECTIVE.mul_u64_limbs(C::ORDER),
+            ProjectivePoint::ZERO
+        );
+        assert_eq!(
+            C::GENERATOR_PROJECTIVE.mul_u64_limbs(&[3]),
+            C::GENERATOR_PROJECTIVE.double() + C::GENERATOR_PROJECTIVE
+        );
+
+        // Multiplying an arbitrary point on the curve by the cofactor yields a point in the
+        // subgroup, whose order divides `ORDER`.
+        let point = C::clear_cofactor(blake_hash_usize_to_curve::<C>(0).to_projective());
+        assert!(point.to_affine().is_in_subgroup());
+        assert_eq!(point.mul_u64_limbs(C::ORDER), ProjectivePoint::ZERO);
+    }
+
+    #[test]
+    fn test_order_and_cofactor() {
+        check_order_and_cofactor::<Tweedledee>();
+        check_order_and_cofactor::<Tweedledum>();
+        check_order_and_cofactor::<Bls12377>();
+        assert!(Tweedledum::has_prime_order());
+        assert!(!Bls12377::has_prime_order());
+    }
+}
@@ -1,17 +1,23 @@
 pub use bls12_377_curve::*;
+#[cfg(feature = "bls-glv")]
+pub use bls12_377_glv::*;
 pub use curve::*;
 pub use curve_adds::*;
 pub use curve_msm::*;
+pub use curve_msm_cache::*;
 pub use curve_multiplication::*;
 pub use curve_summations::*;
 pub use tweedledee_curve::*;
 pub use tweedledum_curve::*;
 
 mod bls12_377_curve;
+#[cfg(feature = "bls-glv")]
+mod bls12_377_glv;
 #[allow(clippy::module_inception)]
 mod curve;
 mod curve_adds;
 mod curve_msm;
+mod curve_msm_cache;
 mod curve_multiplication;
 mod curve_summations;
 mod tweedledee_curve;
@@ -16,6 +16,11 @@ impl Curve for Tweedledee {
         y: TweedledeeBase::TWO,
         zero: false,
     };
+
+    const ORDER: &'static [u64] = &<TweedledumBase as crate::MontyRepr>::ORDER;
+
+    /// Tweedledee has prime order.
+    const COFACTOR: &'static [u64] = &[1];
 }
 
 impl HaloCurve for Tweedledee {
@@ -39,7 +44,11 @@ impl HaloCurve for Tweedledee {
 
 #[cfg(test)]
 mod tests {
-    use crate::curve::{Curve, HaloCurve, ProjectivePoint};
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+    use std::hash::{Hash, Hasher};
+
+    use crate::curve::{AffinePoint, Curve, HaloCurve, ProjectivePoint};
     use crate::{Field, Tweedledee};
 
     /// A simple, somewhat inefficient implementation of multiplication which is used as a reference
@@ -73,6 +82,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_in_subgroup_matches_generic() {
+        type C = Tweedledee;
+        let g = C::GENERATOR_PROJECTIVE;
+        for _ in 0..1000 {
+            let p = (C::convert(<C as Curve>::ScalarField::rand()) * g).to_affine();
+            assert!(p.is_in_subgroup());
+            assert_eq!(p.is_in_subgroup(), p.is_in_subgroup_generic());
+        }
+
+        let off_curve = AffinePoint::<C> {
+            x: C::GENERATOR_AFFINE.x,
+            y: C::GENERATOR_AFFINE.y.double(),
+            zero: false,
+        };
+        assert!(!off_curve.is_in_subgroup());
+        assert!(!off_curve.is_in_subgroup_generic());
+        assert!(AffinePoint::<C>::ZERO.is_in_subgroup());
+        assert!(AffinePoint::<C>::ZERO.is_in_subgroup_generic());
+    }
+
+    #[test]
+    fn test_is_in_subgroup() {
+        // Tweedledee has prime order, so is_in_subgroup only checks the curve equation.
+        type C = Tweedledee;
+        assert!(C::has_prime_order());
+        for _ in 0..10 {
+            let p = AffinePoint::<C>::random();
+            assert!(p.is_in_subgroup());
+
+            let off_curve = AffinePoint::<C> {
+                y: p.y + <C as Curve>::BaseField::ONE,
+                ..p
+            };
+            assert!(!off_curve.is_valid());
+            assert!(!off_curve.is_in_subgroup());
+        }
+    }
+
     #[test]
     fn is_safe_curve() {
         type C = Tweedledee;
@@ -80,4 +128,35 @@ mod tests {
            C::is_safe_curve()
         );
     }
+
+    #[test]
+    fn test_affine_eq_and_hash() {
+        type C = Tweedledee;
+        fn hash_of(p: AffinePoint<C>) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            p.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let g = C::GENERATOR_AFFINE;
+        let two_g = (g.to_projective() + g.to_projective()).to_affine();
+        let two_g_dbl = g.to_projective().double().to_affine();
+        assert_eq!(two_g, two_g_dbl);
+        assert_eq!(hash_of(two_g), hash_of(two_g_dbl));
+        assert_ne!(two_g, g);
+
+        // The identity equals itself, regardless of its coordinates, but not the generator.
+        let other_zero = AffinePoint::<C> {
+            x: g.x,
+            y: g.y,
+            zero: true,
+        };
+        assert_eq!(AffinePoint::<C>::ZERO, other_zero);
+        assert_eq!(hash_of(AffinePoint::ZERO), hash_of(other_zero));
+        assert_ne!(AffinePoint::<C>::ZERO, g);
+
+        let points: HashSet<AffinePoint<C>> =
+            vec![g, two_g, two_g_dbl, AffinePoint::ZERO, other_zero].into_iter().collect();
+        assert_eq!(points.len(), 3);
+    }
 }
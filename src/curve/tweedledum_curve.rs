@@ -31,6 +31,11 @@ impl Curve for Tweedledum {
         },
         zero: false,
     };
+
+    const ORDER: &'static [u64] = &<TweedledeeBase as crate::MontyRepr>::ORDER;
+
+    /// Tweedledum has prime order.
+    const COFACTOR: &'static [u64] = &[1];
 }
 
 impl HaloCurve for Tweedledum {
@@ -54,7 +59,7 @@ impl HaloCurve for Tweedledum {
 
 #[cfg(test)]
 mod tests {
-    use crate::curve::{Curve, HaloCurve, ProjectivePoint};
+    use crate::curve::{AffinePoint, Curve, HaloCurve, ProjectivePoint};
     use crate::{Tweedledum, Field};
 
     /// A simple, somewhat inefficient implementation of multiplication which is used as a reference
@@ -88,6 +93,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_in_subgroup_matches_generic() {
+        type C = Tweedledum;
+        let g = C::GENERATOR_PROJECTIVE;
+        for _ in 0..1000 {
+            let p = (C::convert(<C as Curve>::ScalarField::rand()) * g).to_affine();
+            assert!(p.is_in_subgroup());
+            assert_eq!(p.is_in_subgroup(), p.is_in_subgroup_generic());
+        }
+
+        let off_curve = AffinePoint::<C> {
+            x: C::GENERATOR_AFFINE.x,
+            y: C::GENERATOR_AFFINE.y.double(),
+            zero: false,
+        };
+        assert!(!off_curve.is_in_subgroup());
+        assert!(!off_curve.is_in_subgroup_generic());
+        assert!(AffinePoint::<C>::ZERO.is_in_subgroup());
+        assert!(AffinePoint::<C>::ZERO.is_in_subgroup_generic());
+    }
+
+    #[test]
+    fn test_random_in_subgroup() {
+        type C = Tweedledum;
+        for _ in 0..10 {
+            let p = AffinePoint::<C>::random();
+            assert!(p.is_valid());
+            assert!(p.is_in_subgroup_generic());
+        }
+    }
+
+    #[test]
+    fn test_is_in_subgroup() {
+        // Tweedledum has prime order, so is_in_subgroup only checks the curve equation.
+        type C = Tweedledum;
+        assert!(C::has_prime_order());
+        for _ in 0..10 {
+            let p = AffinePoint::<C>::random();
+            assert!(p.is_in_subgroup());
+
+            let off_curve = AffinePoint::<C> {
+                y: p.y + <C as Curve>::BaseField::ONE,
+                ..p
+            };
+            assert!(!off_curve.is_valid());
+            assert!(!off_curve.is_in_subgroup());
+        }
+    }
+
     #[test]
     fn is_safe_curve() {
         type C = Tweedledum;
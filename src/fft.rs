@@ -88,8 +88,8 @@ pub fn ifft_with_precomputation_power_of_2<F: Field>(
     let mut result = fft_with_precomputation_power_of_2(points, precomputation);
 
     // We reverse all values except the first, and divide each by n.
-    result[0] = result[0] * n_inv;
-    result[n / 2] = result[n / 2] * n_inv;
+    result[0] *= n_inv;
+    result[n / 2] *= n_inv;
     for i in 1..(n / 2) {
         let j = n - i;
         let result_i = result[j] * n_inv;
@@ -155,11 +155,78 @@ pub fn fft_with_precomputation_power_of_2<F: Field>(
     reverse_index_bits(evaluations)
 }
 
+/// A multiplicative subgroup of `F^*` whose order is a power of two, over which polynomials of
+/// degree less than the order can be interpolated and evaluated using FFTs.
+#[derive(Debug, Clone)]
+pub struct Domain<F: Field> {
+    generator: F,
+    precomputation: FftPrecomputation<F>,
+}
+
+impl<F: Field> Domain<F> {
+    pub fn new(size: usize) -> Self {
+        let size_pow = log2_strict(size);
+        Domain {
+            generator: F::primitive_root_of_unity(size_pow),
+            precomputation: fft_precompute(size),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.precomputation.size()
+    }
+
+    /// Returns the generator `g` of the domain, whose powers `g^i` are the points at which
+    /// `evaluate_over_domain` evaluates.
+    pub fn generator(&self) -> F {
+        self.generator
+    }
+
+    /// Returns the elements `[1, g, g^2, ...]` of the domain.
+    pub fn elements(&self) -> Vec<F> {
+        F::cyclic_subgroup_known_order(self.generator, self.size())
+    }
+
+    pub fn precomputation(&self) -> &FftPrecomputation<F> {
+        &self.precomputation
+    }
+}
+
+/// Returns the coefficients of the unique polynomial of degree less than `domain.size()` whose
+/// value at `g^i` is `evals[i]`, using an inverse FFT.
+pub fn interpolate<F: Field>(domain: &Domain<F>, evals: &[F]) -> Vec<F> {
+    assert_eq!(
+        evals.len(),
+        domain.size(),
+        "Expected one evaluation per domain element"
+    );
+    ifft_with_precomputation_power_of_2(evals, &domain.precomputation)
+}
+
+/// Evaluates a polynomial, given by its coefficients, at each element `g^i` of the domain, using an
+/// FFT. The polynomial's degree must be less than `domain.size()`.
+pub fn evaluate_over_domain<F: Field>(domain: &Domain<F>, coeffs: &[F]) -> Vec<F> {
+    assert!(
+        coeffs.len() <= domain.size(),
+        "Polynomial has {} coefficients, but the domain only has {} elements",
+        coeffs.len(),
+        domain.size()
+    );
+    let mut coeffs_padded = coeffs.to_vec();
+    coeffs_padded.resize(domain.size(), F::ZERO);
+    fft_with_precomputation_power_of_2(&coeffs_padded, &domain.precomputation)
+}
+
+/// Evaluates a polynomial, given by its coefficients, at a single point using Horner's method.
+pub fn evaluate_at<F: Field>(coeffs: &[F], z: F) -> F {
+    coeffs.iter().rev().fold(F::ZERO, |acc, &c| acc * z + c)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::fft::{log2_strict, reverse_bits, reverse_index_bits};
     use crate::util::log2_ceil;
-    use crate::{fft_precompute, fft_with_precomputation, ifft_with_precomputation_power_of_2, Bls12377Scalar, Field};
+    use crate::{evaluate_at, evaluate_over_domain, fft_precompute, fft_with_precomputation, ifft_with_precomputation_power_of_2, interpolate, Bls12377Scalar, Domain, Field, TweedledeeBase};
 
     #[test]
     fn fft_and_ifft() {
@@ -184,6 +251,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_domain_interpolation_round_trip() {
+        type F = TweedledeeBase;
+        let domain = Domain::<F>::new(16);
+        assert_eq!(domain.size(), 16);
+        assert_eq!(domain.generator().exp_usize(16), F::ONE);
+        assert_ne!(domain.generator().exp_usize(8), F::ONE);
+
+        let evals: Vec<F> = (0..16).map(|_| F::rand()).collect();
+        let coeffs = interpolate(&domain, &evals);
+        assert_eq!(evaluate_over_domain(&domain, &coeffs), evals);
+        for (&x, &y) in domain.elements().iter().zip(&evals) {
+            assert_eq!(evaluate_at(&coeffs, x), y);
+        }
+    }
+
+    #[test]
+    fn test_domain_recovers_low_degree_polynomial() {
+        type F = TweedledeeBase;
+        let domain = Domain::<F>::new(32);
+
+        for degree in [1, 7, 20, 32].iter().copied() {
+            let coeffs: Vec<F> = (0..degree).map(|_| F::rand()).collect();
+            let evals = evaluate_over_domain(&domain, &coeffs);
+            let expected_evals: Vec<F> = domain
+                .elements()
+                .into_iter()
+                .map(|x| evaluate_at_naive(&coeffs, x))
+                .collect();
+            assert_eq!(evals, expected_evals);
+
+            let mut expected_coeffs = coeffs.clone();
+            expected_coeffs.resize(domain.size(), F::ZERO);
+            assert_eq!(interpolate(&domain, &evals), expected_coeffs);
+        }
+    }
+
     #[test]
     fn test_reverse_bits() {
         assert_eq!(reverse_bits(0b00110101, 8), 0b10101100);
@@ -7,7 +7,7 @@ use rand::Rng;
 
 use unroll::unroll_for_loops;
 
-use crate::{add_no_overflow, cmp, Field, mul2, rand_range, rand_range_from_rng, sub, field_to_biguint};
+use crate::{add_no_overflow, cmp, Field, PrimeField, mul2, rand_range, rand_range_from_rng, sub, field_to_biguint};
 use crate::nonzero_multiplicative_inverse;
 use std::cmp::Ordering;
 use std::fmt::{Formatter, Display};
@@ -166,6 +166,10 @@ impl Neg for Bls12377Base {
     }
 }
 
+impl PrimeField for Bls12377Base {
+    const MODULUS: &'static [u64] = &Self::ORDER;
+}
+
 impl Field for Bls12377Base {
     const BITS: usize = 377;
     const BYTES: usize = 48;
@@ -7,7 +7,7 @@ use rand::Rng;
 
 use unroll::unroll_for_loops;
 
-use crate::{add_no_overflow, cmp, Field, sub, field_to_biguint, rand_range, rand_range_from_rng};
+use crate::{add_no_overflow, cmp, Field, PrimeField, sub, field_to_biguint, rand_range, rand_range_from_rng};
 use crate::nonzero_multiplicative_inverse;
 use std::cmp::Ordering;
 use std::fmt;
@@ -151,6 +151,10 @@ impl Neg for Bls12377Scalar {
     }
 }
 
+impl PrimeField for Bls12377Scalar {
+    const MODULUS: &'static [u64] = &Self::ORDER;
+}
+
 impl Field for Bls12377Scalar {
     const BITS: usize = 253;
     const BYTES: usize = 32;
@@ -232,6 +236,13 @@ mod tests {
     use crate::conversions::u64_slice_to_biguint;
     use crate::test_arithmetic;
 
+    #[test]
+    fn bls12scalar_bits() {
+        assert_eq!(Bls12377Scalar::BITS, 253);
+        assert_eq!(Bls12377Scalar::modulus_bits(), 253);
+        assert_eq!(Bls12377Scalar::modulus(), u64_slice_to_biguint(&Bls12377Scalar::ORDER));
+    }
+
     #[test]
     fn bls12scalar_to_and_from_canonical() {
         let a = [1, 2, 3, 4];
@@ -3,14 +3,16 @@ use std::cmp::Ordering::Equal;
 use std::collections::HashSet;
 use std::fmt::{Debug, Display};
 use std::hash::Hash;
-use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
 
 use anyhow::{Error, Result};
 use num::{BigUint, Integer, One, Zero};
 use rand::Rng;
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::{biguint_to_field, Curve, field_to_biguint, ProjectivePoint};
+use crate::util::ceil_div_usize;
+use crate::{biguint_to_field, Bls12377Base, Bls12377Scalar, Curve, field_to_biguint, ProjectivePoint, TweedledeeBase, TweedledumBase};
 
 pub trait Field:
     'static
@@ -28,6 +30,9 @@ pub trait Field:
     + Sub<Self, Output = Self>
     + Mul<Self, Output = Self>
     + Div<Self, Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
     + Serialize
     + DeserializeOwned
 {
@@ -54,6 +59,10 @@ pub trait Field:
     /// `T = (ORDER - 1) / 2^TWO_ADICITY`
     const T: Self;
 
+    /// `mul_by_small` uses an addition chain for multipliers below this bound, and a full
+    /// multiplication otherwise.
+    const MUL_BY_SMALL_THRESHOLD: u64 = 16;
+
     fn to_canonical_u64_vec(&self) -> Vec<u64>;
 
     fn to_canonical_u32_vec(&self) -> Vec<u32> {
@@ -112,8 +121,53 @@ pub trait Field:
         limbs
     }
 
+    /// Returns the `BITS` bits of this element's canonical representation, in little-endian order.
+    fn to_bits_le(&self) -> Vec<bool> {
+        let mut bits = self.to_canonical_bool_vec();
+        bits.truncate(Self::modulus_bits());
+        bits
+    }
+
+    /// Converts the little-endian bits of a canonical representation to a field element. Returns
+    /// `None` if the bits encode a value which is not less than the field order.
+    fn from_bits_le(bits: &[bool]) -> Option<Self> {
+        let mut limbs = vec![0u64; ceil_div_usize(Self::modulus_bits(), 64)];
+        for (i, &bit) in bits.iter().enumerate() {
+            if bit {
+                *limbs.get_mut(i / 64)? |= 1 << (i % 64);
+            }
+        }
+        if Self::is_valid_canonical_u64(&limbs) {
+            Some(Self::from_canonical_u64_vec(limbs))
+        } else {
+            None
+        }
+    }
+
     fn from_canonical_u64_vec(v: Vec<u64>) -> Self;
 
+    /// Returns the canonical (i.e. non-Montgomery) representation of this element, as
+    /// little-endian `u64` limbs. `N` must be the number of limbs used by this field, i.e.
+    /// `ceil(BITS / 64)`.
+    fn to_canonical_u64_array<const N: usize>(&self) -> [u64; N] {
+        let limbs = self.to_canonical_u64_vec();
+        assert_eq!(limbs.len(), N, "This field uses {} limbs, not {}", limbs.len(), N);
+        let mut array = [0; N];
+        array.copy_from_slice(&limbs);
+        array
+    }
+
+    /// Converts little-endian `u64` limbs of a canonical representation to a field element. Fails
+    /// if `N` is not the number of limbs used by this field, or if the value is not less than the
+    /// field order.
+    fn from_canonical_u64_array<const N: usize>(limbs: [u64; N]) -> Result<Self> {
+        if Self::is_valid_canonical_u64(&limbs) {
+            Ok(Self::from_canonical_u64_vec(limbs.to_vec()))
+        } else {
+            Err(Error::msg("Out of range"))
+        }
+    }
+
     fn from_canonical_u32_vec(u32_limbs: Vec<u32>) -> Self {
         let mut u64_chunks = Vec::new();
         for u32_chunk in u32_limbs.chunks(2) {
@@ -142,6 +196,15 @@ pub trait Field:
 
     fn is_valid_canonical_u64(v: &[u64]) -> bool;
 
+    /// Returns whether this element's internal representation is the canonical one, i.e. is
+    /// reduced modulo the field order. Elements produced by field operations always are, but an
+    /// element constructed directly from its representation, e.g. via public limbs or untrusted
+    /// deserialization, may not be. Such elements can compare unequal to equivalent canonical
+    /// ones.
+    fn is_canonical(&self) -> bool {
+        Self::from_canonical_u64_vec(self.to_canonical_u64_vec()) == *self
+    }
+
     #[inline(always)]
     fn is_zero(&self) -> bool {
         *self == Self::ZERO
@@ -180,17 +243,41 @@ pub trait Field:
 
     #[inline(always)]
     fn double(&self) -> Self {
-        *self * Self::TWO
+        *self + *self
     }
 
     #[inline(always)]
     fn triple(&self) -> Self {
-        *self * Self::THREE
+        self.double() + *self
     }
 
     #[inline(always)]
     fn quadruple(&self) -> Self {
-        *self * Self::FOUR
+        self.double().double()
+    }
+
+    /// Computes `self * k`. Field additions are much cheaper than Montgomery multiplications, so
+    /// for `k < MUL_BY_SMALL_THRESHOLD`, this uses a chain of at most `2 log2(k)` doublings and
+    /// additions, processing the bits of `k` from most to least significant.
+    fn mul_by_small(&self, k: u64) -> Self {
+        match k {
+            0 => Self::ZERO,
+            1 => *self,
+            2 => self.double(),
+            3 => self.triple(),
+            4 => self.quadruple(),
+            k if k < Self::MUL_BY_SMALL_THRESHOLD => {
+                let mut result = *self;
+                for i in (0..(63 - k.leading_zeros())).rev() {
+                    result = result.double();
+                    if (k >> i) & 1 != 0 {
+                        result += *self;
+                    }
+                }
+                result
+            }
+            k => *self * Self::from_canonical_u64(k),
+        }
     }
 
     fn scale_slice(&self, slice: &[Self]) -> Vec<Self> {
@@ -216,7 +303,7 @@ pub trait Field:
         assert_eq!(a.len(), b.len());
         let mut sum = Self::ZERO;
         for (&a_i, &b_i) in a.iter().zip(b.iter()) {
-            sum = sum + a_i * b_i;
+            sum += a_i * b_i;
         }
         sum
     }
@@ -249,6 +336,12 @@ pub trait Field:
         result
     }
 
+    /// Inverts each of the given elements, returning `None` for zeros. Only the nonzero elements
+    /// are batch inverted with Montgomery's trick, so zeros cost nothing extra.
+    fn try_inverse_batch(elements: &[Self]) -> Vec<Option<Self>> {
+        Self::batch_multiplicative_inverse_opt(elements)
+    }
+
     fn batch_multiplicative_inverse<F: Field>(x: &[F]) -> Vec<F> {
         // This is Montgomery's trick. At a high level, we invert the product of the given field
         // elements, then derive the individual inverses from that via multiplication.
@@ -278,6 +371,19 @@ pub trait Field:
         x_inv
     }
 
+    /// Returns an iterator over the powers of this element, `1, x, x^2, ...`.
+    fn powers(&self) -> Powers<Self> {
+        Powers {
+            base: *self,
+            current: Self::ONE,
+        }
+    }
+
+    /// Returns `[1, base, base^2, ..., base^(n - 1)]`.
+    fn powers_of(base: Self, n: usize) -> Vec<Self> {
+        base.powers().take(n).collect()
+    }
+
     fn cyclic_subgroup_unknown_order(generator: Self) -> Vec<Self> {
         let mut subgroup = Vec::new();
         let mut current = generator;
@@ -285,7 +391,7 @@ pub trait Field:
         subgroup.push(Self::ONE);
         while current != Self::ONE {
             subgroup.push(current);
-            current = current * generator;
+            current *= generator;
         }
         subgroup
     }
@@ -295,7 +401,7 @@ pub trait Field:
         let mut current = Self::ONE;
         for _i in 0..order {
             subgroup.push(current);
-            current = current * generator;
+            current *= generator;
         }
         subgroup
     }
@@ -317,7 +423,7 @@ pub trait Field:
             // break inside the loop.
             for j in 0..min(64, power_bits) {
                 if (limb >> j & 1) != 0 {
-                    product = product * current;
+                    product *= current;
                 }
                 current = current.square();
             }
@@ -391,6 +497,19 @@ pub trait Field:
         panic!("Number theory is a lie!")
     }
 
+    /// The order of this field, which is also its characteristic, since all of our fields have
+    /// prime order. Its bit length is `BITS`.
+    fn modulus() -> BigUint {
+        field_to_biguint(Self::NEG_ONE) + BigUint::one()
+    }
+
+    /// The bit length of this field's order, i.e. `BITS`. Unlike `num_bits`, which is the bit
+    /// length of a particular element, this is a property of the field, for generic code which
+    /// decomposes elements into bits or digits.
+    fn modulus_bits() -> usize {
+        Self::BITS
+    }
+
     /// The number of bits in the binary encoding of this field element.
     fn num_bits(&self) -> usize {
         // Search for the most significant nonzero limb.
@@ -463,8 +582,8 @@ pub trait Field:
                 }
 
                 z = w.square();
-                b = b * z;
-                x = x * w;
+                b *= z;
+                x *= w;
                 v = k;
             }
             Some(x)
@@ -473,6 +592,36 @@ pub trait Field:
         }
     }
 
+    /// Parses a field element from a string of digits in the given radix. A leading `-` is
+    /// accepted, in which case the result is negated, and with a radix of 16 an optional `0x`
+    /// prefix is accepted. Returns `None` if the string is malformed or the value is not less than
+    /// the field's order.
+    fn from_str_radix(s: &str, radix: u32) -> Option<Self> {
+        if !(2..=36).contains(&radix) {
+            return None;
+        }
+
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let digits = if radix == 16 {
+            digits
+                .strip_prefix("0x")
+                .or_else(|| digits.strip_prefix("0X"))
+                .unwrap_or(digits)
+        } else {
+            digits
+        };
+
+        let value = BigUint::parse_bytes(digits.as_bytes(), radix)?;
+        if value > field_to_biguint(Self::NEG_ONE) {
+            return None;
+        }
+        let x: Self = biguint_to_field(value);
+        Some(if negative { -x } else { x })
+    }
+
     /// Return this field element re-encoded as an element of `F` if it fits, or `Err` if not.
     fn try_convert<F: Field>(&self) -> Result<F> {
         F::from_canonical_u8_vec(self.to_canonical_u8_vec())
@@ -481,15 +630,144 @@ pub trait Field:
     fn try_convert_all<F: Field>(values: &[Self]) -> Result<Vec<F>> {
         values.iter().map(|f| f.try_convert::<F>()).collect()
     }
+
+    /// Maps an element of another field to the element of this field with the same canonical
+    /// representative, i.e. the same integer in `[0, |F|)`. Returns `None` if that integer is not
+    /// less than this field's order. Unlike `try_convert`, this works between fields with
+    /// different numbers of limbs.
+    ///
+    /// On the Tweedle cycle, this is how a base field element of one curve is reinterpreted as a
+    /// scalar field element of the other.
+    fn from_other_field<F: Field>(x: F) -> Option<Self> {
+        let x_biguint = field_to_biguint(x);
+        if x_biguint > field_to_biguint(Self::NEG_ONE) {
+            None
+        } else {
+            Some(biguint_to_field(x_biguint))
+        }
+    }
+}
+
+/// A field of prime order, as opposed to an extension field.
+///
+/// All of our fields currently have prime order, so `Field` itself provides operations which are
+/// specific to prime fields, such as `is_quadratic_residue`, `square_root` and the canonical bit
+/// decompositions. Generic code which relies on those should still require `PrimeField`, so that
+/// it keeps its meaning if extension fields are added later; those would then implement `Field`
+/// but not `PrimeField`, and operations like a Frobenius map would be added alongside them.
+pub trait PrimeField: Field {
+    /// The order of the field, as little-endian `u64` limbs.
+    const MODULUS: &'static [u64];
+
+    /// The bit length of `MODULUS`.
+    const NUM_BITS: usize = Self::BITS;
+}
+
+/// An endless iterator over the powers of a field element; see `Field::powers`.
+#[derive(Clone, Debug)]
+pub struct Powers<F: Field> {
+    base: F,
+    current: F,
+}
+
+impl<F: Field> Iterator for Powers<F> {
+    type Item = F;
+
+    fn next(&mut self) -> Option<F> {
+        let result = self.current;
+        self.current *= self.base;
+        Some(result)
+    }
 }
 
+/// Implements `FromStr` for a field type, parsing decimal strings via `Field::from_str_radix`.
+macro_rules! impl_from_str_field {
+    ($field:ty) => {
+        impl FromStr for $field {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self> {
+                <$field>::from_str_radix(s, 10)
+                    .ok_or_else(|| Error::msg(format!("Invalid field element: {}", s)))
+            }
+        }
+    };
+}
+
+impl_from_str_field!(TweedledumBase);
+impl_from_str_field!(TweedledeeBase);
+impl_from_str_field!(Bls12377Base);
+impl_from_str_field!(Bls12377Scalar);
+
+/// Implements `AddAssign`, `SubAssign` and `MulAssign` for a field type, along with `Add`, `Sub` and
+/// `Mul` for references, in terms of its by-value operators.
+macro_rules! impl_assign_and_ref_ops_field {
+    ($field:ty) => {
+        impl AddAssign for $field {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $field {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl MulAssign for $field {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl_ref_op_field!($field, Add, add);
+        impl_ref_op_field!($field, Sub, sub);
+        impl_ref_op_field!($field, Mul, mul);
+    };
+}
+
+/// Implements a binary operator for `&F op &F`, `F op &F` and `&F op F`.
+macro_rules! impl_ref_op_field {
+    ($field:ty, $op:ident, $method:ident) => {
+        impl<'a> $op<&'a $field> for &'a $field {
+            type Output = $field;
+
+            fn $method(self, rhs: &'a $field) -> $field {
+                $op::$method(*self, *rhs)
+            }
+        }
+
+        impl<'a> $op<&'a $field> for $field {
+            type Output = $field;
+
+            fn $method(self, rhs: &'a $field) -> $field {
+                $op::$method(self, *rhs)
+            }
+        }
+
+        impl<'a> $op<$field> for &'a $field {
+            type Output = $field;
+
+            fn $method(self, rhs: $field) -> $field {
+                $op::$method(*self, rhs)
+            }
+        }
+    };
+}
+
+impl_assign_and_ref_ops_field!(TweedledumBase);
+impl_assign_and_ref_ops_field!(TweedledeeBase);
+impl_assign_and_ref_ops_field!(Bls12377Base);
+impl_assign_and_ref_ops_field!(Bls12377Scalar);
+
 #[cfg(test)]
 pub mod field_tests {
     use std::io::Result;
 
     use num::{BigUint, One, Zero};
 
-    use crate::{biguint_to_field, Field, field_to_biguint};
+    use crate::{biguint_to_field, Field, field_to_biguint, PrimeField};
     use crate::util::ceil_div_usize;
 
     /// Generates a series of non-negative integers less than
@@ -574,6 +852,19 @@ pub mod field_tests {
         Ok(())
     }
 
+    /// Checks that the `PrimeField` constants of `F` match its `Field` modulus.
+    pub fn check_prime_field<F: PrimeField>() {
+        let modulus = BigUint::from_slice(
+            &F::MODULUS
+                .iter()
+                .flat_map(|&limb| vec![limb as u32, (limb >> 32) as u32])
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(modulus, F::modulus());
+        assert_eq!(modulus.bits() as usize, F::NUM_BITS);
+        assert_eq!(F::MODULUS.len(), (F::NUM_BITS + 63) / 64);
+    }
+
     /// Apply the binary functions `op` and `expected_op` to each pair
     /// in `zip(inputs, rotate_right(inputs, i))` where `inputs` is
     /// `test_inputs(modulus, word_bits)` and `i` ranges from 0 to
@@ -620,24 +911,19 @@ pub mod field_tests {
 macro_rules! test_arithmetic {
     ($field:ty) => {
         mod arithmetic {
-            use crate::{biguint_to_field, field_tests, field_to_biguint, Field};
+            use crate::{biguint_to_field, field_tests, Field};
 
             use num::{BigUint, Zero};
             use std::io::Result;
             use std::ops::{Add, Div, Mul, Neg, Sub};
 
-            /// Return the modulus of the type `Fld`.
-            fn field_modulus<F: Field>() -> BigUint {
-                field_to_biguint(F::NEG_ONE) + 1u32
-            }
-
             // Can be 32 or 64; doesn't have to be computer's actual word
             // bits. Choosing 32 gives more tests...
             const WORD_BITS: usize = 32;
 
             #[test]
             fn arithmetic_addition() -> Result<()> {
-                let modulus = field_modulus::<$field>();
+                let modulus = <$field>::modulus();
                 field_tests::run_binaryop_test_cases(&modulus, WORD_BITS, <$field>::add, |x, y| {
                     let z = x + y;
                     if z < modulus {
@@ -650,7 +936,7 @@ macro_rules! test_arithmetic {
 
             #[test]
             fn arithmetic_subtraction() -> Result<()> {
-                let modulus = field_modulus::<$field>();
+                let modulus = <$field>::modulus();
                 field_tests::run_binaryop_test_cases(&modulus, WORD_BITS, <$field>::sub, |x, y| {
                     if x >= y {
                         x - y
@@ -662,7 +948,7 @@ macro_rules! test_arithmetic {
 
             #[test]
             fn arithmetic_negation() -> Result<()> {
-                let modulus = field_modulus::<$field>();
+                let modulus = <$field>::modulus();
                 field_tests::run_unaryop_test_cases(&modulus, WORD_BITS, <$field>::neg, |x| {
                     if x.is_zero() {
                         BigUint::zero()
@@ -674,7 +960,7 @@ macro_rules! test_arithmetic {
 
             #[test]
             fn arithmetic_multiplication() -> Result<()> {
-                let modulus = field_modulus::<$field>();
+                let modulus = <$field>::modulus();
                 field_tests::run_binaryop_test_cases(&modulus, WORD_BITS, <$field>::mul, |x, y| {
                     x * y % &modulus
                 })
@@ -682,7 +968,7 @@ macro_rules! test_arithmetic {
 
             #[test]
             fn arithmetic_square() -> Result<()> {
-                let modulus = field_modulus::<$field>();
+                let modulus = <$field>::modulus();
                 field_tests::run_unaryop_test_cases(
                     &modulus, WORD_BITS,
                     |x| <$field>::square(&x),
@@ -695,7 +981,7 @@ macro_rules! test_arithmetic {
                 // This test takes ages to finish so is #[ignore]d by default.
                 // TODO: Re-enable and reimplement when
                 // https://github.com/rust-num/num-bigint/issues/60 is finally resolved.
-                let modulus = field_modulus::<$field>();
+                let modulus = <$field>::modulus();
                 field_tests::run_binaryop_test_cases(
                     &modulus,
                     WORD_BITS,
@@ -728,7 +1014,7 @@ macro_rules! test_arithmetic {
                 // NB: Could calculate modular sqrt with BigUint with
                 // x^{(p+1)/2} (mod p) but this will be slow as with modular
                 // inverse above.
-                let modulus = field_modulus::<$field>();
+                let modulus = <$field>::modulus();
                 const WORD_BITS: usize = 32;
                 let inputs = field_tests::test_inputs(&modulus, WORD_BITS)
                     .iter()
@@ -743,6 +1029,154 @@ macro_rules! test_arithmetic {
                 Ok(())
             }
 
+            #[test]
+            fn from_str_radix() {
+                let sixteen = <$field>::from_canonical_u64(16);
+                assert_eq!(<$field>::from_str_radix("0x10", 16), Some(sixteen));
+                assert_eq!(<$field>::from_str_radix("10", 16), Some(sixteen));
+                assert_eq!(<$field>::from_str_radix("16", 10), Some(sixteen));
+                assert_eq!("16".parse::<$field>().unwrap(), sixteen);
+                assert_eq!(<$field>::from_str_radix("-1", 10), Some(<$field>::NEG_ONE));
+
+                let modulus = <$field>::modulus();
+                assert_eq!(<$field>::from_str_radix(&modulus.to_str_radix(10), 10), None);
+                assert_eq!(<$field>::from_str_radix(&modulus.to_str_radix(16), 16), None);
+                assert!(modulus.to_string().parse::<$field>().is_err());
+                let neg_one = (&modulus - 1u32).to_string();
+                assert_eq!(neg_one.parse::<$field>().unwrap(), <$field>::NEG_ONE);
+
+                assert_eq!(<$field>::from_str_radix("", 10), None);
+                assert_eq!(<$field>::from_str_radix("12a", 10), None);
+                assert!("0x10".parse::<$field>().is_err());
+            }
+
+            #[test]
+            fn prime_field_modulus() {
+                field_tests::check_prime_field::<$field>();
+            }
+
+            #[test]
+            fn modulus_consistent_with_bits() {
+                let modulus = <$field>::modulus();
+                assert_eq!(modulus.bits() as usize, <$field>::BITS);
+                assert_eq!(<$field>::modulus_bits(), <$field>::BITS);
+                assert_eq!((<$field>::BITS + 7) / 8, <$field>::BYTES);
+                assert_eq!(biguint_to_field::<$field>(modulus - 1u32), <$field>::NEG_ONE);
+            }
+
+            #[test]
+            fn canonical_u64_array_round_trip() {
+                const N: usize = (<$field>::BITS + 63) / 64;
+                let top_limb_bits = <$field>::BITS - 64 * (N - 1);
+                for x in vec![<$field>::ZERO, <$field>::ONE, <$field>::NEG_ONE, <$field>::rand()] {
+                    let limbs = x.to_canonical_u64_array::<N>();
+                    assert_eq!(limbs.to_vec(), x.to_canonical_u64_vec());
+                    assert_eq!(<$field>::from_canonical_u64_array(limbs).unwrap(), x);
+                    assert!(top_limb_bits == 64 || limbs[N - 1] >> top_limb_bits == 0);
+                }
+
+                // The field order itself is out of range.
+                let mut order = <$field>::NEG_ONE.to_canonical_u64_array::<N>();
+                order[0] += 1;
+                assert!(<$field>::from_canonical_u64_array(order).is_err());
+                assert!(<$field>::from_canonical_u64_array([u64::MAX; N]).is_err());
+                assert!(<$field>::from_canonical_u64_array([0u64; 1]).is_err());
+            }
+
+            #[test]
+            fn try_inverse_batch() {
+                let x = <$field>::rand();
+                let y = <$field>::rand();
+                let elements = [<$field>::ZERO, x, <$field>::ZERO, <$field>::ZERO, y, <$field>::ONE];
+                let inverses = <$field>::try_inverse_batch(&elements);
+                assert_eq!(inverses.len(), elements.len());
+                for (&element, &inverse) in elements.iter().zip(&inverses) {
+                    assert_eq!(inverse, element.multiplicative_inverse());
+                }
+                assert_eq!(inverses[4].unwrap() * y, <$field>::ONE);
+                assert_eq!(<$field>::try_inverse_batch(&[]), vec![]);
+                assert_eq!(<$field>::try_inverse_batch(&[<$field>::ZERO]), vec![None]);
+            }
+
+            #[test]
+            fn mul_by_small_matches_mul() {
+                let x = <$field>::rand();
+                for k in (0..=16).chain(vec![31, 1000, u64::MAX]) {
+                    assert_eq!(x.mul_by_small(k), x * <$field>::from_canonical_u64(k), "k = {}", k);
+                }
+                assert_eq!(<$field>::NEG_ONE.mul_by_small(15), -<$field>::from_canonical_u64(15));
+                assert_eq!(x.double(), x * <$field>::TWO);
+                assert_eq!(x.triple(), x * <$field>::THREE);
+                assert_eq!(x.quadruple(), x * <$field>::FOUR);
+            }
+
+            #[test]
+            fn bits_le_round_trip() {
+                for x in [<$field>::ZERO, <$field>::ONE, <$field>::NEG_ONE, <$field>::rand()].iter() {
+                    let bits = x.to_bits_le();
+                    assert_eq!(bits.len(), <$field>::BITS);
+                    assert_eq!(<$field>::from_bits_le(&bits), Some(*x));
+                }
+                assert_eq!(<$field>::from_bits_le(&[]), Some(<$field>::ZERO));
+                assert_eq!(
+                    <$field>::from_bits_le(&[true, false, true]),
+                    Some(<$field>::from_canonical_u64(5))
+                );
+
+                // Bits beyond the field's limbs can only be padding.
+                let mut padded = <$field>::TWO.to_bits_le();
+                padded.resize(<$field>::BITS + 64, false);
+                assert_eq!(<$field>::from_bits_le(&padded), Some(<$field>::TWO));
+                padded.push(true);
+                assert_eq!(<$field>::from_bits_le(&padded), None);
+            }
+
+            #[test]
+            fn from_bits_le_rejects_order() {
+                let order_bits: Vec<bool> = <$field>::modulus()
+                    .to_radix_le(2)
+                    .into_iter()
+                    .map(|digit| digit == 1)
+                    .collect();
+                assert_eq!(order_bits.len(), <$field>::BITS);
+                assert_eq!(<$field>::from_bits_le(&order_bits), None);
+            }
+
+            #[test]
+            fn assign_operators_match_binary_operators() {
+                for _ in 0..10 {
+                    let x = <$field>::rand();
+                    let y = <$field>::rand();
+
+                    let mut z = x;
+                    z += y;
+                    assert_eq!(z, x + y);
+                    let mut z = x;
+                    z -= y;
+                    assert_eq!(z, x - y);
+                    let mut z = x;
+                    z *= y;
+                    assert_eq!(z, x * y);
+
+                    assert_eq!(&x + &y, x + y);
+                    assert_eq!(&x - &y, x - y);
+                    assert_eq!(&x * &y, x * y);
+                    assert_eq!(x + &y, x + y);
+                    assert_eq!(&x - y, x - y);
+                }
+            }
+
+            #[test]
+            fn powers_of() {
+                let z = <$field>::rand();
+                assert_eq!(
+                    <$field>::powers_of(z, 5),
+                    vec![<$field>::ONE, z, z.square(), z.cube(), z.exp_usize(4)]
+                );
+                assert_eq!(z.powers().nth(7), Some(z.exp_usize(7)));
+                assert!(<$field>::powers_of(z, 0).is_empty());
+            }
+
             #[test]
             fn kth_root_consistent_with_exp() {
                 let degs = [5, 7, 11, 13, 17, 19, 23, 101];
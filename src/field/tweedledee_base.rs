@@ -8,7 +8,7 @@ use std::fmt::{Debug, Display, Formatter};
 
 use crate::{cmp, field_to_biguint,
             rand_range, rand_range_from_rng,
-            MontyRepr, Field};
+            MontyRepr, Field, PrimeField};
 
 /// An element of the Tweedledee group's base field.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Default)]
@@ -113,6 +113,10 @@ impl Neg for TweedledeeBase {
     }
 }
 
+impl PrimeField for TweedledeeBase {
+    const MODULUS: &'static [u64] = &<Self as MontyRepr>::ORDER;
+}
+
 impl Field for TweedledeeBase {
     const BITS: usize = 255;
     const BYTES: usize = 32;
@@ -264,5 +268,40 @@ mod tests {
         assert_eq!(TweedledeeBase::is_valid_canonical_u64(&limbs), false);
     }
 
+    #[test]
+    fn from_other_field() {
+        use crate::{biguint_to_field, field_to_biguint, Bls12377Base, TweedledumBase};
+
+        let x = TweedledumBase::from_canonical_u64(12345);
+        assert_eq!(
+            TweedledeeBase::from_other_field(x),
+            Some(TweedledeeBase::from_canonical_u64(12345))
+        );
+        assert_eq!(
+            TweedledeeBase::from_other_field(Bls12377Base::from_canonical_u64(12345)),
+            Some(TweedledeeBase::from_canonical_u64(12345))
+        );
+
+        // The Tweedledum base field is the larger one, so its largest elements don't fit.
+        let dee_max = field_to_biguint(TweedledeeBase::NEG_ONE);
+        assert!(field_to_biguint(TweedledumBase::NEG_ONE) > dee_max);
+        assert_eq!(TweedledeeBase::from_other_field(TweedledumBase::NEG_ONE), None);
+        assert_eq!(
+            TweedledeeBase::from_other_field(biguint_to_field::<TweedledumBase>(&dee_max + 1u32)),
+            None
+        );
+        assert_eq!(
+            TweedledumBase::from_other_field(TweedledeeBase::NEG_ONE),
+            Some(biguint_to_field(dee_max))
+        );
+
+        // Round trips preserve in-range values.
+        let y = TweedledeeBase::rand();
+        let y_dum = TweedledumBase::from_other_field(y).unwrap();
+        assert_eq!(TweedledeeBase::from_other_field(y_dum), Some(y));
+        let y_bls = Bls12377Base::from_other_field(y).unwrap();
+        assert_eq!(TweedledeeBase::from_other_field(y_bls), Some(y));
+    }
+
     test_arithmetic!(crate::TweedledeeBase);
 }
@@ -8,7 +8,7 @@ use std::fmt::{Debug, Display, Formatter};
 
 use crate::{cmp, field_to_biguint,
             rand_range, rand_range_from_rng,
-            MontyRepr, Field};
+            MontyRepr, Field, PrimeField};
 
 /// An element of the Tweedledum group's base field.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Default)]
@@ -113,6 +113,10 @@ impl Neg for TweedledumBase {
     }
 }
 
+impl PrimeField for TweedledumBase {
+    const MODULUS: &'static [u64] = &<Self as MontyRepr>::ORDER;
+}
+
 impl Field for TweedledumBase {
     const BITS: usize = 255;
     const BYTES: usize = 32;
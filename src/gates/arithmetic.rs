@@ -30,7 +30,9 @@ impl<C: HaloCurve> ArithmeticGate<C> {
 impl<C: HaloCurve> Gate<C> for ArithmeticGate<C> {
     const NAME: &'static str = "ArithmeticGate";
 
-    const PREFIX: &'static [bool] = &[true, false, false, true];
+    const PREFIX: &'static [bool] = &[true, false, false, true, false];
+
+    const DEGREE: usize = 3;
 
     fn evaluate_unfiltered(
         local_constant_values: &[C::ScalarField],
@@ -67,6 +69,19 @@ impl<C: HaloCurve> Gate<C> for ArithmeticGate<C> {
         let computed_output = builder.add_many(&[product_term, addend_term]);
         vec![builder.sub(computed_output, output)]
     }
+
+    fn constraint_descriptions() -> Vec<String> {
+        let k = Self::PREFIX.len();
+        vec![format!(
+            "w[{}] = c[{}] * w[{}] * w[{}] + c[{}] * w[{}]",
+            Self::WIRE_OUTPUT,
+            k,
+            Self::WIRE_MULTIPLICAND_0,
+            Self::WIRE_MULTIPLICAND_1,
+            k + 1,
+            Self::WIRE_ADDEND
+        )]
+    }
 }
 
 impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for ArithmeticGate<C> {
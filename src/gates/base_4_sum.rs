@@ -33,6 +33,8 @@ impl<C: HaloCurve> Gate<C> for Base4SumGate<C> {
 
     const PREFIX: &'static [bool] = &[true, false, false, false];
 
+    const DEGREE: usize = 4;
+
     fn evaluate_unfiltered(
         _local_constant_values: &[C::ScalarField],
         local_wire_values: &[C::ScalarField],
@@ -54,7 +56,7 @@ impl<C: HaloCurve> Gate<C> for Base4SumGate<C> {
         for limb in limbs {
             let mut product = C::ScalarField::ONE;
             for j in 0..4 {
-                product = product * (limb - C::ScalarField::from_canonical_usize(j));
+                product *= limb - C::ScalarField::from_canonical_usize(j);
             }
             constraints.push(product);
         }
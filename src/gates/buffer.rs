@@ -24,7 +24,9 @@ impl<C: HaloCurve> BufferGate<C> {
 impl<C: HaloCurve> Gate<C> for BufferGate<C> {
     const NAME: &'static str = "BufferGate";
 
-    const PREFIX: &'static [bool] = &[true, false, true, false, false, false];
+    const PREFIX: &'static [bool] = &[true, false, true, false, false, false, false];
+
+    const DEGREE: usize = 0;
 
     fn evaluate_unfiltered(
         _local_constant_values: &[C::ScalarField],
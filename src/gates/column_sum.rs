@@ -0,0 +1,124 @@
+use std::marker::PhantomData;
+
+use crate::gates::Gate;
+use crate::{CircuitBuilder, HaloCurve, PartialWitness, Target, Wire, WitnessGenerator, GRID_WIDTH};
+
+/// Like `RunningSumGate`, but the new sum is "output" using the accumulator wire of the gate
+/// below, i.e. of gate `index + GRID_WIDTH`, which `CircuitBuilder::wire_below` refers to. A chain
+/// of these gates thus runs down a column of the grid, leaving the gates in between free for other
+/// purposes. The gate below the last gate in a chain should be of some other type, such as a
+/// `BufferGate`, which receives the final sum.
+///
+/// ```text
+/// below.acc := local.acc + local.value
+/// ```
+pub struct ColumnSumGate<C: HaloCurve> {
+    pub index: usize,
+    _phantom: PhantomData<C>,
+}
+
+impl<C: HaloCurve> ColumnSumGate<C> {
+    pub fn new(index: usize) -> Self {
+        ColumnSumGate {
+            index,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub const WIRE_ACC: usize = 0;
+    pub const WIRE_VALUE: usize = 1;
+}
+
+impl<C: HaloCurve> Gate<C> for ColumnSumGate<C> {
+    const NAME: &'static str = "ColumnSumGate";
+
+    const PREFIX: &'static [bool] = &[true, false, true, false, false, true, true];
+
+    const DEGREE: usize = 1;
+
+    fn evaluate_unfiltered(
+        _local_constant_values: &[C::ScalarField],
+        local_wire_values: &[C::ScalarField],
+        _right_wire_values: &[C::ScalarField],
+        below_wire_values: &[C::ScalarField],
+    ) -> Vec<C::ScalarField> {
+        let acc_old = local_wire_values[Self::WIRE_ACC];
+        let value = local_wire_values[Self::WIRE_VALUE];
+        let acc_new = below_wire_values[Self::WIRE_ACC];
+        vec![acc_old + value - acc_new]
+    }
+
+    fn evaluate_unfiltered_recursively(
+        builder: &mut CircuitBuilder<C>,
+        _local_constant_values: &[Target<C::ScalarField>],
+        local_wire_values: &[Target<C::ScalarField>],
+        _right_wire_values: &[Target<C::ScalarField>],
+        below_wire_values: &[Target<C::ScalarField>],
+    ) -> Vec<Target<C::ScalarField>> {
+        let acc_old = local_wire_values[Self::WIRE_ACC];
+        let value = local_wire_values[Self::WIRE_VALUE];
+        let acc_new = below_wire_values[Self::WIRE_ACC];
+        let computed_acc_new = builder.add(acc_old, value);
+        vec![builder.sub(computed_acc_new, acc_new)]
+    }
+
+    fn constraint_descriptions() -> Vec<String> {
+        vec![format!(
+            "b[{}] = w[{}] + w[{}]",
+            Self::WIRE_ACC,
+            Self::WIRE_ACC,
+            Self::WIRE_VALUE
+        )]
+    }
+}
+
+impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for ColumnSumGate<C> {
+    fn dependencies(&self) -> Vec<Target<C::ScalarField>> {
+        vec![
+            Target::Wire(Wire {
+                gate: self.index,
+                input: Self::WIRE_ACC,
+            }),
+            Target::Wire(Wire {
+                gate: self.index,
+                input: Self::WIRE_VALUE,
+            }),
+        ]
+    }
+
+    fn generate(
+        &self,
+        _constants: &[Vec<C::ScalarField>],
+        witness: &PartialWitness<C::ScalarField>,
+    ) -> PartialWitness<C::ScalarField> {
+        let acc_old = witness.get_wire(Wire {
+            gate: self.index,
+            input: Self::WIRE_ACC,
+        });
+        let value = witness.get_wire(Wire {
+            gate: self.index,
+            input: Self::WIRE_VALUE,
+        });
+
+        let mut result = PartialWitness::new();
+        result.set_wire(
+            Wire {
+                gate: self.index + GRID_WIDTH,
+                input: Self::WIRE_ACC,
+            },
+            acc_old + value,
+        );
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_gate_low_degree, ColumnSumGate, Tweedledum};
+
+    test_gate_low_degree!(
+        low_degree_ColumnSumGate,
+        Tweedledum,
+        ColumnSumGate<Tweedledum>
+    );
+}
@@ -23,7 +23,9 @@ impl<C: HaloCurve> ConstantGate<C> {
 impl<C: HaloCurve> Gate<C> for ConstantGate<C> {
     const NAME: &'static str = "ConstantGate";
 
-    const PREFIX: &'static [bool] = &[true, false, true, true, false];
+    const PREFIX: &'static [bool] = &[true, false, true, true, false, false];
+
+    const DEGREE: usize = 1;
 
     fn evaluate_unfiltered(
         local_constant_values: &[C::ScalarField],
@@ -47,6 +49,10 @@ impl<C: HaloCurve> Gate<C> for ConstantGate<C> {
         let out = local_wire_values[Self::WIRE_OUTPUT];
         vec![builder.sub(c, out)]
     }
+
+    fn constraint_descriptions() -> Vec<String> {
+        vec![format!("w[{}] = c[{}]", Self::WIRE_OUTPUT, Self::PREFIX.len())]
+    }
 }
 
 impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for ConstantGate<C> {
@@ -38,6 +38,8 @@ impl<C: HaloCurve, InnerC: Curve<BaseField = C::ScalarField>> Gate<C> for CurveA
 
     const PREFIX: &'static [bool] = &[true, false, true, false, true];
 
+    const DEGREE: usize = 3;
+
     fn evaluate_unfiltered(
         _local_constant_values: &[InnerC::BaseField],
         local_wire_values: &[InnerC::BaseField],
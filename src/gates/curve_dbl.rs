@@ -32,6 +32,8 @@ impl<C: HaloCurve, InnerC: Curve<BaseField = C::ScalarField>> Gate<C> for CurveD
 
     const PREFIX: &'static [bool] = &[true, false, true, true, true];
 
+    const DEGREE: usize = 3;
+
     fn evaluate_unfiltered(
         _local_constant_values: &[InnerC::BaseField],
         local_wire_values: &[InnerC::BaseField],
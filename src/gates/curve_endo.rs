@@ -38,6 +38,8 @@ impl<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>> Gate<C>
 
     const PREFIX: &'static [bool] = &[true, true];
 
+    const DEGREE: usize = 6;
+
     fn evaluate_unfiltered(
         _local_constant_values: &[InnerC::BaseField],
         local_wire_values: &[InnerC::BaseField],
@@ -269,7 +271,7 @@ impl<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>> WitnessGenerat
 
         let mut s_i_x = p_x;
         if scalar_bit_0 == InnerC::BaseField::ONE {
-            s_i_x = s_i_x * InnerC::ZETA;
+            s_i_x *= InnerC::ZETA;
         }
         let mut s_i_y = p_y;
         if scalar_bit_1 == InnerC::BaseField::ZERO {
@@ -288,7 +290,7 @@ impl<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>> WitnessGenerat
             InnerC::BaseField::NEG_ONE
         };
         if scalar_bit_1 == InnerC::BaseField::ONE {
-            scalar_acc_signed_limb = scalar_acc_signed_limb * InnerC::ZETA;
+            scalar_acc_signed_limb *= InnerC::ZETA;
         }
         let scalar_acc_signed_new = scalar_acc_signed_old.double() + scalar_acc_signed_limb;
 
@@ -0,0 +1,144 @@
+use crate::gates::*;
+use crate::HaloCurve;
+
+/// The degrees of a set of gate types, which determine how large the quotient polynomial is, and
+/// thus the size of the coset on which the prover must evaluate the constraints.
+#[derive(Clone, Debug, Default)]
+pub struct GateCollection {
+    /// The name, prefix and filtered degree, i.e. `DEGREE + PREFIX.len()`, of each registered gate
+    /// type.
+    gates: Vec<(&'static str, &'static [bool], usize)>,
+}
+
+impl GateCollection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a collection of the gate types which `evaluate_all_constraints` checks.
+    pub fn all<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>() -> Self {
+        let mut collection = Self::new();
+        collection.register::<C, CurveAddGate<C, InnerC>>();
+        collection.register::<C, CurveDblGate<C, InnerC>>();
+        collection.register::<C, CurveEndoGate<C, InnerC>>();
+        collection.register::<C, Base4SumGate<C>>();
+        collection.register::<C, PublicInputGate<C>>();
+        collection.register::<C, BufferGate<C>>();
+        collection.register::<C, RunningSumGate<C>>();
+        collection.register::<C, ColumnSumGate<C>>();
+        collection.register::<C, MdsGate<C>>();
+        collection.register::<C, ConstantGate<C>>();
+        collection.register::<C, ArithmeticGate<C>>();
+        collection.register::<C, SmallTableGate<C, BitReversal3Table>>();
+        collection.register::<C, RescueStepAGate<C>>();
+        collection.register::<C, RescueStepBGate<C>>();
+        collection
+    }
+
+    /// Registers a gate type. Panics if its prefix overlaps with that of a gate type registered
+    /// earlier, i.e. if either is a prefix of the other, since such gates would be
+    /// indistinguishable.
+    pub fn register<C: HaloCurve, G: Gate<C>>(&mut self) {
+        for &(name, prefix, _degree) in &self.gates {
+            assert!(
+                !prefix.starts_with(G::PREFIX) && !G::PREFIX.starts_with(prefix),
+                "The prefix of {} overlaps with that of {}",
+                G::NAME,
+                name
+            );
+        }
+        self.gates.push((G::NAME, G::PREFIX, G::DEGREE + G::PREFIX.len()));
+    }
+
+    /// Returns the maximum filtered degree of any registered gate's constraints, i.e. the degree
+    /// of the combined constraint polynomial, as a multiple of the degree of the wire polynomials.
+    pub fn max_degree(&self) -> usize {
+        self.gates
+            .iter()
+            .map(|&(_name, _prefix, degree)| degree)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the name of a gate type with the maximum filtered degree, if any are registered.
+    pub fn max_degree_gate(&self) -> Option<&'static str> {
+        self.gates
+            .iter()
+            .max_by_key(|&&(_name, _prefix, degree)| degree)
+            .map(|&(name, _prefix, _degree)| name)
+    }
+
+    /// Returns the degree of the quotient polynomial `t`, as a multiple of the circuit degree `n`.
+    /// Dividing the combined constraint polynomial by `Z_H` reduces its degree by `n`.
+    pub fn quotient_degree_multiplier(&self) -> usize {
+        self.max_degree().saturating_sub(1)
+    }
+
+    /// Returns the size of the domain on which a circuit with the given number of gates should
+    /// evaluate its constraints. The circuit degree `n` is the next power of two which is at least
+    /// `num_gates`, and the combined constraint polynomial has degree below `max_degree() * n`, so
+    /// we return the next power of two which is at least that bound.
+    pub fn recommended_domain_size(&self, num_gates: usize) -> usize {
+        let n = num_gates.next_power_of_two();
+        (self.max_degree().max(1) * n).next_power_of_two()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::{ArithmeticGate, GateCollection, RescueStepAGate, RescueStepBGate};
+    use crate::plonk::QUOTIENT_POLYNOMIAL_DEGREE_MULTIPLIER;
+    use crate::{BitReversal3Table, SmallTable, SmallTableGate, Tweedledee, Tweedledum};
+
+    #[test]
+    fn test_rescue_gate_collection() {
+        type C = Tweedledee;
+
+        let mut collection = GateCollection::new();
+        // The Rescue gates have degree-5 constraints and 2-bit prefixes.
+        collection.register::<C, RescueStepAGate<C>>();
+        collection.register::<C, RescueStepBGate<C>>();
+        assert_eq!(collection.max_degree(), 7);
+        assert_eq!(collection.quotient_degree_multiplier(), 6);
+        assert_eq!(collection.recommended_domain_size(1000), 8 * 1024);
+        assert_eq!(collection.recommended_domain_size(1024), 8 * 1024);
+        assert_eq!(collection.recommended_domain_size(1025), 8 * 2048);
+
+        // ArithmeticGate has degree-3 constraints, but its 5-bit prefix makes them degree 8.
+        collection.register::<C, ArithmeticGate<C>>();
+        assert_eq!(collection.max_degree(), 8);
+        assert_eq!(collection.max_degree_gate(), Some("ArithmeticGate"));
+        assert_eq!(collection.quotient_degree_multiplier(), 7);
+        assert_eq!(collection.recommended_domain_size(1000), 8 * 1024);
+    }
+
+    #[test]
+    fn test_all_gates_match_quotient_degree() {
+        let collection = GateCollection::all::<Tweedledee, Tweedledum>();
+        assert_eq!(
+            collection.quotient_degree_multiplier(),
+            QUOTIENT_POLYNOMIAL_DEGREE_MULTIPLIER
+        );
+        assert_eq!(collection.recommended_domain_size(1000), 8 * 1024);
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps with that of SmallTableGate<BitReversal3Table>")]
+    fn test_overlapping_prefixes() {
+        type C = Tweedledee;
+
+        /// Since its prefix is the same as `BitReversal3Table`'s, this can't be used alongside it.
+        struct Identity3Table;
+
+        impl SmallTable for Identity3Table {
+            const NAME: &'static str = "SmallTableGate<Identity3Table>";
+            const PREFIX: &'static [bool] = BitReversal3Table::PREFIX;
+            const BITS: usize = 3;
+            const TABLE: &'static [u64] = &[0, 1, 2, 3, 4, 5, 6, 7];
+        }
+
+        let mut collection = GateCollection::new();
+        collection.register::<C, SmallTableGate<C, BitReversal3Table>>();
+        collection.register::<C, SmallTableGate<C, Identity3Table>>();
+    }
+}
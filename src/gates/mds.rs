@@ -0,0 +1,213 @@
+use std::marker::PhantomData;
+
+use crate::gates::Gate;
+use crate::{mds_matrix, CircuitBuilder, Field, HaloCurve, PartialWitness, Target, Wire, WitnessGenerator, RESCUE_SPONGE_WIDTH};
+
+/// A gate which multiplies a `RESCUE_SPONGE_WIDTH`-dimensional vector by the MDS matrix used in
+/// Rescue. It spans two rows, taking its inputs from its first row and "outputting" the product
+/// using the wires of its second row, which the builder reserves for it.
+///
+/// The matrix is fixed for a given field and width, so its entries are baked into the constraints
+/// rather than passed in as gate constants (of which there are far fewer than `width^2`).
+pub struct MdsGate<C: HaloCurve> {
+    pub index: usize,
+    _phantom: PhantomData<C>,
+}
+
+impl<C: HaloCurve> MdsGate<C> {
+    pub fn new(index: usize) -> Self {
+        MdsGate {
+            index,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the index of the `i`th input wire.
+    pub fn wire_input(i: usize) -> usize {
+        i
+    }
+
+    /// Returns the index of the `i`th output wire, within the gate's second row.
+    pub fn wire_output(i: usize) -> usize {
+        i
+    }
+
+    /// Computes `M x`, where `M` is the MDS matrix.
+    pub fn mix(inputs: &[C::ScalarField]) -> Vec<C::ScalarField> {
+        debug_assert_eq!(inputs.len(), RESCUE_SPONGE_WIDTH);
+        let mds = mds_matrix::<C::ScalarField>(RESCUE_SPONGE_WIDTH);
+        (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| {
+                let mut sum = C::ScalarField::ZERO;
+                for j in 0..RESCUE_SPONGE_WIDTH {
+                    sum += mds.get(i, j) * inputs[j];
+                }
+                sum
+            })
+            .collect()
+    }
+
+    /// Describes each entry of `M x`, given a description of each entry of `x`, as in
+    /// `Gate::constraint_descriptions`.
+    pub(crate) fn describe_mix(inputs: &[String]) -> Vec<String> {
+        (0..inputs.len())
+            .map(|i| {
+                inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(j, input)| format!("M[{}][{}] * {}", i, j, input))
+                    .collect::<Vec<_>>()
+                    .join(" + ")
+            })
+            .collect()
+    }
+
+    /// Like `mix`, but in the context of a recursive circuit.
+    pub fn mix_recursively(
+        builder: &mut CircuitBuilder<C>,
+        inputs: &[Target<C::ScalarField>],
+    ) -> Vec<Target<C::ScalarField>> {
+        debug_assert_eq!(inputs.len(), RESCUE_SPONGE_WIDTH);
+        let mds = mds_matrix::<C::ScalarField>(RESCUE_SPONGE_WIDTH);
+        (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| {
+                let mut sum = builder.zero_wire();
+                for j in 0..RESCUE_SPONGE_WIDTH {
+                    let mds_entry = builder.constant_wire(mds.get(i, j));
+                    sum = builder.mul_add(mds_entry, inputs[j], sum);
+                }
+                sum
+            })
+            .collect()
+    }
+}
+
+impl<C: HaloCurve> Gate<C> for MdsGate<C> {
+    const NAME: &'static str = "MdsGate";
+
+    const PREFIX: &'static [bool] = &[true, false, true, true, false, true];
+
+    const DEGREE: usize = 1;
+
+    /// The outputs are written to the second row.
+    fn num_rows(&self) -> usize {
+        2
+    }
+
+    fn evaluate_unfiltered(
+        _local_constant_values: &[C::ScalarField],
+        local_wire_values: &[C::ScalarField],
+        right_wire_values: &[C::ScalarField],
+        _below_wire_values: &[C::ScalarField],
+    ) -> Vec<C::ScalarField> {
+        let ins: Vec<C::ScalarField> = (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| local_wire_values[Self::wire_input(i)])
+            .collect();
+        let outs: Vec<C::ScalarField> = (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| right_wire_values[Self::wire_output(i)])
+            .collect();
+
+        Self::mix(&ins)
+            .into_iter()
+            .zip(outs)
+            .map(|(computed_out, out)| computed_out - out)
+            .collect()
+    }
+
+    fn evaluate_unfiltered_recursively(
+        builder: &mut CircuitBuilder<C>,
+        _local_constant_values: &[Target<C::ScalarField>],
+        local_wire_values: &[Target<C::ScalarField>],
+        right_wire_values: &[Target<C::ScalarField>],
+        _below_wire_values: &[Target<C::ScalarField>],
+    ) -> Vec<Target<C::ScalarField>> {
+        let ins: Vec<Target<C::ScalarField>> = (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| local_wire_values[Self::wire_input(i)])
+            .collect();
+        let outs: Vec<Target<C::ScalarField>> = (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| right_wire_values[Self::wire_output(i)])
+            .collect();
+
+        Self::mix_recursively(builder, &ins)
+            .into_iter()
+            .zip(outs)
+            .map(|(computed_out, out)| builder.sub(computed_out, out))
+            .collect()
+    }
+
+    fn constraint_descriptions() -> Vec<String> {
+        let ins: Vec<String> = (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| format!("w[{}]", Self::wire_input(i)))
+            .collect();
+        Self::describe_mix(&ins)
+            .into_iter()
+            .enumerate()
+            .map(|(i, mixed)| format!("r[{}] = {}", Self::wire_output(i), mixed))
+            .collect()
+    }
+}
+
+impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for MdsGate<C> {
+    fn dependencies(&self) -> Vec<Target<C::ScalarField>> {
+        (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| {
+                Target::Wire(Wire {
+                    gate: self.index,
+                    input: Self::wire_input(i),
+                })
+            })
+            .collect()
+    }
+
+    fn generate(
+        &self,
+        _constants: &[Vec<C::ScalarField>],
+        witness: &PartialWitness<C::ScalarField>,
+    ) -> PartialWitness<C::ScalarField> {
+        let ins: Vec<C::ScalarField> = (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| {
+                witness.get_wire(Wire {
+                    gate: self.index,
+                    input: Self::wire_input(i),
+                })
+            })
+            .collect();
+
+        let mut result = PartialWitness::new();
+        for (i, out_i) in Self::mix(&ins).into_iter().enumerate() {
+            let wire_out_i = Wire {
+                gate: self.index + 1,
+                input: Self::wire_output(i),
+            };
+            result.set_wire(wire_out_i, out_i);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{apply_mds, test_gate_low_degree, CircuitBuilder, Curve, Field, MdsGate, PartialWitness, Tweedledum};
+
+    test_gate_low_degree!(low_degree_MdsGate, Tweedledum, MdsGate<Tweedledum>);
+
+    #[test]
+    fn mds_layer_matches_apply_mds() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let inputs = (0..4).map(|_| SF::rand()).collect::<Vec<_>>();
+        let expected_outputs = apply_mds(inputs.clone());
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let input_targets = builder.add_virtual_targets(4);
+        let output_targets = builder.mds_layer(&input_targets);
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&input_targets, &inputs);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(witness.get_targets(&output_targets), expected_outputs);
+        assert_eq!(MdsGate::<C>::mix(&inputs), expected_outputs);
+    }
+}
@@ -1,16 +1,20 @@
 //! For reference, here is our gate prefix tree:
 //!
 //! ```text
-//! 101001 PublicInputGate
-//! 101000 CurveAddGate
-//! 10111* CurveDblGate
-//! 11**** CurveEndoGate
-//! 1000** Base4SumGate
-//! 101010 BufferGate
-//! 10110* ConstantGate
-//! 1001** ArithmeticGate
-//! 00**** RescueStepAGate
-//! 01**** RescueStepBGate
+//! 1010010 PublicInputGate
+//! 1010011 ColumnSumGate
+//! 10101** CurveAddGate
+//! 10111** CurveDblGate
+//! 11***** CurveEndoGate
+//! 1000*** Base4SumGate
+//! 1010000 BufferGate
+//! 1010001 RunningSumGate
+//! 101101* MdsGate
+//! 101100* ConstantGate
+//! 10010** ArithmeticGate
+//! 10011** SmallTableGate<BitReversal3Table>
+//! 00***** RescueStepAGate
+//! 01***** RescueStepBGate
 //! ```
 //!
 //! The `*`s above represent constants which are not used in the gate prefix, and are thus available
@@ -19,26 +23,36 @@
 pub use arithmetic::*;
 pub use base_4_sum::*;
 pub use buffer::*;
+pub use column_sum::*;
 pub use constant::*;
 pub use curve_add::*;
 pub use curve_dbl::*;
 pub use curve_endo::*;
+pub use gate_collection::*;
+pub use mds::*;
 pub use public_input::*;
 pub use rescue_a::*;
 pub use rescue_b::*;
+pub use running_sum::*;
+pub use small_table::*;
 
-use crate::{CircuitBuilder, Field, HaloCurve, Target, WitnessGenerator};
+use crate::{CircuitBuilder, Curve, Field, HaloCurve, Target, WitnessGenerator, NUM_CONSTANTS, NUM_ROUTED_WIRES, NUM_WIRES};
 
 mod arithmetic;
 mod base_4_sum;
 mod buffer;
+mod column_sum;
 mod constant;
 mod curve_add;
 mod curve_dbl;
 mod curve_endo;
+mod gate_collection;
+mod mds;
 mod public_input;
 mod rescue_a;
 mod rescue_b;
+mod running_sum;
+mod small_table;
 
 pub const RESCUE_SPONGE_WIDTH: usize = 4;
 pub const RESCUE_SPONGE_RATE: usize = RESCUE_SPONGE_WIDTH - 1;
@@ -86,6 +100,24 @@ pub fn evaluate_all_constraints<C: HaloCurve, InnerC: HaloCurve<BaseField = C::S
             right_wire_values,
             below_wire_values,
         ),
+        RunningSumGate::<C>::evaluate_filtered(
+            local_constant_values,
+            local_wire_values,
+            right_wire_values,
+            below_wire_values,
+        ),
+        ColumnSumGate::<C>::evaluate_filtered(
+            local_constant_values,
+            local_wire_values,
+            right_wire_values,
+            below_wire_values,
+        ),
+        MdsGate::<C>::evaluate_filtered(
+            local_constant_values,
+            local_wire_values,
+            right_wire_values,
+            below_wire_values,
+        ),
         ConstantGate::<C>::evaluate_filtered(
             local_constant_values,
             local_wire_values,
@@ -98,6 +130,12 @@ pub fn evaluate_all_constraints<C: HaloCurve, InnerC: HaloCurve<BaseField = C::S
             right_wire_values,
             below_wire_values,
         ),
+        SmallTableGate::<C, BitReversal3Table>::evaluate_filtered(
+            local_constant_values,
+            local_wire_values,
+            right_wire_values,
+            below_wire_values,
+        ),
         RescueStepAGate::<C>::evaluate_filtered(
             local_constant_values,
             local_wire_values,
@@ -118,7 +156,7 @@ pub fn evaluate_all_constraints<C: HaloCurve, InnerC: HaloCurve<BaseField = C::S
             unified_constraint_set.push(C::ScalarField::ZERO);
         }
         for i in 0..constraint_sets.len() {
-            unified_constraint_set[i] = unified_constraint_set[i] + constraint_sets[i];
+            unified_constraint_set[i] += constraint_sets[i];
         }
     }
     unified_constraint_set
@@ -177,6 +215,27 @@ pub fn evaluate_all_constraints_recursively<
             right_wire_values,
             below_wire_values,
         ),
+        RunningSumGate::<C>::evaluate_filtered_recursively(
+            builder,
+            local_constant_values,
+            local_wire_values,
+            right_wire_values,
+            below_wire_values,
+        ),
+        ColumnSumGate::<C>::evaluate_filtered_recursively(
+            builder,
+            local_constant_values,
+            local_wire_values,
+            right_wire_values,
+            below_wire_values,
+        ),
+        MdsGate::<C>::evaluate_filtered_recursively(
+            builder,
+            local_constant_values,
+            local_wire_values,
+            right_wire_values,
+            below_wire_values,
+        ),
         ConstantGate::<C>::evaluate_filtered_recursively(
             builder,
             local_constant_values,
@@ -191,6 +250,13 @@ pub fn evaluate_all_constraints_recursively<
             right_wire_values,
             below_wire_values,
         ),
+        SmallTableGate::<C, BitReversal3Table>::evaluate_filtered_recursively(
+            builder,
+            local_constant_values,
+            local_wire_values,
+            right_wire_values,
+            below_wire_values,
+        ),
         RescueStepAGate::<C>::evaluate_filtered_recursively(
             builder,
             local_constant_values,
@@ -219,6 +285,105 @@ pub fn evaluate_all_constraints_recursively<
     unified_constraint_set
 }
 
+/// Finds the gate type matching the given constants, and evaluates its unfiltered constraints.
+/// Returns the gate's name along with the constraint values, or `None` if no gate type matches.
+///
+/// Like `evaluate_all_constraints`, this must list every gate type.
+pub(crate) fn evaluate_matching_gate<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>(
+    local_constant_values: &[C::ScalarField],
+    local_wire_values: &[C::ScalarField],
+    right_wire_values: &[C::ScalarField],
+    below_wire_values: &[C::ScalarField],
+) -> Option<(&'static str, Vec<C::ScalarField>)> {
+    let evaluators: &[MatchingGateEvaluator<C>] = &[
+        evaluate_if_matching::<C, CurveAddGate<C, InnerC>>,
+        evaluate_if_matching::<C, CurveDblGate<C, InnerC>>,
+        evaluate_if_matching::<C, CurveEndoGate<C, InnerC>>,
+        evaluate_if_matching::<C, Base4SumGate<C>>,
+        evaluate_if_matching::<C, PublicInputGate<C>>,
+        evaluate_if_matching::<C, BufferGate<C>>,
+        evaluate_if_matching::<C, RunningSumGate<C>>,
+        evaluate_if_matching::<C, ColumnSumGate<C>>,
+        evaluate_if_matching::<C, MdsGate<C>>,
+        evaluate_if_matching::<C, ConstantGate<C>>,
+        evaluate_if_matching::<C, ArithmeticGate<C>>,
+        evaluate_if_matching::<C, SmallTableGate<C, BitReversal3Table>>,
+        evaluate_if_matching::<C, RescueStepAGate<C>>,
+        evaluate_if_matching::<C, RescueStepBGate<C>>,
+    ];
+    evaluators.iter().find_map(|evaluate| {
+        evaluate(
+            local_constant_values,
+            local_wire_values,
+            right_wire_values,
+            below_wire_values,
+        )
+    })
+}
+
+/// Finds the gate type matching the given constants, like `evaluate_matching_gate`, and returns
+/// its name, its prefix length and its constraint descriptions, or `None` if no gate type matches.
+pub(crate) fn describe_matching_gate<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>(
+    local_constant_values: &[C::ScalarField],
+) -> Option<(&'static str, usize, Vec<String>)> {
+    let describers: &[MatchingGateDescriber<C>] = &[
+        describe_if_matching::<C, CurveAddGate<C, InnerC>>,
+        describe_if_matching::<C, CurveDblGate<C, InnerC>>,
+        describe_if_matching::<C, CurveEndoGate<C, InnerC>>,
+        describe_if_matching::<C, Base4SumGate<C>>,
+        describe_if_matching::<C, PublicInputGate<C>>,
+        describe_if_matching::<C, BufferGate<C>>,
+        describe_if_matching::<C, RunningSumGate<C>>,
+        describe_if_matching::<C, ColumnSumGate<C>>,
+        describe_if_matching::<C, MdsGate<C>>,
+        describe_if_matching::<C, ConstantGate<C>>,
+        describe_if_matching::<C, ArithmeticGate<C>>,
+        describe_if_matching::<C, SmallTableGate<C, BitReversal3Table>>,
+        describe_if_matching::<C, RescueStepAGate<C>>,
+        describe_if_matching::<C, RescueStepBGate<C>>,
+    ];
+    describers
+        .iter()
+        .find_map(|describe| describe(local_constant_values))
+}
+
+type MatchingGateDescriber<C> =
+    fn(&[<C as Curve>::ScalarField]) -> Option<(&'static str, usize, Vec<String>)>;
+
+fn describe_if_matching<C: HaloCurve, G: Gate<C>>(
+    local_constant_values: &[C::ScalarField],
+) -> Option<(&'static str, usize, Vec<String>)> {
+    if G::evaluate_prefix_filter(local_constant_values) != C::ScalarField::ONE {
+        return None;
+    }
+    Some((G::NAME, G::PREFIX.len(), G::constraint_descriptions()))
+}
+
+type MatchingGateEvaluator<C> = fn(
+    &[<C as Curve>::ScalarField],
+    &[<C as Curve>::ScalarField],
+    &[<C as Curve>::ScalarField],
+    &[<C as Curve>::ScalarField],
+) -> Option<(&'static str, Vec<<C as Curve>::ScalarField>)>;
+
+fn evaluate_if_matching<C: HaloCurve, G: Gate<C>>(
+    local_constant_values: &[C::ScalarField],
+    local_wire_values: &[C::ScalarField],
+    right_wire_values: &[C::ScalarField],
+    below_wire_values: &[C::ScalarField],
+) -> Option<(&'static str, Vec<C::ScalarField>)> {
+    if G::evaluate_prefix_filter(local_constant_values) != C::ScalarField::ONE {
+        return None;
+    }
+    let constraints = G::evaluate_unfiltered(
+        local_constant_values,
+        local_wire_values,
+        right_wire_values,
+        below_wire_values,
+    );
+    Some((G::NAME, constraints))
+}
+
 /// Computes `x * (x - 1)`, which should vanish iff `x` is binary.
 fn assert_binary_recursively<C: HaloCurve>(
     builder: &mut CircuitBuilder<C>,
@@ -247,6 +412,10 @@ pub trait Gate<C: HaloCurve>: WitnessGenerator<C::ScalarField> {
     /// assign each gate type a binary prefix such that no two prefixes overlap.
     const PREFIX: &'static [bool];
 
+    /// The maximum degree of this gate's unfiltered constraints, treating both wire values and
+    /// constant values as variables. Filtering adds `PREFIX.len()` to this.
+    const DEGREE: usize;
+
     fn evaluate_filtered(
         local_constant_values: &[C::ScalarField],
         local_wire_values: &[C::ScalarField],
@@ -289,9 +458,9 @@ pub trait Gate<C: HaloCurve>: WitnessGenerator<C::ScalarField> {
         for (i, &bit) in Self::PREFIX.iter().enumerate() {
             let c = local_constant_values[i];
             if bit {
-                product = product * c;
+                product *= c;
             } else {
-                product = product * (C::ScalarField::ONE - c);
+                product *= C::ScalarField::ONE - c;
             }
         }
         product
@@ -311,6 +480,23 @@ pub trait Gate<C: HaloCurve>: WitnessGenerator<C::ScalarField> {
         product
     }
 
+    /// The indices of this gate's wires which may participate in copy constraints. The remaining
+    /// wires are advice wires, which hold values used only within the gate's own constraints, and
+    /// must not be copied to other wires. The default is every wire covered by the permutation
+    /// argument.
+    fn routed_wires(&self) -> Vec<usize> {
+        (0..NUM_ROUTED_WIRES).collect()
+    }
+
+    /// The number of consecutive rows occupied by this gate. A gate's constraints are evaluated at
+    /// its first row, where its second row is visible as `right_wire_values`, so at most two rows
+    /// are supported. When such a gate is added, `CircuitBuilder::add_gate` fills its second row
+    /// with a `BufferGate`, so the gate may freely use those wires for outputs. The default is a
+    /// single row.
+    fn num_rows(&self) -> usize {
+        1
+    }
+
     /// Evaluate the constraints implied by this gate at the given challenge point.
     ///
     /// For example, if the gate computes `c = a * b`, this should return `[c(x) - a(x) * b(x)]`,
@@ -330,6 +516,22 @@ pub trait Gate<C: HaloCurve>: WitnessGenerator<C::ScalarField> {
         right_wire_values: &[Target<C::ScalarField>],
         below_wire_values: &[Target<C::ScalarField>],
     ) -> Vec<Target<C::ScalarField>>;
+
+    /// Returns a human-readable description of each unfiltered constraint, in the order that
+    /// `evaluate_unfiltered` returns them, for auditing; see `Circuit::to_debug_string`.
+    ///
+    /// Wires of the gate's row are written as `w[i]`, wires of the row to its right as `r[i]` and
+    /// wires of the row below as `b[i]`. Constants are written as `c[i]`, where indices count the
+    /// prefix bits, and `M[i][j]` is an entry of the MDS matrix. The default only numbers the
+    /// constraints, so gates should describe them where practical.
+    fn constraint_descriptions() -> Vec<String> {
+        let constants = vec![C::ScalarField::ZERO; NUM_CONSTANTS];
+        let wires = vec![C::ScalarField::ZERO; NUM_WIRES];
+        let num_constraints = Self::evaluate_unfiltered(&constants, &wires, &wires, &wires).len();
+        (0..num_constraints)
+            .map(|i| format!("{} constraint {}", Self::NAME, i))
+            .collect()
+    }
 }
 
 /// Test that a gate's constraints are within degree 8n, including the gate prefix filter.
@@ -427,6 +629,18 @@ macro_rules! test_gate_low_degree {
                 .iter()
                 .map(|c| $crate::plonk_util::polynomial_degree_plus_1(c, &fft_precomputation_16n))
                 .collect::<Vec<_>>();
+            let filtered_degree = <$gate as $crate::gates::Gate<C>>::PREFIX.len()
+                + <$gate as $crate::gates::Gate<C>>::DEGREE;
+            for (i, &deg_plus_1) in constraint_degrees_plus_1.iter().enumerate() {
+                assert!(
+                    deg_plus_1 <= filtered_degree * (n - 1) + 1,
+                    "Constraint at index {} has degree+1 {}, which exceeds the declared degree {}",
+                    i,
+                    deg_plus_1,
+                    <$gate as $crate::gates::Gate<C>>::DEGREE
+                );
+            }
+
             let max_degree_excl = (crate::plonk::QUOTIENT_POLYNOMIAL_DEGREE_MULTIPLIER + 1) * n;
             for (i, &deg_plus_1) in constraint_degrees_plus_1.iter().enumerate() {
                 assert!(
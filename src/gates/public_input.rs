@@ -27,7 +27,9 @@ impl<C: HaloCurve> PublicInputGate<C> {
 impl<C: HaloCurve> Gate<C> for PublicInputGate<C> {
     const NAME: &'static str = "PublicInputGate";
 
-    const PREFIX: &'static [bool] = &[true, false, true, false, false, true];
+    const PREFIX: &'static [bool] = &[true, false, true, false, false, true, false];
+
+    const DEGREE: usize = 1;
 
     fn evaluate_unfiltered(
         _local_constant_values: &[C::ScalarField],
@@ -60,6 +62,12 @@ impl<C: HaloCurve> Gate<C> for PublicInputGate<C> {
             })
             .collect()
     }
+
+    fn constraint_descriptions() -> Vec<String> {
+        (0..NUM_ADVICE_WIRES)
+            .map(|i| format!("w[{}] = r[{}]", NUM_ROUTED_WIRES + i, i))
+            .collect()
+    }
 }
 
 impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for PublicInputGate<C> {
@@ -1,7 +1,7 @@
 use std::marker::PhantomData;
 
-use crate::gates::Gate;
-use crate::{mds_matrix, CircuitBuilder, Field, HaloCurve, PartialWitness, Target, Wire, WitnessGenerator, RESCUE_SPONGE_WIDTH};
+use crate::gates::{Gate, MdsGate};
+use crate::{CircuitBuilder, Field, HaloCurve, PartialWitness, Target, Wire, WitnessGenerator, RESCUE_SPONGE_WIDTH};
 
 /// The first step of Rescue, i.e. the one with the `x^(1/5)` layer.
 pub struct RescueStepAGate<C: HaloCurve> {
@@ -33,6 +33,14 @@ impl<C: HaloCurve> Gate<C> for RescueStepAGate<C> {
 
     const PREFIX: &'static [bool] = &[false, false];
 
+    const DEGREE: usize = 5;
+
+    /// The root wires hold intermediate values of the permutation, so only the accumulator wires
+    /// are routed.
+    fn routed_wires(&self) -> Vec<usize> {
+        (0..RESCUE_SPONGE_WIDTH).map(Self::wire_acc).collect()
+    }
+
     fn evaluate_unfiltered(
         local_constant_values: &[C::ScalarField],
         local_wire_values: &[C::ScalarField],
@@ -49,16 +57,13 @@ impl<C: HaloCurve> Gate<C> for RescueStepAGate<C> {
             .map(|i| local_wire_values[Self::wire_root(i)])
             .collect();
 
-        let mds = mds_matrix::<C::ScalarField>(RESCUE_SPONGE_WIDTH);
+        let mixed_roots = MdsGate::<C>::mix(&roots);
 
         let mut constraints = Vec::new();
         for i in 0..RESCUE_SPONGE_WIDTH {
             constraints.push(roots[i].exp_usize(5) - ins[i]);
 
-            let mut computed_out_i = local_constant_values[Self::PREFIX.len() + i];
-            for j in 0..RESCUE_SPONGE_WIDTH {
-                computed_out_i = computed_out_i + mds.get(i, j) * roots[j];
-            }
+            let computed_out_i = local_constant_values[Self::PREFIX.len() + i] + mixed_roots[i];
             constraints.push(computed_out_i - outs[i]);
         }
         constraints
@@ -83,22 +88,38 @@ impl<C: HaloCurve> Gate<C> for RescueStepAGate<C> {
             .map(|i| local_wire_values[Self::wire_root(i)])
             .collect();
 
-        let mds = mds_matrix::<C::ScalarField>(RESCUE_SPONGE_WIDTH);
+        let mixed_roots = MdsGate::<C>::mix_recursively(builder, &roots);
 
         let mut constraints = Vec::new();
         for i in 0..RESCUE_SPONGE_WIDTH {
             let computed_in_i = builder.exp_constant_usize(roots[i], 5);
             constraints.push(builder.sub(computed_in_i, ins[i]));
 
-            let mut computed_out_i = local_constant_values[Self::PREFIX.len() + i];
-            for j in 0..RESCUE_SPONGE_WIDTH {
-                let mds_entry = builder.constant_wire(mds.get(i, j));
-                computed_out_i = builder.mul_add(mds_entry, roots[j], computed_out_i);
-            }
+            let computed_out_i =
+                builder.add(local_constant_values[Self::PREFIX.len() + i], mixed_roots[i]);
             constraints.push(builder.sub(computed_out_i, outs[i]));
         }
         constraints
     }
+
+    fn constraint_descriptions() -> Vec<String> {
+        let roots: Vec<String> = (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| format!("w[{}]", Self::wire_root(i)))
+            .collect();
+        let mixed_roots = MdsGate::<C>::describe_mix(&roots);
+
+        let mut descriptions = Vec::new();
+        for i in 0..RESCUE_SPONGE_WIDTH {
+            descriptions.push(format!("{}^5 = w[{}]", roots[i], Self::wire_acc(i)));
+            descriptions.push(format!(
+                "r[{}] = c[{}] + {}",
+                Self::wire_acc(i),
+                Self::PREFIX.len() + i,
+                mixed_roots[i]
+            ));
+        }
+        descriptions
+    }
 }
 
 impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for RescueStepAGate<C> {
@@ -131,7 +152,7 @@ impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for RescueStepAGate<C> {
 
         let roots: Vec<C::ScalarField> = ins.iter().map(|n| n.kth_root_u32(5)).collect();
 
-        let mds = mds_matrix::<C::ScalarField>(RESCUE_SPONGE_WIDTH);
+        let mixed_roots = MdsGate::<C>::mix(&roots);
 
         let mut result = PartialWitness::new();
         for i in 0..RESCUE_SPONGE_WIDTH {
@@ -141,10 +162,7 @@ impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for RescueStepAGate<C> {
             };
             result.set_wire(wire_root_i, roots[i]);
 
-            let mut out_i = constants[Self::PREFIX.len() + i];
-            for j in 0..RESCUE_SPONGE_WIDTH {
-                out_i = out_i + mds.get(i, j) * roots[j];
-            }
+            let out_i = constants[Self::PREFIX.len() + i] + mixed_roots[i];
             let wire_out_i = Wire {
                 gate: self.index + 1,
                 input: Self::wire_acc(i),
@@ -157,11 +175,44 @@ impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for RescueStepAGate<C> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{test_gate_low_degree, RescueStepAGate, Tweedledum};
+    use crate::gates::Gate;
+    use crate::{test_gate_low_degree, Curve, Field, RescueStepAGate, Tweedledum, NUM_CONSTANTS, NUM_WIRES, RESCUE_SPONGE_WIDTH};
 
     test_gate_low_degree!(
         low_degree_RescueStepAGate,
         Tweedledum,
         RescueStepAGate<Tweedledum>
     );
+
+    #[test]
+    fn root_wires_not_routed() {
+        let routed_wires = RescueStepAGate::<Tweedledum>::new(0).routed_wires();
+        for i in 0..RESCUE_SPONGE_WIDTH {
+            assert!(routed_wires.contains(&RescueStepAGate::<Tweedledum>::wire_acc(i)));
+            assert!(!routed_wires.contains(&RescueStepAGate::<Tweedledum>::wire_root(i)));
+        }
+    }
+
+    #[test]
+    fn constraint_descriptions() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let descriptions = RescueStepAGate::<C>::constraint_descriptions();
+        let constants = vec![SF::ZERO; NUM_CONSTANTS];
+        let wires = vec![SF::ZERO; NUM_WIRES];
+        assert_eq!(
+            descriptions.len(),
+            RescueStepAGate::<C>::evaluate_unfiltered(&constants, &wires, &wires, &wires).len()
+        );
+        // The first constraint relates the first root wire to the first accumulator wire.
+        assert_eq!(
+            descriptions[0],
+            format!(
+                "w[{}]^5 = w[{}]",
+                RescueStepAGate::<C>::wire_root(0),
+                RescueStepAGate::<C>::wire_acc(0)
+            )
+        );
+    }
 }
@@ -1,7 +1,7 @@
 use std::marker::PhantomData;
 
-use crate::gates::Gate;
-use crate::{mds_matrix, CircuitBuilder, Field, HaloCurve, PartialWitness, Target, Wire, WitnessGenerator, RESCUE_SPONGE_WIDTH};
+use crate::gates::{Gate, MdsGate};
+use crate::{CircuitBuilder, Field, HaloCurve, PartialWitness, Target, Wire, WitnessGenerator, RESCUE_SPONGE_WIDTH};
 
 /// The second step of Rescue, i.e. the one with the `x^5` layer.
 pub struct RescueStepBGate<C: HaloCurve> {
@@ -28,6 +28,8 @@ impl<C: HaloCurve> Gate<C> for RescueStepBGate<C> {
 
     const PREFIX: &'static [bool] = &[false, true];
 
+    const DEGREE: usize = 5;
+
     fn evaluate_unfiltered(
         local_constant_values: &[C::ScalarField],
         local_wire_values: &[C::ScalarField],
@@ -44,14 +46,11 @@ impl<C: HaloCurve> Gate<C> for RescueStepBGate<C> {
             .map(|i| right_wire_values[Self::wire_acc(i)])
             .collect();
 
-        let mds = mds_matrix::<C::ScalarField>(RESCUE_SPONGE_WIDTH);
+        let mixed_exps = MdsGate::<C>::mix(&exps);
 
         let mut constraints = Vec::new();
         for i in 0..RESCUE_SPONGE_WIDTH {
-            let mut computed_out_i = local_constant_values[Self::PREFIX.len() + i];
-            for j in 0..RESCUE_SPONGE_WIDTH {
-                computed_out_i = computed_out_i + mds.get(i, j) * exps[j];
-            }
+            let computed_out_i = local_constant_values[Self::PREFIX.len() + i] + mixed_exps[i];
             constraints.push(computed_out_i - outs[i]);
         }
         constraints
@@ -77,19 +76,34 @@ impl<C: HaloCurve> Gate<C> for RescueStepBGate<C> {
             .map(|i| right_wire_values[Self::wire_acc(i)])
             .collect();
 
-        let mds = mds_matrix::<C::ScalarField>(RESCUE_SPONGE_WIDTH);
+        let mixed_exps = MdsGate::<C>::mix_recursively(builder, &exps);
 
         let mut constraints = Vec::new();
         for i in 0..RESCUE_SPONGE_WIDTH {
-            let mut computed_out_i = local_constant_values[Self::PREFIX.len() + i];
-            for j in 0..RESCUE_SPONGE_WIDTH {
-                let mds_entry = builder.constant_wire(mds.get(i, j));
-                computed_out_i = builder.mul_add(mds_entry, exps[i], computed_out_i);
-            }
+            let computed_out_i =
+                builder.add(local_constant_values[Self::PREFIX.len() + i], mixed_exps[i]);
             constraints.push(builder.sub(computed_out_i, outs[i]));
         }
         constraints
     }
+
+    fn constraint_descriptions() -> Vec<String> {
+        let exps: Vec<String> = (0..RESCUE_SPONGE_WIDTH)
+            .map(|i| format!("w[{}]^5", Self::wire_acc(i)))
+            .collect();
+        MdsGate::<C>::describe_mix(&exps)
+            .into_iter()
+            .enumerate()
+            .map(|(i, mixed_exps)| {
+                format!(
+                    "r[{}] = c[{}] + {}",
+                    Self::wire_acc(i),
+                    Self::PREFIX.len() + i,
+                    mixed_exps
+                )
+            })
+            .collect()
+    }
 }
 
 impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for RescueStepBGate<C> {
@@ -122,14 +136,11 @@ impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for RescueStepBGate<C> {
 
         let exps: Vec<C::ScalarField> = ins.iter().map(|n| n.exp_usize(5)).collect();
 
-        let mds = mds_matrix::<C::ScalarField>(RESCUE_SPONGE_WIDTH);
+        let mixed_exps = MdsGate::<C>::mix(&exps);
 
         let mut result = PartialWitness::new();
         for i in 0..RESCUE_SPONGE_WIDTH {
-            let mut out_i = constants[Self::PREFIX.len() + i];
-            for j in 0..RESCUE_SPONGE_WIDTH {
-                out_i = out_i + mds.get(i, j) * exps[j];
-            }
+            let out_i = constants[Self::PREFIX.len() + i] + mixed_exps[i];
             let wire_out_i = Wire {
                 gate: self.index + 1,
                 input: Self::wire_acc(i),
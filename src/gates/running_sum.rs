@@ -0,0 +1,114 @@
+use std::marker::PhantomData;
+
+use crate::gates::Gate;
+use crate::{CircuitBuilder, HaloCurve, PartialWitness, Target, Wire, WitnessGenerator};
+
+/// A gate which adds a value to a running sum. The new sum is "output" using the accumulator
+/// wire of the next gate, so a chain of these gates can sum arbitrarily many values, using one
+/// row per value. The last gate in a chain should be followed by a gate of some other type, such
+/// as a `BufferGate`, which receives the final sum.
+///
+/// ```text
+/// right.acc := local.acc + local.value
+/// ```
+pub struct RunningSumGate<C: HaloCurve> {
+    pub index: usize,
+    _phantom: PhantomData<C>,
+}
+
+impl<C: HaloCurve> RunningSumGate<C> {
+    pub fn new(index: usize) -> Self {
+        RunningSumGate {
+            index,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub const WIRE_ACC: usize = 0;
+    pub const WIRE_VALUE: usize = 1;
+}
+
+impl<C: HaloCurve> Gate<C> for RunningSumGate<C> {
+    const NAME: &'static str = "RunningSumGate";
+
+    const PREFIX: &'static [bool] = &[true, false, true, false, false, false, true];
+
+    const DEGREE: usize = 1;
+
+    fn evaluate_unfiltered(
+        _local_constant_values: &[C::ScalarField],
+        local_wire_values: &[C::ScalarField],
+        right_wire_values: &[C::ScalarField],
+        _below_wire_values: &[C::ScalarField],
+    ) -> Vec<C::ScalarField> {
+        let acc_old = local_wire_values[Self::WIRE_ACC];
+        let value = local_wire_values[Self::WIRE_VALUE];
+        let acc_new = right_wire_values[Self::WIRE_ACC];
+        vec![acc_old + value - acc_new]
+    }
+
+    fn evaluate_unfiltered_recursively(
+        builder: &mut CircuitBuilder<C>,
+        _local_constant_values: &[Target<C::ScalarField>],
+        local_wire_values: &[Target<C::ScalarField>],
+        right_wire_values: &[Target<C::ScalarField>],
+        _below_wire_values: &[Target<C::ScalarField>],
+    ) -> Vec<Target<C::ScalarField>> {
+        let acc_old = local_wire_values[Self::WIRE_ACC];
+        let value = local_wire_values[Self::WIRE_VALUE];
+        let acc_new = right_wire_values[Self::WIRE_ACC];
+        let computed_acc_new = builder.add(acc_old, value);
+        vec![builder.sub(computed_acc_new, acc_new)]
+    }
+}
+
+impl<C: HaloCurve> WitnessGenerator<C::ScalarField> for RunningSumGate<C> {
+    fn dependencies(&self) -> Vec<Target<C::ScalarField>> {
+        vec![
+            Target::Wire(Wire {
+                gate: self.index,
+                input: Self::WIRE_ACC,
+            }),
+            Target::Wire(Wire {
+                gate: self.index,
+                input: Self::WIRE_VALUE,
+            }),
+        ]
+    }
+
+    fn generate(
+        &self,
+        _constants: &[Vec<C::ScalarField>],
+        witness: &PartialWitness<C::ScalarField>,
+    ) -> PartialWitness<C::ScalarField> {
+        let acc_old = witness.get_wire(Wire {
+            gate: self.index,
+            input: Self::WIRE_ACC,
+        });
+        let value = witness.get_wire(Wire {
+            gate: self.index,
+            input: Self::WIRE_VALUE,
+        });
+
+        let mut result = PartialWitness::new();
+        result.set_wire(
+            Wire {
+                gate: self.index + 1,
+                input: Self::WIRE_ACC,
+            },
+            acc_old + value,
+        );
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_gate_low_degree, RunningSumGate, Tweedledum};
+
+    test_gate_low_degree!(
+        low_degree_RunningSumGate,
+        Tweedledum,
+        RunningSumGate<Tweedledum>
+    );
+}
@@ -0,0 +1,229 @@
+use std::marker::PhantomData;
+
+use crate::gates::Gate;
+use crate::{CircuitBuilder, Field, HaloCurve, PartialWitness, Target, Wire, WitnessGenerator};
+
+/// A fixed function from `BITS`-bit inputs to field elements, given by its table of outputs.
+///
+/// Since the table is baked into the constraints of `SmallTableGate<C, Self>`, each table is its
+/// own gate type, and so needs its own `PREFIX` in the gate prefix tree described in `gates`.
+pub trait SmallTable: 'static + Sync {
+    /// The name of the table's gate type.
+    const NAME: &'static str;
+
+    /// The table's gate prefix. Like any gate prefix, it must not be a prefix of any other gate's,
+    /// and its length plus `SmallTableGate::DEGREE` must not exceed 8.
+    const PREFIX: &'static [bool];
+
+    /// The number of input bits.
+    const BITS: usize;
+
+    /// The output for each input, indexed by the input. Must have length `2^BITS`.
+    const TABLE: &'static [u64];
+}
+
+/// The 3-bit bit-reversal permutation.
+pub struct BitReversal3Table;
+
+impl SmallTable for BitReversal3Table {
+    const NAME: &'static str = "SmallTableGate<BitReversal3Table>";
+
+    const PREFIX: &'static [bool] = &[true, false, false, true, true];
+
+    const BITS: usize = 3;
+
+    const TABLE: &'static [u64] = &[0, 4, 2, 6, 1, 5, 3, 7];
+}
+
+/// A gate which enforces that `output = T::TABLE[input]`, for a fixed table `T` on inputs of at
+/// most 3 bits.
+///
+/// The input is decomposed into bits, and the output is checked against the multilinear
+/// interpolation of the table in those bits, which has degree `T::BITS`. As with `MdsGate`, the
+/// table is baked into the constraints, since there are not enough gate constants to hold it: a
+/// 3-bit table has 8 entries, while a gate with a 5-bit prefix has only 2 constants to spare.
+/// Every table used in a circuit is thus its own gate type, with its own prefix, and needs to be
+/// registered in `evaluate_all_constraints`.
+pub struct SmallTableGate<C: HaloCurve, T: SmallTable> {
+    pub index: usize,
+    _phantom_c: PhantomData<C>,
+    _phantom_t: PhantomData<T>,
+}
+
+impl<C: HaloCurve, T: SmallTable> SmallTableGate<C, T> {
+    pub fn new(index: usize) -> Self {
+        assert!(T::BITS <= 3, "Tables with more than 3 input bits are too high degree");
+        assert_eq!(T::TABLE.len(), 1 << T::BITS, "{} has the wrong size", T::NAME);
+        SmallTableGate {
+            index,
+            _phantom_c: PhantomData,
+            _phantom_t: PhantomData,
+        }
+    }
+
+    pub const WIRE_INPUT: usize = 0;
+    pub const WIRE_OUTPUT: usize = 1;
+
+    /// Returns the index of the `i`th (little-endian) input bit wire.
+    pub fn wire_bit(i: usize) -> usize {
+        2 + i
+    }
+
+    /// Evaluates the multilinear interpolation of the table at the given bits.
+    fn interpolate(bits: &[C::ScalarField]) -> C::ScalarField {
+        let mut values: Vec<C::ScalarField> = T::TABLE
+            .iter()
+            .map(|&v| C::ScalarField::from_canonical_u64(v))
+            .collect();
+        for &bit in bits {
+            values = values
+                .chunks(2)
+                .map(|pair| pair[0] + bit * (pair[1] - pair[0]))
+                .collect();
+        }
+        values[0]
+    }
+
+    fn interpolate_recursively(
+        builder: &mut CircuitBuilder<C>,
+        bits: &[Target<C::ScalarField>],
+    ) -> Target<C::ScalarField> {
+        let mut values: Vec<Target<C::ScalarField>> = T::TABLE
+            .iter()
+            .map(|&v| builder.constant_wire(C::ScalarField::from_canonical_u64(v)))
+            .collect();
+        for &bit in bits {
+            values = values
+                .chunks(2)
+                .map(|pair| {
+                    let diff = builder.sub(pair[1], pair[0]);
+                    builder.mul_add(bit, diff, pair[0])
+                })
+                .collect();
+        }
+        values[0]
+    }
+}
+
+impl<C: HaloCurve, T: SmallTable> Gate<C> for SmallTableGate<C, T> {
+    const NAME: &'static str = T::NAME;
+
+    const PREFIX: &'static [bool] = T::PREFIX;
+
+    const DEGREE: usize = if T::BITS > 2 { T::BITS } else { 2 };
+
+    fn evaluate_unfiltered(
+        _local_constant_values: &[C::ScalarField],
+        local_wire_values: &[C::ScalarField],
+        _right_wire_values: &[C::ScalarField],
+        _below_wire_values: &[C::ScalarField],
+    ) -> Vec<C::ScalarField> {
+        let input = local_wire_values[Self::WIRE_INPUT];
+        let output = local_wire_values[Self::WIRE_OUTPUT];
+        let bits: Vec<C::ScalarField> = (0..T::BITS)
+            .map(|i| local_wire_values[Self::wire_bit(i)])
+            .collect();
+
+        let mut computed_input = C::ScalarField::ZERO;
+        for &bit in bits.iter().rev() {
+            computed_input = computed_input.double() + bit;
+        }
+
+        let mut constraints = vec![
+            computed_input - input,
+            Self::interpolate(&bits) - output,
+        ];
+        for &bit in &bits {
+            constraints.push(bit * (bit - C::ScalarField::ONE));
+        }
+        constraints
+    }
+
+    fn evaluate_unfiltered_recursively(
+        builder: &mut CircuitBuilder<C>,
+        _local_constant_values: &[Target<C::ScalarField>],
+        local_wire_values: &[Target<C::ScalarField>],
+        _right_wire_values: &[Target<C::ScalarField>],
+        _below_wire_values: &[Target<C::ScalarField>],
+    ) -> Vec<Target<C::ScalarField>> {
+        let input = local_wire_values[Self::WIRE_INPUT];
+        let output = local_wire_values[Self::WIRE_OUTPUT];
+        let bits: Vec<Target<C::ScalarField>> = (0..T::BITS)
+            .map(|i| local_wire_values[Self::wire_bit(i)])
+            .collect();
+
+        let mut computed_input = builder.zero_wire();
+        for &bit in bits.iter().rev() {
+            let doubled = builder.double(computed_input);
+            computed_input = builder.add(doubled, bit);
+        }
+
+        let interpolated = Self::interpolate_recursively(builder, &bits);
+        let mut constraints = vec![
+            builder.sub(computed_input, input),
+            builder.sub(interpolated, output),
+        ];
+        for &bit in &bits {
+            constraints.push(super::assert_binary_recursively(builder, bit));
+        }
+        constraints
+    }
+}
+
+impl<C: HaloCurve, T: SmallTable> WitnessGenerator<C::ScalarField> for SmallTableGate<C, T> {
+    fn dependencies(&self) -> Vec<Target<C::ScalarField>> {
+        vec![Target::Wire(Wire {
+            gate: self.index,
+            input: Self::WIRE_INPUT,
+        })]
+    }
+
+    fn generate(
+        &self,
+        _constants: &[Vec<C::ScalarField>],
+        witness: &PartialWitness<C::ScalarField>,
+    ) -> PartialWitness<C::ScalarField> {
+        let input = witness.get_wire(Wire {
+            gate: self.index,
+            input: Self::WIRE_INPUT,
+        });
+        let input_canonical = input.to_canonical_u64_vec();
+        assert!(
+            input_canonical[1..].iter().all(|&limb| limb == 0)
+                && input_canonical[0] < T::TABLE.len() as u64,
+            "Input to {} is out of range",
+            T::NAME
+        );
+        let input_usize = input_canonical[0] as usize;
+
+        let mut result = PartialWitness::new();
+        for i in 0..T::BITS {
+            result.set_wire(
+                Wire {
+                    gate: self.index,
+                    input: Self::wire_bit(i),
+                },
+                C::ScalarField::from_canonical_usize(input_usize >> i & 1),
+            );
+        }
+        result.set_wire(
+            Wire {
+                gate: self.index,
+                input: Self::WIRE_OUTPUT,
+            },
+            C::ScalarField::from_canonical_u64(T::TABLE[input_usize]),
+        );
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_gate_low_degree, BitReversal3Table, SmallTableGate, Tweedledum};
+
+    test_gate_low_degree!(
+        low_degree_SmallTableGate,
+        Tweedledum,
+        SmallTableGate<Tweedledum, BitReversal3Table>
+    );
+}
@@ -1,10 +1,17 @@
-use crate::plonk_challenger::Challenger;
-use crate::plonk_util::{halo_n, halo_n_mul, powers, reduce_with_powers};
+use crate::plonk_challenger::{Challenger, SpongeHash};
+use crate::plonk_util::{halo_n, halo_n_mul, reduce_with_powers};
 use crate::util::log2_strict;
 use crate::{msm_execute_parallel, msm_parallel, msm_precompute, AffinePoint, Curve, Field, HaloCurve, PolynomialCommitment, ProjectivePoint, SchnorrProof};
-use anyhow::Result;
+use anyhow::{ensure, Result};
+use rand::Rng;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
+/// An IPA opening proof. Its points are serialized in compressed form, i.e. as an `x` coordinate
+/// and the parity of `y`, so the `2 log n` points in `halo_l` and `halo_r` take about half the
+/// space they would with both coordinates.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct OpeningProof<C: HaloCurve> {
     pub halo_l: Vec<AffinePoint<C>>,
     pub halo_r: Vec<AffinePoint<C>>,
@@ -12,8 +19,26 @@ pub struct OpeningProof<C: HaloCurve> {
     pub schnorr_proof: SchnorrProof<C>,
 }
 
+impl<C: HaloCurve> OpeningProof<C> {
+    pub fn to_compressed_bytes(&self) -> Result<Vec<u8>> {
+        Ok(serde_cbor::to_vec(self)?)
+    }
+
+    /// Decompresses an opening proof, checking that each of its points lies in the subgroup of
+    /// order `|ScalarField|`.
+    pub fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
+        let proof: Self = serde_cbor::from_slice(bytes)?;
+        let other_points = [proof.halo_g, proof.schnorr_proof.r];
+        let points = proof.halo_l.iter().chain(&proof.halo_r).chain(&other_points);
+        for point in points {
+            ensure!(point.is_in_subgroup(), "Opening proof point is not in the subgroup");
+        }
+        Ok(proof)
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
-pub fn batch_opening_proof<C: HaloCurve>(
+pub fn batch_opening_proof<C: HaloCurve, H: SpongeHash<C::BaseField>, R: Rng>(
     polynomials_coeffs: &[&[C::ScalarField]],
     commitments: &[PolynomialCommitment<C>],
     opening_points: &[C::ScalarField],
@@ -25,13 +50,15 @@ pub fn batch_opening_proof<C: HaloCurve>(
     u_scaling: C::ScalarField,
     degree: usize,
     security_bits: usize,
-    challenger: &mut Challenger<C::BaseField>,
+    challenger: &mut Challenger<C::BaseField, H>,
+    rng: &mut R,
 ) -> Result<OpeningProof<C>> {
     // Normally we would reduce these lists using powers of u, but for the sake of efficiency
     // (particularly in the recursive verifier) we instead use n(u^i) for each u^i, where n is
     // the injective function related to the Halo endomorphism. Here we compute n(u^i).
-    let actual_scalars: Vec<C::ScalarField> = powers(u, polynomials_coeffs.len())
-        .iter()
+    let actual_scalars: Vec<C::ScalarField> = u
+        .powers()
+        .take(polynomials_coeffs.len())
         .map(|u_power| halo_n::<C>(&u_power.to_canonical_bool_vec()[..security_bits]))
         .collect();
 
@@ -39,7 +66,7 @@ pub fn batch_opening_proof<C: HaloCurve>(
     let mut reduced_coeffs = vec![C::ScalarField::ZERO; degree];
     for (i, coeffs) in polynomials_coeffs.iter().enumerate() {
         for (j, &c) in coeffs.iter().enumerate() {
-            reduced_coeffs[j] = reduced_coeffs[j] + actual_scalars[i] * c;
+            reduced_coeffs[j] += actual_scalars[i] * c;
         }
     }
 
@@ -80,8 +107,8 @@ pub fn batch_opening_proof<C: HaloCurve>(
         // We may need to re-generate L_i/R_i a few times with different blinding factors until
         // we get a challenge r such that n(r) is square.
         let u_j = loop {
-            let l_j_blinding_factor = C::ScalarField::rand();
-            let r_j_blinding_factor = C::ScalarField::rand();
+            let l_j_blinding_factor = C::ScalarField::rand_from_rng(rng);
+            let r_j_blinding_factor = C::ScalarField::rand_from_rng(rng);
 
             // L_i = <a_lo, G_hi> + [l_j] H + [<a_lo, b_hi>] U.
             let halo_l_j = msm_parallel(a_lo, g_hi, window_size)
@@ -129,7 +156,7 @@ pub fn batch_opening_proof<C: HaloCurve>(
     debug_assert_eq!(halo_a.len(), 1);
     debug_assert_eq!(halo_b.len(), 1);
     let schnorr_proof = schnorr_protocol(
-        halo_a[0], halo_b[0], halo_g, randomness, u_prime, pedersen_h, challenger,
+        halo_a[0], halo_b[0], halo_g, randomness, u_prime, pedersen_h, challenger, rng,
     );
 
     Ok(OpeningProof {
@@ -147,23 +174,28 @@ fn build_halo_b<C: Curve>(
 ) -> Vec<C::ScalarField> {
     let power_points = points
         .iter()
-        .map(|&p| powers(p, degree))
+        .map(|&p| C::ScalarField::powers_of(p, degree))
         .collect::<Vec<_>>();
     (0..degree)
         .map(|i| reduce_with_powers(&power_points.iter().map(|v| v[i]).collect::<Vec<_>>(), v))
         .collect()
 }
 
-fn schnorr_protocol<C: HaloCurve>(
+#[allow(clippy::too_many_arguments)]
+fn schnorr_protocol<C: HaloCurve, H: SpongeHash<C::BaseField>, R: Rng>(
     halo_a: C::ScalarField,
     halo_b: C::ScalarField,
     halo_g: AffinePoint<C>,
     randomness: C::ScalarField,
     u_curve: ProjectivePoint<C>,
     pedersen_h: ProjectivePoint<C>,
-    challenger: &mut Challenger<C::BaseField>,
+    challenger: &mut Challenger<C::BaseField, H>,
+    rng: &mut R,
 ) -> SchnorrProof<C> {
-    let (d, s) = (C::ScalarField::rand(), C::ScalarField::rand());
+    let (d, s) = (
+        C::ScalarField::rand_from_rng(rng),
+        C::ScalarField::rand_from_rng(rng),
+    );
     let r_curve = C::convert(d) * (halo_g.to_projective() + C::convert(halo_b) * u_curve)
         + C::convert(s) * pedersen_h;
 
@@ -181,6 +213,40 @@ fn schnorr_protocol<C: HaloCurve>(
     }
 }
 
+/// Decompresses an opening proof serialized with `OpeningProof::to_compressed_bytes`, and verifies
+/// it as in `verify_ipa`. Returns an error if the proof can't be decompressed or any of its points
+/// lie outside the subgroup.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_open<C: HaloCurve>(
+    opening_proof_bytes: &[u8],
+    commitment: ProjectivePoint<C>,
+    value: C::ScalarField,
+    halo_b: C::ScalarField,
+    halo_us: &[C::ScalarField],
+    u_prime: ProjectivePoint<C>,
+    pedersen_h: AffinePoint<C>,
+    schnorr_challenge: C::ScalarField,
+) -> Result<bool> {
+    let opening_proof = OpeningProof::<C>::from_compressed_bytes(opening_proof_bytes)?;
+    ensure!(
+        opening_proof.halo_l.len() == halo_us.len() && opening_proof.halo_r.len() == halo_us.len(),
+        "Opening proof has the wrong number of L and R points"
+    );
+    Ok(verify_ipa(
+        &opening_proof.halo_l,
+        &opening_proof.halo_r,
+        opening_proof.halo_g,
+        commitment,
+        value,
+        halo_b,
+        halo_us,
+        u_prime,
+        pedersen_h,
+        schnorr_challenge,
+        opening_proof.schnorr_proof,
+    ))
+}
+
 /// Verify the final IPA.
 #[allow(clippy::too_many_arguments)]
 pub fn verify_ipa<C: HaloCurve>(
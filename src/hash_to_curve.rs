@@ -54,6 +54,8 @@ pub fn blake_hash_usize_to_curve<C: Curve>(seed: usize) -> AffinePoint<C> {
     blake_hash_base_field_to_curve(C::BaseField::from_canonical_usize(seed))
 }
 
+/// The result is on the curve, but it is only guaranteed to lie in the subgroup of order `C::ORDER`
+/// if `C::COFACTOR` is one; otherwise the caller should apply `C::clear_cofactor`.
 pub fn blake_hash_base_field_to_curve<C: Curve>(seed: C::BaseField) -> AffinePoint<C> {
     // Based on the MapToGroup method of BLS.
     let mut i = 0;
@@ -76,6 +78,8 @@ pub fn blake_hash_base_field_to_curve<C: Curve>(seed: C::BaseField) -> AffinePoi
 }
 
 // TODO: This is rather slow! Should use ChaCha20 or something instead of Rescue.
+/// The result is on the curve, but it is only guaranteed to lie in the subgroup of order `C::ORDER`
+/// if `C::COFACTOR` is one; otherwise the caller should apply `C::clear_cofactor`.
 pub fn hash_base_field_to_curve<C: Curve>(
     seed: C::BaseField,
     security_bits: usize,
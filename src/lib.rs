@@ -3,22 +3,18 @@
 #![allow(clippy::suspicious_arithmetic_impl)]
 // We have tons of bigint literals in Montgomery form, which won't be readable with or without underscores.
 #![allow(clippy::unreadable_literal)]
-#![feature(associated_type_bounds)]
 // This is annoying and often wrong.
 #![allow(clippy::needless_range_loop)]
 
-// Required for generic low-level functions on small arrays.
-#![feature(const_generics)]
-// Unfortunately it makes rustc complain, so we include
-#![allow(incomplete_features)]
-
-
+pub use aggregation::*;
 pub use bigint::*;
+pub use chunked_prover::*;
 pub use circuit_bigint::*;
 pub use circuit_builder::*;
 pub use circuit_curve::*;
 pub use circuit_foreign_field::*;
 pub use circuit_ordering::*;
+pub use circuit_rescue::*;
 pub use conversions::*;
 pub use curve::*;
 pub use fft::*;
@@ -26,12 +22,15 @@ pub use field::*;
 pub use gates::*;
 pub use hash_to_curve::*;
 pub use mds::*;
+pub use mock_prover::*;
 pub use partition::*;
+pub use pedersen::*;
 pub use plonk::*;
 pub use plonk_proof::*;
 pub use plonk_recursion::*;
 pub use poly_commit::*;
 pub use polynomial::*;
+pub use poseidon::*;
 pub use pseudorandom::*;
 pub use rescue::*;
 pub use serialization::*;
@@ -39,12 +38,16 @@ pub use target::*;
 pub use verifier::*;
 pub use witness::*;
 
+mod aggregation;
 mod bigint;
+mod chunked_prover;
 mod circuit_bigint;
 mod circuit_builder;
 mod circuit_curve;
 mod circuit_foreign_field;
 mod circuit_ordering;
+mod circuit_pedersen;
+mod circuit_rescue;
 mod conversions;
 mod curve;
 mod fft;
@@ -53,7 +56,9 @@ mod gates;
 pub mod halo;
 mod hash_to_curve;
 mod mds;
+mod mock_prover;
 mod partition;
+mod pedersen;
 mod plonk;
 pub mod plonk_challenger;
 mod plonk_proof;
@@ -61,10 +66,12 @@ mod plonk_recursion;
 pub mod plonk_util;
 pub mod poly_commit;
 pub mod polynomial;
+mod poseidon;
 mod pseudorandom;
 mod rescue;
 mod serialization;
 mod target;
+mod type_keyed_cache;
 pub mod util;
 mod verifier;
 mod witness;
@@ -0,0 +1,181 @@
+use rayon::prelude::*;
+
+use crate::gates::evaluate_matching_gate;
+use crate::{Circuit, Field, HaloCurve, PartialWitness, Witness, GRID_WIDTH, NUM_WIRES};
+
+/// A gate constraint which is not satisfied by a witness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintViolation {
+    /// The index of the gate whose constraint is violated.
+    pub gate: usize,
+    /// The name of the gate's type.
+    pub gate_name: &'static str,
+    /// The index of the violated constraint, within that gate type's constraints.
+    pub constraint: usize,
+    /// The label which was active when the gate was added, as set by
+    /// `CircuitBuilder::debug_annotate`. Always `None` unless the `debug` feature is enabled.
+    pub label: Option<String>,
+}
+
+/// Checks whether a witness satisfies a circuit's gate constraints, by evaluating each gate's
+/// constraints directly on the witness. This does no FFTs, MSMs or commitments, so it is much
+/// faster than generating a proof, and reports exactly which constraints fail, which makes it
+/// useful for debugging gadgets.
+///
+/// Only gate constraints are checked. Copy constraints are enforced by witness generation, rather
+/// than checked here.
+pub struct MockProver<'a, C: HaloCurve> {
+    circuit: &'a Circuit<C>,
+}
+
+impl<'a, C: HaloCurve> MockProver<'a, C> {
+    pub fn new(circuit: &'a Circuit<C>) -> Self {
+        MockProver { circuit }
+    }
+
+    /// Generates a witness from the given inputs, and returns every constraint it violates.
+    pub fn run<InnerC: HaloCurve<BaseField = C::ScalarField>>(
+        &self,
+        inputs: PartialWitness<C::ScalarField>,
+    ) -> Vec<ConstraintViolation> {
+        let witness = self.circuit.generate_witness(inputs);
+        self.check_witness::<InnerC>(&witness)
+    }
+
+    /// Returns every constraint violated by the given witness, ordered by gate index.
+    pub fn check_witness<InnerC: HaloCurve<BaseField = C::ScalarField>>(
+        &self,
+        witness: &Witness<C::ScalarField>,
+    ) -> Vec<ConstraintViolation> {
+        let degree = self.circuit.degree();
+        let wire_values = |gate: usize| {
+            (0..NUM_WIRES)
+                .map(|input| witness.get_indices(gate % degree, input))
+                .collect::<Vec<_>>()
+        };
+
+        (0..degree)
+            .into_par_iter()
+            .flat_map(|gate| {
+                let (gate_name, constraints) = evaluate_matching_gate::<C, InnerC>(
+                    &self.circuit.gate_constants[gate],
+                    &wire_values(gate),
+                    &wire_values(gate + 1),
+                    &wire_values(gate + GRID_WIDTH),
+                )
+                .expect("No gate type matches the gate's constants");
+
+                constraints
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_i, c)| c.is_nonzero())
+                    .map(|(constraint, _c)| ConstraintViolation {
+                        gate,
+                        gate_name,
+                        constraint,
+                        label: self.circuit.gate_label(gate).map(str::to_string),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Generates a partial witness for `circuit` from `inputs`, asserts that `MockProver` finds no
+/// constraint violations in it, and returns it so that tests can check the generated values.
+#[cfg(test)]
+pub(crate) fn generate_satisfying_witness<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>(
+    circuit: &Circuit<C>,
+    inputs: PartialWitness<C::ScalarField>,
+) -> PartialWitness<C::ScalarField> {
+    let partial_witness = circuit.generate_partial_witness(inputs);
+    let witness = Witness::from_partial(&partial_witness, circuit.degree());
+    assert_eq!(MockProver::new(circuit).check_witness::<InnerC>(&witness), vec![]);
+    partial_witness
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CircuitBuilder, ConstraintViolation, Curve, Field, MockProver, PartialWitness, Target, Tweedledee, Tweedledum, Witness, NUM_WIRES};
+
+    #[test]
+    fn test_mock_prover() {
+        type C = Tweedledee;
+        type SF = <C as Curve>::ScalarField;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let x_y = builder.mul(x, y);
+        let x_y_sq = builder.mul(x_y, x_y);
+        builder.rescue_hash_n_to_1(&[x_y_sq]);
+        let circuit = builder.build();
+        let mock_prover = MockProver::new(&circuit);
+
+        let (x_value, y_value) = (SF::rand(), SF::rand());
+        let inputs = || {
+            let mut partial_witness = PartialWitness::new();
+            partial_witness.set_target(x, x_value);
+            partial_witness.set_target(y, y_value);
+            partial_witness
+        };
+        assert_eq!(mock_prover.run::<Tweedledum>(inputs()), vec![]);
+
+        // Tamper with the output of the first multiplication.
+        let mut partial_witness = circuit.generate_partial_witness(inputs());
+        let bad_gate = match x_y {
+            Target::Wire(wire) => {
+                partial_witness.set_wire(wire, SF::rand());
+                wire.gate
+            }
+            _ => panic!("Expected a wire"),
+        };
+        let bad_witness = Witness::new(partial_witness.to_wire_values(circuit.degree(), NUM_WIRES));
+        assert_eq!(
+            mock_prover.check_witness::<Tweedledum>(&bad_witness),
+            vec![ConstraintViolation {
+                gate: bad_gate,
+                gate_name: "ArithmeticGate",
+                constraint: 0,
+                label: None,
+            }]
+        );
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn test_debug_annotate() {
+        type C = Tweedledee;
+        type SF = <C as Curve>::ScalarField;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let x = builder.add_virtual_target();
+        builder.debug_annotate("square");
+        let x_sq = builder.mul(x, x);
+        builder.debug_annotate("cube");
+        let x_cube = builder.mul(x_sq, x);
+        let circuit = builder.build();
+        let mock_prover = MockProver::new(&circuit);
+
+        // Tamper with the output of the second multiplication.
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_target(x, SF::rand());
+        let mut partial_witness = circuit.generate_partial_witness(partial_witness);
+        let bad_gate = match x_cube {
+            Target::Wire(wire) => {
+                partial_witness.set_wire(wire, SF::rand());
+                wire.gate
+            }
+            _ => panic!("Expected a wire"),
+        };
+        assert_eq!(circuit.gate_label(bad_gate), Some("cube"));
+        let bad_witness = Witness::new(partial_witness.to_wire_values(circuit.degree(), NUM_WIRES));
+        let violations = mock_prover.check_witness::<Tweedledum>(&bad_witness);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].gate, bad_gate);
+        assert_eq!(violations[0].label.as_deref(), Some("cube"));
+
+        // Gates added by `build` aren't labelled.
+        assert_eq!(circuit.gate_label(circuit.degree() - 1), None);
+    }
+}
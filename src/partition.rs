@@ -1,7 +1,7 @@
 use crate::{Field, Target, Wire, NUM_ROUTED_WIRES, NUM_WIRES};
 use rand_chacha::rand_core::SeedableRng;
 use rand_chacha::ChaCha8Rng;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 pub struct TargetPartitions<F: Field> {
@@ -26,6 +26,13 @@ impl<F: Field> TargetPartitions<F> {
         &self.partitions[self.indices[&target]]
     }
 
+    /// Returns the partition containing the given target, or `None` if it isn't in any partition.
+    pub fn try_get_partition(&self, target: Target<F>) -> Option<&[Target<F>]> {
+        self.indices
+            .get(&target)
+            .map(|&index| &self.partitions[index][..])
+    }
+
     /// Add a new partition with a single member.
     pub fn add_partition(&mut self, target: Target<F>) {
         let index = self.partitions.len();
@@ -81,6 +88,92 @@ impl<F: Field> TargetPartitions<F> {
     }
 }
 
+/// A union-find structure over targets, used to reduce a list of copy constraints to an
+/// equivalent minimal list.
+pub(crate) struct TargetUnionFind<F: Field> {
+    /// Every target seen so far, in the order they were first seen.
+    targets: Vec<Target<F>>,
+    indices: HashMap<Target<F>, usize>,
+    parents: Vec<usize>,
+    ranks: Vec<usize>,
+}
+
+impl<F: Field> TargetUnionFind<F> {
+    pub fn new() -> Self {
+        Self {
+            targets: Vec::new(),
+            indices: HashMap::new(),
+            parents: Vec::new(),
+            ranks: Vec::new(),
+        }
+    }
+
+    fn index(&mut self, target: Target<F>) -> usize {
+        if let Some(&index) = self.indices.get(&target) {
+            return index;
+        }
+        let index = self.targets.len();
+        self.targets.push(target);
+        self.indices.insert(target, index);
+        self.parents.push(index);
+        self.ranks.push(0);
+        index
+    }
+
+    fn find(&mut self, index: usize) -> usize {
+        let mut root = index;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        // Compress the path, so that later lookups are fast.
+        let mut i = index;
+        while self.parents[i] != root {
+            let parent = self.parents[i];
+            self.parents[i] = root;
+            i = parent;
+        }
+        root
+    }
+
+    /// Merges the classes containing `a` and `b`.
+    pub fn union(&mut self, a: Target<F>, b: Target<F>) {
+        let (a_index, b_index) = (self.index(a), self.index(b));
+        let a_root = self.find(a_index);
+        let b_root = self.find(b_index);
+        if a_root == b_root {
+            return;
+        }
+        if self.ranks[a_root] < self.ranks[b_root] {
+            self.parents[a_root] = b_root;
+        } else {
+            self.parents[b_root] = a_root;
+            if self.ranks[a_root] == self.ranks[b_root] {
+                self.ranks[a_root] += 1;
+            }
+        }
+    }
+
+    /// Returns the number of classes.
+    pub fn num_classes(&mut self) -> usize {
+        (0..self.targets.len()).filter(|&i| self.find(i) == i).count()
+    }
+
+    /// Returns a minimal list of pairs which induces the same classes, namely a pair connecting
+    /// each target to the root of its class, for each target other than the roots.
+    pub fn into_pairs(mut self) -> Vec<(Target<F>, Target<F>)> {
+        (0..self.targets.len())
+            .filter_map(|i| {
+                let root = self.find(i);
+                if root == i {
+                    None
+                } else {
+                    Some((self.targets[i], self.targets[root]))
+                }
+            })
+            .collect()
+    }
+}
+
 pub struct WirePartitions {
     partitions: Vec<Vec<Wire>>,
     indices: HashMap<Wire, usize>,
@@ -101,6 +194,18 @@ impl WirePartitions {
         }
     }
 
+    /// Asserts that each of the given wires is in a partition of its own.
+    pub(crate) fn assert_isolated(&self, wires: &HashSet<Wire>) {
+        for wire in wires {
+            assert_eq!(
+                self.partitions[self.indices[wire]].len(),
+                1,
+                "Advice wire {:?} should not be in a partition containing other wires",
+                wire
+            );
+        }
+    }
+
     /// Find a wire's "neighbor" in the context of Plonk's "extended copy constraints" check. In
     /// other words, find the next wire in the given wire's partition. If the given wire is last in
     /// its partition, this will loop around. If the given wire has a partition all to itself, it
@@ -0,0 +1,104 @@
+use crate::util::ceil_div_usize;
+use crate::{AffinePoint, Curve, Field, ProjectivePoint};
+
+/// The number of message bits in each chunk of a Pedersen hash. The first two bits of a chunk
+/// select a multiple in `{1, 2, 3, 4}`, and the third bit selects its sign, so a chunk never
+/// encodes zero.
+pub const PEDERSEN_CHUNK_BITS: usize = 3;
+
+/// The number of chunks assigned to each base. Chunk `j` of a base `B` is multiplied by
+/// `[16^j] B`, so the combined scalar of a base is `sum_j e_j 16^j` for nonzero `e_j` in
+/// `[-4, 4]`. With 63 chunks, these scalars are distinct and nonzero for fields of at least 252
+/// bits, which ensures that the incomplete additions used by the circuit never encounter equal or
+/// opposite points, except with negligible probability over the choice of bases.
+pub const PEDERSEN_CHUNKS_PER_BASE: usize = 63;
+
+/// Returns the number of bases needed to hash a message of the given length.
+pub fn pedersen_num_bases(num_bits: usize) -> usize {
+    ceil_div_usize(
+        ceil_div_usize(num_bits, PEDERSEN_CHUNK_BITS),
+        PEDERSEN_CHUNKS_PER_BASE,
+    )
+}
+
+/// Returns the signed multiple in `{-4, ..., -1, 1, ..., 4}` encoded by the given chunk.
+pub(crate) fn pedersen_chunk_value(chunk: [bool; PEDERSEN_CHUNK_BITS]) -> i64 {
+    let magnitude = 1 + chunk[0] as i64 + 2 * chunk[1] as i64;
+    if chunk[2] {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Returns the chunks of a message, padded with zero bits to a multiple of `PEDERSEN_CHUNK_BITS`.
+pub(crate) fn pedersen_chunks<T: Copy>(bits: &[T], zero: T) -> Vec<[T; PEDERSEN_CHUNK_BITS]> {
+    bits.chunks(PEDERSEN_CHUNK_BITS)
+        .map(|chunk| {
+            let mut padded = [zero; PEDERSEN_CHUNK_BITS];
+            padded[..chunk.len()].copy_from_slice(chunk);
+            padded
+        })
+        .collect()
+}
+
+/// Returns the point multiplied by chunk `j` of a base, i.e. `[16^j] base`.
+pub(crate) fn pedersen_chunk_base<C: Curve>(base: AffinePoint<C>, j: usize) -> ProjectivePoint<C> {
+    let mut point = base.to_projective();
+    for _ in 0..(4 * j) {
+        point = point.double();
+    }
+    point
+}
+
+/// Computes a Pedersen hash of the given bits. Bits are split into chunks of
+/// `PEDERSEN_CHUNK_BITS`, and each group of `PEDERSEN_CHUNKS_PER_BASE` chunks is assigned to the
+/// next base, which should be independent generators with unknown discrete logarithms, e.g. from
+/// `blake_hash_usize_to_curve`. The message must be nonempty, and there must be at least
+/// `pedersen_num_bases(bits.len())` bases.
+pub fn pedersen_hash_bits<C: Curve>(bits: &[bool], bases: &[AffinePoint<C>]) -> AffinePoint<C> {
+    assert!(!bits.is_empty(), "Cannot hash an empty message");
+    assert!(
+        bases.len() >= pedersen_num_bases(bits.len()),
+        "Not enough bases for a message of {} bits",
+        bits.len()
+    );
+
+    let mut result = ProjectivePoint::ZERO;
+    for (t, chunk) in pedersen_chunks(bits, false).into_iter().enumerate() {
+        let base = bases[t / PEDERSEN_CHUNKS_PER_BASE];
+        let point = pedersen_chunk_base(base, t % PEDERSEN_CHUNKS_PER_BASE);
+        let value = pedersen_chunk_value(chunk);
+        let multiple = C::convert(C::ScalarField::from_canonical_u64(value.unsigned_abs())) * point;
+        result = result + if value < 0 { -multiple } else { multiple };
+    }
+    result.to_affine()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{blake_hash_usize_to_curve, pedersen_hash_bits, AffinePoint, Curve, Field, Tweedledum};
+
+    #[test]
+    fn test_pedersen_hash_bits() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+        let base: AffinePoint<C> = blake_hash_usize_to_curve(0);
+        let multiple = |k: u64| C::convert(SF::from_canonical_u64(k)) * base.to_projective();
+
+        assert_eq!(pedersen_hash_bits(&[false], &[base]), base);
+        assert_eq!(
+            pedersen_hash_bits(&[false, true], &[base]),
+            multiple(3).to_affine()
+        );
+        assert_eq!(
+            pedersen_hash_bits(&[true, true, true], &[base]),
+            (-multiple(4)).to_affine()
+        );
+        // The second chunk is multiplied by [16] base.
+        assert_eq!(
+            pedersen_hash_bits(&[false, false, false, true], &[base]),
+            multiple(1 + 2 * 16).to_affine()
+        );
+    }
+}
@@ -1,32 +1,56 @@
 use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet};
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Write};
 use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{ensure, Result};
+use rand::{thread_rng, Rng};
 use rayon::prelude::*;
 
 use crate::halo::batch_opening_proof;
 use crate::partition::{get_subgroup_shift, TargetPartitions};
-use crate::plonk_challenger::Challenger;
+use crate::plonk_challenger::{Challenger, RescueHash, SpongeHash};
 use crate::plonk_proof::{OldProof, Proof};
-use crate::plonk_util::{commit_polynomials, eval_l_1, eval_polys, eval_zero_poly, pad_to_8n, permutation_polynomial, polynomials_to_values_padded, powers, reduce_with_powers, scale_polynomials, values_to_polynomials};
+use crate::plonk_util::{commit_polynomials_with_rng, eval_l_1, eval_polys, eval_zero_poly, pad_to_8n, permutation_polynomial, polynomials_to_values_padded, powers, reduce_with_powers, scale_polynomials, values_to_polynomials};
 use crate::poly_commit::PolynomialCommitment;
 use crate::polynomial::Polynomial;
-use crate::target::Target;
+use crate::gates::describe_matching_gate;
+use crate::target::{Target, VirtualTarget, Wire};
 use crate::util::{ceil_div_usize, log2_strict};
+use crate::verifier::verifier_key_hash;
 use crate::witness::{PartialWitness, Witness, WitnessGenerator};
-use crate::{evaluate_all_constraints, fft_with_precomputation_power_of_2, AffinePoint, FftPrecomputation, Field, HaloCurve, MsmPrecomputation, OpeningSet, VerificationKey};
+use crate::{evaluate_all_constraints, fft_with_precomputation_power_of_2, AffinePoint, FftPrecomputation, Field, HaloCurve, MergePolicy, MockProver, MsmPrecomputation, OpeningSet, VerificationKey};
 
 pub(crate) const NUM_WIRES: usize = 9;
 pub(crate) const NUM_ROUTED_WIRES: usize = 6;
 pub(crate) const NUM_ADVICE_WIRES: usize = NUM_WIRES - NUM_ROUTED_WIRES;
-pub(crate) const NUM_CONSTANTS: usize = 6;
+pub(crate) const NUM_CONSTANTS: usize = 7;
 pub(crate) const GRID_WIDTH: usize = 65;
 // This is currently dominated by Base4SumGate. It has degree-4n constraints, and its prefix is 4
 // bits long, so its filtered constraints are degree-8n. Dividing by Z_H makes t degree-7n.
+// `GateCollection::all` derives the same multiplier from each gate's declared degree.
 pub(crate) const QUOTIENT_POLYNOMIAL_DEGREE_MULTIPLIER: usize = 7;
 
+/// An error returned by the prover when a witness does not satisfy a circuit's gate constraints,
+/// in which case any proof it produced would fail to verify.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnsatisfiedConstraints {
+    /// The indices of the gates with at least one violated constraint, in increasing order.
+    pub gates: Vec<usize>,
+}
+
+impl fmt::Display for UnsatisfiedConstraints {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Witness does not satisfy the constraints of gates {:?}",
+            self.gates
+        )
+    }
+}
+
+impl std::error::Error for UnsatisfiedConstraints {}
+
 /// Contains all data needed to generate and/or verify proofs.
 pub struct Circuit<C: HaloCurve> {
     pub security_bits: usize,
@@ -67,6 +91,10 @@ pub struct Circuit<C: HaloCurve> {
     pub fft_precomputation_n: FftPrecomputation<C::ScalarField>,
     /// A precomputation used for FFTs of degree 8n, where n is the number of gates.
     pub fft_precomputation_8n: FftPrecomputation<C::ScalarField>,
+    /// The label which was active when each gate was added, as set by
+    /// `CircuitBuilder::debug_annotate`.
+    #[cfg(feature = "debug")]
+    pub gate_labels: Vec<Option<String>>,
 }
 
 impl<C: HaloCurve> Circuit<C> {
@@ -78,6 +106,20 @@ impl<C: HaloCurve> Circuit<C> {
         log2_strict(self.degree())
     }
 
+    /// Returns the label which was active when the given gate was added, if any. This is always
+    /// `None` unless the `debug` feature is enabled.
+    #[allow(unused_variables)]
+    pub fn gate_label(&self, gate: usize) -> Option<&str> {
+        #[cfg(feature = "debug")]
+        {
+            self.gate_labels.get(gate).and_then(|label| label.as_deref())
+        }
+        #[cfg(not(feature = "debug"))]
+        {
+            None
+        }
+    }
+
     // TODO: For now we assume that there's exactly one embedded curve, InnerC.
     // Ideally it should be possible to use any number of embedded curves (including zero),
     // and we should add a set of curve gates for each embedded curve.
@@ -87,7 +129,82 @@ impl<C: HaloCurve> Circuit<C> {
         old_proofs: &[OldProof<C>],
         blinding_commitments: bool,
     ) -> Result<Proof<C>> {
-        let mut challenger = Challenger::new(self.security_bits);
+        self.generate_proof_with_rng::<InnerC, _>(
+            witness,
+            old_proofs,
+            blinding_commitments,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Like `generate_proof`, but first checks that the witness satisfies the gate constraints,
+    /// returning an `UnsatisfiedConstraints` error if not, before doing any FFTs or MSMs. Debug
+    /// builds always perform this check; this method also performs it in release builds.
+    pub fn generate_proof_checked<InnerC: HaloCurve<BaseField = C::ScalarField>>(
+        &self,
+        witness: &Witness<C::ScalarField>,
+        old_proofs: &[OldProof<C>],
+        blinding_commitments: bool,
+    ) -> Result<Proof<C>> {
+        self.check_witness::<InnerC>(witness)?;
+        self.generate_proof::<InnerC>(witness, old_proofs, blinding_commitments)
+    }
+
+    /// Checks that the witness satisfies every gate constraint, by evaluating each gate's
+    /// constraints directly, as `MockProver` does. This is much cheaper than generating a proof.
+    pub fn check_witness<InnerC: HaloCurve<BaseField = C::ScalarField>>(
+        &self,
+        witness: &Witness<C::ScalarField>,
+    ) -> std::result::Result<(), UnsatisfiedConstraints> {
+        let mut gates: Vec<usize> = MockProver::new(self)
+            .check_witness::<InnerC>(witness)
+            .into_iter()
+            .map(|violation| violation.gate)
+            .collect();
+        gates.dedup();
+        if gates.is_empty() {
+            Ok(())
+        } else {
+            Err(UnsatisfiedConstraints { gates })
+        }
+    }
+
+    /// Like `generate_proof`, but draws all prover randomness from the given RNG, so that proofs
+    /// generated with identically seeded RNGs are identical.
+    pub fn generate_proof_with_rng<InnerC: HaloCurve<BaseField = C::ScalarField>, R: Rng>(
+        &self,
+        witness: &Witness<C::ScalarField>,
+        old_proofs: &[OldProof<C>],
+        blinding_commitments: bool,
+        rng: &mut R,
+    ) -> Result<Proof<C>> {
+        self.generate_proof_with_hash::<InnerC, RescueHash<C::BaseField>, R>(
+            witness,
+            old_proofs,
+            blinding_commitments,
+            rng,
+        )
+    }
+
+    /// Like `generate_proof_with_rng`, but hashes the transcript with the given `SpongeHash`. The
+    /// proof must be verified with `verify_proof_with_hash` using the same `H`. Only Rescue
+    /// transcripts are supported by the recursive verifier.
+    pub fn generate_proof_with_hash<
+        InnerC: HaloCurve<BaseField = C::ScalarField>,
+        H: SpongeHash<C::BaseField>,
+        R: Rng,
+    >(
+        &self,
+        witness: &Witness<C::ScalarField>,
+        old_proofs: &[OldProof<C>],
+        blinding_commitments: bool,
+        rng: &mut R,
+    ) -> Result<Proof<C>> {
+        if cfg!(debug_assertions) {
+            self.check_witness::<InnerC>(witness)?;
+        }
+
+        let mut challenger = Challenger::<C::BaseField, H>::with_hash(self.security_bits);
 
         // Convert the witness both to coefficient form and a degree-8n LDE.
         let wire_values_by_wire_index = &witness.transpose();
@@ -97,11 +214,12 @@ impl<C: HaloCurve> Circuit<C> {
             polynomials_to_values_padded(&wire_polynomials, &self.fft_precomputation_8n);
 
         // Commit to the wire polynomials.
-        let c_wires = commit_polynomials(
+        let c_wires = commit_polynomials_with_rng(
             &wire_polynomials,
             &self.pedersen_g_msm_precomputation,
             self.pedersen_h,
             blinding_commitments,
+            rng,
         );
 
         let num_public_input_gates = ceil_div_usize(self.num_public_inputs, NUM_WIRES);
@@ -135,10 +253,11 @@ impl<C: HaloCurve> Circuit<C> {
         // Commit to Z.
         let plonk_z_polynomial =
             Polynomial::from_evaluations(&plonk_z_points_n, &self.fft_precomputation_n);
-        let c_plonk_z = plonk_z_polynomial.commit(
+        let c_plonk_z = plonk_z_polynomial.commit_with_rng(
             &self.pedersen_g_msm_precomputation,
             self.pedersen_h,
             blinding_commitments,
+            rng,
         );
 
         // Generate a random alpha from the transcript.
@@ -156,14 +275,16 @@ impl<C: HaloCurve> Circuit<C> {
         );
 
         if cfg!(debug_assertions) {
-            // Check that the vanishing polynomial indeed vanishes.
-            self.subgroup_n.iter().enumerate().for_each(|(i, &x)| {
-                assert!(
+            // Check that the vanishing polynomial indeed vanishes. This also catches violated copy
+            // constraints, which `check_witness` doesn't check.
+            for (i, &x) in self.subgroup_n.iter().enumerate() {
+                ensure!(
                     vanishing_poly.eval(x).is_zero(),
-                    "{}-th gate constraints are not satisfied",
-                    i
+                    "{}-th gate constraints are not satisfied (label: {:?})",
+                    i,
+                    self.gate_label(i)
                 );
-            });
+            }
         }
 
         // Compute the quotient polynomial, t(x) = vanishing(x) / Z_H(x).
@@ -178,6 +299,14 @@ impl<C: HaloCurve> Circuit<C> {
             );
         }
 
+        // If the witness doesn't satisfy the constraints, the vanishing polynomial isn't divisible by
+        // `Z_H`, and the purported quotient is generally too large to be split into 7 chunks.
+        ensure!(
+            plonk_t_poly.is_zero()
+                || plonk_t_poly.degree() < QUOTIENT_POLYNOMIAL_DEGREE_MULTIPLIER * self.degree(),
+            "The quotient polynomial is too large; the witness does not satisfy the constraints"
+        );
+
         // Pad the coefficients to 7n.
         plonk_t_poly.pad(QUOTIENT_POLYNOMIAL_DEGREE_MULTIPLIER * self.degree());
 
@@ -189,11 +318,12 @@ impl<C: HaloCurve> Circuit<C> {
             .collect::<Vec<_>>();
 
         // Commit to the quotient polynomial.
-        let c_plonk_t = commit_polynomials(
+        let c_plonk_t = commit_polynomials_with_rng(
             &plonk_t_poly_chunks,
             &self.pedersen_g_msm_precomputation,
             self.pedersen_h,
             blinding_commitments,
+            rng,
         );
 
         // Combine the coefficients in `wires_coeffs_no_pis` using a linear combination weighted by `alpha`.
@@ -228,10 +358,11 @@ impl<C: HaloCurve> Circuit<C> {
             ans
         };
         // Commit to the public inputs quotient polynomial.
-        let c_pis_quotient = pis_quotient_poly.commit(
+        let c_pis_quotient = pis_quotient_poly.commit_with_rng(
             &self.pedersen_g_msm_precomputation,
             self.pedersen_h,
             blinding_commitments,
+            rng,
         );
 
         let public_inputs = (0..self.num_public_inputs)
@@ -355,9 +486,11 @@ impl<C: HaloCurve> Circuit<C> {
             self.degree(),
             self.security_bits,
             &mut challenger,
+            rng,
         )?;
 
         Ok(Proof {
+            vk_hash: self.verifier_key_hash(),
             c_wires: c_wires.iter().map(|c| c.to_affine()).collect(),
             c_plonk_z: c_plonk_z.to_affine(),
             c_plonk_t: c_plonk_t.iter().map(|c| c.to_affine()).collect(),
@@ -390,14 +523,22 @@ impl<C: HaloCurve> Circuit<C> {
             &self.fft_precomputation_8n,
         );
 
-        // We will evaluate the vanishing polynomial at 8n points, then interpolate.
+        // We will evaluate the vanishing polynomial at 8n points, then interpolate. Each thread
+        // reuses its own buffers for the constant and wire values, rather than allocating them for
+        // every point.
         let vanishing_points = self
             .subgroup_8n
             .par_iter()
             .enumerate()
-            .map(|(i, &x)| {
+            .map_init(VanishingScratch::default, |scratch, (i, &x)| {
                 // Load the constant polynomials' values at x.
-                let mut local_constant_values = Vec::new();
+                let VanishingScratch {
+                    local_constant_values,
+                    local_wire_values,
+                    right_wire_values,
+                    below_wire_values,
+                } = scratch;
+                local_constant_values.clear();
                 for j in 0..NUM_CONSTANTS {
                     local_constant_values.push(self.constants_8n[j][i]);
                 }
@@ -407,9 +548,9 @@ impl<C: HaloCurve> Circuit<C> {
                 // to a shift of 8 in the degree-8n subgroup.
                 let i_right = (i + 8) % (8 * degree);
                 let i_below = (i + 8 * GRID_WIDTH) % (8 * degree);
-                let mut local_wire_values = Vec::new();
-                let mut right_wire_values = Vec::new();
-                let mut below_wire_values = Vec::new();
+                local_wire_values.clear();
+                right_wire_values.clear();
+                below_wire_values.clear();
                 for j in 0..NUM_WIRES {
                     local_wire_values.push(wire_values_8n[j][i]);
                     right_wire_values.push(wire_values_8n[j][i_right]);
@@ -417,10 +558,10 @@ impl<C: HaloCurve> Circuit<C> {
                 }
 
                 let constraint_terms = evaluate_all_constraints::<C, InnerC>(
-                    &local_constant_values,
-                    &local_wire_values,
-                    &right_wire_values,
-                    &below_wire_values,
+                    local_constant_values,
+                    local_wire_values,
+                    right_wire_values,
+                    below_wire_values,
                 );
 
                 // Evaluate the L_1(x) (Z(x) - 1) vanishing term.
@@ -436,8 +577,8 @@ impl<C: HaloCurve> Circuit<C> {
                     let k_i = k_is[j];
                     let s_id = k_i * x;
                     let s_sigma = self.s_sigma_values_8n[j][i];
-                    f_prime = f_prime * (wire_value + beta_sf * s_id + gamma_sf);
-                    g_prime = g_prime * (wire_value + beta_sf * s_sigma + gamma_sf);
+                    f_prime *= wire_value + beta_sf * s_id + gamma_sf;
+                    g_prime *= wire_value + beta_sf * s_sigma + gamma_sf;
                 }
                 let vanishing_v_shift_term = f_prime * z_x - g_prime * z_gz;
 
@@ -510,7 +651,9 @@ impl<C: HaloCurve> Circuit<C> {
 
         let mut copy_result = self.generate_copies(&witness, &witness.all_populated_targets());
         copy_result.copy_buffer_to_pi_gate(self.num_gates_without_pis);
-        witness.extend(copy_result);
+        witness
+            .extend(copy_result, MergePolicy::ErrorOnConflict)
+            .expect("Copy constraints produced a conflicting value");
 
         // Build a list of "pending" generators which are ready to run.
         let mut pending_generator_indices = HashSet::new();
@@ -537,14 +680,18 @@ impl<C: HaloCurve> Circuit<C> {
                     self.generators[generator_idx].borrow();
                 let result = generator.generate(&self.gate_constants, &witness);
                 populated_targets.extend(result.all_populated_targets());
-                witness.extend(result);
+                witness
+                    .extend(result, MergePolicy::ErrorOnConflict)
+                    .expect("Generator produced a value conflicting with the witness");
                 completed_generator_indices.insert(generator_idx);
             }
 
             let mut copy_result = self.generate_copies(&witness, &populated_targets);
             copy_result.copy_buffer_to_pi_gate(self.num_gates_without_pis);
             populated_targets.extend(copy_result.all_populated_targets());
-            witness.extend(copy_result);
+            witness
+                .extend(copy_result, MergePolicy::ErrorOnConflict)
+                .expect("Copy constraints produced a conflicting value");
 
             // Refresh the set of pending generators.
             pending_generator_indices.clear();
@@ -615,6 +762,21 @@ impl<C: HaloCurve> Circuit<C> {
         result
     }
 
+    /// Returns the digest of this circuit's verification key; see
+    /// `VerificationKey::verifier_key_hash`.
+    pub fn verifier_key_hash(&self) -> C::BaseField {
+        let c_constants: Vec<_> = self.c_constants.iter().map(|c| c.to_affine()).collect();
+        let c_s_sigmas: Vec<_> = self.c_s_sigmas.iter().map(|c| c.to_affine()).collect();
+        verifier_key_hash(
+            &c_constants,
+            &c_s_sigmas,
+            self.degree(),
+            self.num_public_inputs,
+            self.num_gates_without_pis,
+            self.security_bits,
+        )
+    }
+
     pub fn to_vk(&self) -> VerificationKey<C> {
         VerificationKey {
             c_constants: self
@@ -636,6 +798,54 @@ impl<C: HaloCurve> Circuit<C> {
         }
     }
 
+    /// Returns a human-readable listing of the circuit's constraint system, for auditing. For each
+    /// gate row, this lists the gate type, its configuration constants (i.e. those after its
+    /// prefix), each routed wire which is copy constrained to other targets, and the gate's
+    /// constraints, as described by `Gate::constraint_descriptions`.
+    pub fn to_debug_string<InnerC: HaloCurve<BaseField = C::ScalarField>>(&self) -> String {
+        let mut result = String::new();
+        for (gate, constants) in self.gate_constants.iter().enumerate() {
+            let (name, prefix_len, descriptions) =
+                describe_matching_gate::<C, InnerC>(constants)
+                    .expect("No gate type matches the gate's constants");
+            writeln!(result, "gate {}: {}", gate, name).unwrap();
+
+            let config_constants: Vec<String> = constants[prefix_len..]
+                .iter()
+                .enumerate()
+                .map(|(i, c)| format!("c[{}] = {}", prefix_len + i, c))
+                .collect();
+            writeln!(result, "  constants: [{}]", config_constants.join(", ")).unwrap();
+
+            let mut routes = Vec::new();
+            for input in 0..NUM_ROUTED_WIRES {
+                let wire = Target::Wire(Wire { gate, input });
+                let partition = self.routing_target_partitions.try_get_partition(wire);
+                let siblings: Vec<String> = partition
+                    .unwrap_or(&[])
+                    .iter()
+                    .filter(|&&t| t != wire)
+                    .map(|&t| match t {
+                        Target::Wire(Wire { gate, input }) => format!("({}, {})", gate, input),
+                        Target::VirtualTarget(VirtualTarget { index }) => format!("v{}", index),
+                        Target::PublicInput(public_input) => format!("pi{}", public_input.index),
+                        Target::_Field(_, _) => unreachable!(),
+                    })
+                    .collect();
+                if !siblings.is_empty() {
+                    routes.push(format!("w[{}] ~ {}", input, siblings.join(", ")));
+                }
+            }
+            writeln!(result, "  routing: [{}]", routes.join("; ")).unwrap();
+
+            writeln!(result, "  constraints:").unwrap();
+            for description in descriptions {
+                writeln!(result, "    {}", description).unwrap();
+            }
+        }
+        result
+    }
+
     pub fn get_public_inputs(&self, witness: &Witness<C::ScalarField>) -> Vec<C::ScalarField> {
         (0..self.num_public_inputs)
             .map(|i| witness.get_indices(self.num_gates_without_pis + 2 * (i / NUM_WIRES), i % NUM_WIRES))
@@ -648,3 +858,23 @@ impl<C: HaloCurve> Debug for Circuit<C> {
         write!(f, "Circuit of size {}.", self.degree())
     }
 }
+
+/// Buffers for the values loaded at each point when evaluating the vanishing polynomial, which are
+/// reused across the points handled by a single thread.
+struct VanishingScratch<F: Field> {
+    local_constant_values: Vec<F>,
+    local_wire_values: Vec<F>,
+    right_wire_values: Vec<F>,
+    below_wire_values: Vec<F>,
+}
+
+impl<F: Field> Default for VanishingScratch<F> {
+    fn default() -> Self {
+        VanishingScratch {
+            local_constant_values: Vec::with_capacity(NUM_CONSTANTS),
+            local_wire_values: Vec::with_capacity(NUM_WIRES),
+            right_wire_values: Vec::with_capacity(NUM_WIRES),
+            below_wire_values: Vec::with_capacity(NUM_WIRES),
+        }
+    }
+}
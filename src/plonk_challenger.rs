@@ -1,29 +1,130 @@
-use crate::{rescue_permutation, AffinePoint, AffinePointTarget, CircuitBuilder, Curve, Field, HaloCurve, ProjectivePoint, Target, RESCUE_SPONGE_RATE, RESCUE_SPONGE_WIDTH};
+use crate::util::ceil_div_usize;
+use crate::{poseidon_permutation, rescue_permutation, AffinePoint, AffinePointTarget, CircuitBuilder, Curve, Field, HaloCurve, ProjectivePoint, Target, RESCUE_SPONGE_RATE, RESCUE_SPONGE_WIDTH};
+
+/// A permutation-based sponge, which a `Challenger` uses to hash its transcript. The prover and
+/// verifier of a proof must use the same implementation.
+pub trait SpongeHash<F: Field>: Clone {
+    /// The number of elements which can be absorbed or squeezed per permutation.
+    const RATE: usize;
+
+    fn new(security_bits: usize) -> Self;
+
+    /// Adds at most `RATE` inputs to the rate portion of the state, then applies the permutation.
+    fn absorb(&mut self, inputs: &[F]);
+
+    /// Returns the rate portion of the state.
+    fn squeeze(&self) -> Vec<F>;
+
+    /// Applies the permutation, without absorbing any inputs.
+    fn permute(&mut self);
+}
+
+/// A sponge using the Rescue permutation. This is the default, as the recursive verifier hashes
+/// its transcript with Rescue gates.
+#[derive(Clone)]
+pub struct RescueHash<F: Field> {
+    state: Vec<F>,
+    security_bits: usize,
+}
+
+impl<F: Field> SpongeHash<F> for RescueHash<F> {
+    const RATE: usize = RESCUE_SPONGE_RATE;
+
+    fn new(security_bits: usize) -> Self {
+        RescueHash {
+            state: vec![F::ZERO; RESCUE_SPONGE_WIDTH],
+            security_bits,
+        }
+    }
+
+    fn absorb(&mut self, inputs: &[F]) {
+        debug_assert!(inputs.len() <= Self::RATE);
+        for (x, &input) in self.state.iter_mut().zip(inputs) {
+            *x += input;
+        }
+        self.permute();
+    }
+
+    fn squeeze(&self) -> Vec<F> {
+        self.state[0..Self::RATE].to_vec()
+    }
+
+    fn permute(&mut self) {
+        self.state = rescue_permutation(&self.state, self.security_bits);
+    }
+}
+
+/// A sponge using the Poseidon permutation, which is cheaper to evaluate natively than Rescue.
+/// There is no recursive version of it, so proofs whose transcripts use it cannot be verified
+/// recursively.
+#[derive(Clone)]
+pub struct PoseidonHash<F: Field> {
+    state: Vec<F>,
+}
+
+impl<F: Field> SpongeHash<F> for PoseidonHash<F> {
+    const RATE: usize = RESCUE_SPONGE_RATE;
+
+    fn new(security_bits: usize) -> Self {
+        assert!(
+            security_bits <= 128,
+            "The Poseidon parameters only target 128-bit security"
+        );
+        PoseidonHash {
+            state: vec![F::ZERO; RESCUE_SPONGE_WIDTH],
+        }
+    }
+
+    fn absorb(&mut self, inputs: &[F]) {
+        debug_assert!(inputs.len() <= Self::RATE);
+        for (x, &input) in self.state.iter_mut().zip(inputs) {
+            *x += input;
+        }
+        self.permute();
+    }
+
+    fn squeeze(&self) -> Vec<F> {
+        self.state[0..Self::RATE].to_vec()
+    }
+
+    fn permute(&mut self) {
+        self.state = poseidon_permutation(&self.state);
+    }
+}
 
 /// Observes prover messages, and generates challenges by hashing the transcript.
 #[derive(Clone)]
-pub struct Challenger<F: Field> {
-    sponge_state: Vec<F>,
+pub struct Challenger<F: Field, H: SpongeHash<F> = RescueHash<F>> {
+    sponge: H,
     input_buffer: Vec<F>,
     output_buffer: Vec<F>,
-    security_bits: usize,
+}
+
+impl<F: Field> Challenger<F, RescueHash<F>> {
+    /// Creates a challenger which hashes its transcript with Rescue. This is separate from
+    /// `with_hash` so that the sponge type can be inferred, since type parameter defaults don't
+    /// guide inference.
+    pub fn new(security_bits: usize) -> Self {
+        Self::with_hash(security_bits)
+    }
 }
 
 /// Observes prover messages, and generates verifier challenges based on the transcript.
 ///
-/// The implementation is roughly based on a duplex sponge with a Rescue permutation. Note that in
-/// each round, our sponge can absorb an arbitrary number of prover messages and generate an
-/// arbitrary number of verifier challenges. This might appear to diverge from the duplex sponge
-/// design, but it can be viewed as a duplex sponge whose inputs are sometimes zero (when we perform
-/// multiple squeezes) and whose outputs are sometimes ignored (when we perform multiple
-/// absorptions). Thus the security properties of a duplex sponge still apply to our design.
-impl<F: Field> Challenger<F> {
-    pub fn new(security_bits: usize) -> Challenger<F> {
+/// The implementation is roughly based on a duplex sponge with a Rescue permutation, or another
+/// `SpongeHash`. Note that in each round, our sponge can absorb an arbitrary number of prover
+/// messages and generate an arbitrary number of verifier challenges. This might appear to diverge
+/// from the duplex sponge design, but it can be viewed as a duplex sponge whose inputs are
+/// sometimes zero (when we perform multiple squeezes) and whose outputs are sometimes ignored (when
+/// we perform multiple absorptions). Thus the security properties of a duplex sponge still apply
+/// to our design.
+impl<F: Field, H: SpongeHash<F>> Challenger<F, H> {
+    /// Creates a challenger which hashes its transcript with the sponge `H`.
+    pub fn with_hash(security_bits: usize) -> Challenger<F, H> {
         Challenger {
-            sponge_state: vec![F::ZERO; RESCUE_SPONGE_WIDTH],
+            sponge: H::new(security_bits),
             input_buffer: Vec::new(),
             output_buffer: Vec::new(),
-            security_bits,
         }
     }
 
@@ -60,13 +161,50 @@ impl<F: Field> Challenger<F> {
         self.observe_affine_points(&ProjectivePoint::batch_to_affine(points));
     }
 
+    /// Observes a labelled byte string, such as a domain tag or an arbitrary payload.
+    ///
+    /// The label and the bytes are each prefixed with their length, then packed into field
+    /// elements of `(F::BITS - 1) / 8` little-endian bytes each, so that every chunk is less than
+    /// the field order. The length prefixes make the encoding injective, so e.g. observing `"ab"`
+    /// then `"c"` differs from observing `"a"` then `"bc"`.
+    pub fn observe_bytes(&mut self, label: &str, bytes: &[u8]) {
+        self.observe_length_prefixed_bytes(label.as_bytes());
+        self.observe_length_prefixed_bytes(bytes);
+    }
+
+    fn observe_length_prefixed_bytes(&mut self, bytes: &[u8]) {
+        let bytes_per_element = (F::BITS - 1) / 8;
+        let bytes_per_canonical = ceil_div_usize(F::BITS, 64) * 8;
+        self.observe_element(F::from_canonical_usize(bytes.len()));
+        for chunk in bytes.chunks(bytes_per_element) {
+            let mut padded = chunk.to_vec();
+            padded.resize(bytes_per_canonical, 0);
+            let element = F::from_canonical_u8_vec(padded)
+                .expect("A chunk of at most BITS - 1 bits is less than the field order");
+            self.observe_element(element);
+        }
+    }
+
+    /// Returns an independent copy of this challenger. The fork initially generates the same
+    /// challenges as `self`, but observations made by either one have no effect on the other.
+    pub fn fork(&self) -> Challenger<F, H> {
+        self.clone()
+    }
+
+    /// Merges a fork's transcript into this one, by squeezing a digest from a copy of `other` and
+    /// observing it. The fork itself is left unchanged.
+    pub fn absorb_transcript(&mut self, other: &Challenger<F, H>) {
+        let digest = other.clone().get_challenge();
+        self.observe_element(digest);
+    }
+
     pub fn get_challenge(&mut self) -> F {
         self.absorb_buffered_inputs();
 
         if self.output_buffer.is_empty() {
             // Evaluate the permutation to produce `r` new outputs.
-            self.sponge_state = rescue_permutation(&self.sponge_state, self.security_bits);
-            self.output_buffer = self.sponge_state[0..RESCUE_SPONGE_RATE].to_vec();
+            self.sponge.permute();
+            self.output_buffer = self.sponge.squeeze();
         }
 
         self.output_buffer
@@ -92,17 +230,11 @@ impl<F: Field> Challenger<F> {
 
     /// Absorb any buffered inputs. After calling this, the input buffer will be empty.
     fn absorb_buffered_inputs(&mut self) {
-        for input_chunk in self.input_buffer.chunks(RESCUE_SPONGE_RATE) {
-            // Add the inputs to our sponge state.
-            for (i, &input) in input_chunk.iter().enumerate() {
-                self.sponge_state[i] = self.sponge_state[i] + input;
-            }
-
-            // Apply the permutation.
-            self.sponge_state = rescue_permutation(&self.sponge_state, self.security_bits);
+        for input_chunk in self.input_buffer.chunks(H::RATE) {
+            self.sponge.absorb(input_chunk);
         }
 
-        self.output_buffer = self.sponge_state[0..RESCUE_SPONGE_RATE].to_vec();
+        self.output_buffer = self.sponge.squeeze();
 
         self.input_buffer.clear();
     }
@@ -232,6 +364,58 @@ mod tests {
     use crate::plonk_challenger::{Challenger, RecursiveChallenger};
     use crate::{CircuitBuilder, Curve, Field, PartialWitness, Target, Tweedledum};
 
+    #[test]
+    fn test_fork() {
+        type F = <Tweedledum as Curve>::ScalarField;
+
+        let mut parent = Challenger::<F>::new(128);
+        parent.observe_elements(&[F::rand(), F::rand()]);
+        let mut fork = parent.fork();
+        assert_eq!(parent.clone().get_challenge(), fork.clone().get_challenge());
+
+        fork.observe_element(F::rand());
+        let fork_challenge = fork.clone().get_challenge();
+        assert_ne!(parent.clone().get_challenge(), fork_challenge);
+
+        // Merging the fork changes the parent's challenges, and depends on the fork's state.
+        let mut merged = parent.fork();
+        merged.absorb_transcript(&fork);
+        let mut merged_unchanged = parent.fork();
+        merged_unchanged.absorb_transcript(&parent);
+        let merged_challenge = merged.get_challenge();
+        assert_ne!(merged_challenge, parent.get_challenge());
+        assert_ne!(merged_challenge, merged_unchanged.get_challenge());
+        assert_eq!(fork.get_challenge(), fork_challenge);
+    }
+
+    #[test]
+    fn test_observe_bytes() {
+        type F = <Tweedledum as Curve>::ScalarField;
+
+        let challenge = |observations: &[(&str, &[u8])]| {
+            let mut challenger = Challenger::<F>::new(128);
+            for &(label, bytes) in observations {
+                challenger.observe_bytes(label, bytes);
+            }
+            challenger.get_challenge()
+        };
+
+        // Splits of the same concatenation, whether within the payloads or between a label and
+        // its payload, yield different challenges.
+        let payload: Vec<u8> = (0..100).collect();
+        let whole = challenge(&[("data", &payload)]);
+        assert_ne!(whole, challenge(&[("data", &payload[..40]), ("", &payload[40..])]));
+        assert_ne!(whole, challenge(&[("dat", b"a"), ("", &payload)]));
+        assert_ne!(whole, challenge(&[("data", &payload[..99]), ("", &payload[99..])]));
+        assert_ne!(challenge(&[("", b"")]), challenge(&[]));
+
+        // Trailing zero bytes are not lost to padding.
+        assert_ne!(challenge(&[("data", &[1])]), challenge(&[("data", &[1, 0])]));
+
+        assert_eq!(whole, challenge(&[("data", &payload)]));
+        assert_ne!(whole, challenge(&[("tag", &payload)]));
+    }
+
     /// Tests for consistency between `Challenger` and `RecursiveChallenger`.
     #[test]
     fn test_consistency() {
@@ -249,7 +433,7 @@ mod tests {
             .map(|&n| (0..n).map(|_| SF::rand()).collect::<Vec<_>>())
             .collect();
 
-        let mut challenger = Challenger::new(128);
+        let mut challenger = Challenger::<SF>::new(128);
         let mut outputs_per_round: Vec<Vec<SF>> = Vec::new();
         for (r, inputs) in inputs_per_round.iter().enumerate() {
             challenger.observe_elements(inputs);
@@ -1,11 +1,17 @@
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Write};
+
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
-use crate::plonk_challenger::Challenger;
+use crate::halo::OpeningProof;
+use crate::plonk_challenger::{Challenger, RescueHash, SpongeHash};
 use crate::plonk_util::{halo_g, halo_n, halo_s};
-use crate::{AffinePoint, AffinePointTarget, Curve, Field, HaloCurve, PartialWitness, Target, SECURITY_BITS};
+use crate::{AffinePoint, AffinePointTarget, Curve, Field, HaloCurve, FromBytes, PartialWitness, Target, ToBytes, VerifyError, SECURITY_BITS};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+// The fields are serializable for any curve, so we don't need serde's default `C: Serialize` bound.
+#[serde(bound = "")]
 pub struct SchnorrProof<C: HaloCurve> {
     pub r: AffinePoint<C>,
     pub z1: C::ScalarField,
@@ -18,8 +24,14 @@ pub struct SchnorrProofTarget<C: Curve> {
     pub z2: Target<C::ScalarField>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+/// A proof. Equality and hashing are based on the canonical serialization returned by `to_bytes`,
+/// so proofs can be deduplicated by keying them in a `HashSet`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct Proof<C: HaloCurve> {
+    /// The digest of the circuit this proof is for, as in `VerificationKey::verifier_key_hash`.
+    /// The recursive verifier doesn't check it, since its circuit is fixed by its key anyway.
+    pub vk_hash: C::BaseField,
     /// A commitment to each wire polynomial.
     pub c_wires: Vec<AffinePoint<C>>,
     /// A commitment to Z, in the context of the permutation argument.
@@ -46,7 +58,59 @@ pub struct Proof<C: HaloCurve> {
     pub schnorr_proof: SchnorrProof<C>,
 }
 
+impl<C: HaloCurve> PartialEq for Proof<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl<C: HaloCurve> Eq for Proof<C> {}
+
+impl<C: HaloCurve> Hash for Proof<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state);
+    }
+}
+
 impl<C: HaloCurve> Proof<C> {
+    /// Returns the canonical serialization of this proof, in which points are compressed and field
+    /// elements are in canonical form. Two proofs have the same serialization iff they are equal.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_cbor::to_vec(self).expect("Serializing a proof to a vector should not fail")
+    }
+
+    /// Reads a proof in the format written by its `ToBytes` implementation. Each point is checked
+    /// for subgroup membership as soon as it is read, so that a malformed proof is rejected
+    /// without reading the rest of it. Note that this does not check that the proof's dimensions
+    /// match any particular circuit; verification does that.
+    pub fn read_from<R: Read>(reader: R) -> std::result::Result<Self, VerifyError> {
+        ProofReader { reader }.read_proof()
+    }
+
+    /// Returns the IPA opening proof contained in this proof.
+    pub fn opening_proof(&self) -> OpeningProof<C> {
+        OpeningProof {
+            halo_l: self.halo_l.clone(),
+            halo_r: self.halo_r.clone(),
+            halo_g: self.halo_g,
+            schnorr_proof: self.schnorr_proof,
+        }
+    }
+
+    /// Returns every point in this proof, in the order they are serialized.
+    pub fn all_points(&self) -> Vec<AffinePoint<C>> {
+        [
+            self.c_wires.as_slice(),
+            &[self.c_plonk_z],
+            &self.c_plonk_t,
+            &[self.c_pis_quotient],
+            &self.halo_l,
+            &self.halo_r,
+            &[self.halo_g, self.schnorr_proof.r],
+        ]
+        .concat()
+    }
+
     pub fn all_opening_sets(&self) -> Vec<OpeningSet<C::ScalarField>> {
         vec![
             self.o_local.clone(),
@@ -61,7 +125,16 @@ impl<C: HaloCurve> Proof<C> {
         public_inputs: &[C::ScalarField],
         old_proofs: &[OldProof<C>],
     ) -> Result<ProofChallenge<C>> {
-        let mut challenger = Challenger::new(SECURITY_BITS);
+        self.get_challenges_with_hash::<RescueHash<C::BaseField>>(public_inputs, old_proofs)
+    }
+
+    // Computes all challenges used in the proof verification, hashing the transcript with `H`.
+    pub fn get_challenges_with_hash<H: SpongeHash<C::BaseField>>(
+        &self,
+        public_inputs: &[C::ScalarField],
+        old_proofs: &[OldProof<C>],
+    ) -> Result<ProofChallenge<C>> {
+        let mut challenger = Challenger::<C::BaseField, H>::with_hash(SECURITY_BITS);
         let error_msg = "Conversion from base to scalar field failed.";
         challenger.observe_affine_points(&self.c_wires);
         let (beta_bf, gamma_bf) = challenger.get_2_challenges();
@@ -125,6 +198,119 @@ impl<C: HaloCurve> Proof<C> {
     }
 }
 
+/// Writes a proof in a simple binary format which can be read incrementally by
+/// `verify_from_reader`. Fields are written in the order they are declared, with each point
+/// compressed and each field element in canonical form, as in their `ToBytes` implementations.
+/// Each vector is preceded by its length, as a little-endian `u32`.
+impl<C: HaloCurve> ToBytes for Proof<C> {
+    fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.vk_hash.write(&mut writer)?;
+        write_slice(&mut writer, &self.c_wires)?;
+        self.c_plonk_z.write(&mut writer)?;
+        write_slice(&mut writer, &self.c_plonk_t)?;
+        self.c_pis_quotient.write(&mut writer)?;
+        for opening_set in &[&self.o_local, &self.o_right, &self.o_below] {
+            opening_set.write(&mut writer)?;
+        }
+        write_slice(&mut writer, &self.halo_l)?;
+        write_slice(&mut writer, &self.halo_r)?;
+        self.halo_g.write(&mut writer)?;
+        self.schnorr_proof.r.write(&mut writer)?;
+        self.schnorr_proof.z1.write(&mut writer)?;
+        self.schnorr_proof.z2.write(&mut writer)
+    }
+}
+
+impl<F: Field> ToBytes for OpeningSet<F> {
+    fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write_slice(&mut writer, &self.o_constants)?;
+        write_slice(&mut writer, &self.o_plonk_sigmas)?;
+        write_slice(&mut writer, &self.o_wires)?;
+        self.o_plonk_z.write(&mut writer)?;
+        write_slice(&mut writer, &self.o_plonk_t)?;
+        write_slice(&mut writer, &self.o_old_proofs)?;
+        self.o_pi_quotient.write(&mut writer)
+    }
+}
+
+struct ProofReader<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> ProofReader<R> {
+    fn read_proof<C: HaloCurve>(&mut self) -> std::result::Result<Proof<C>, VerifyError> {
+        Ok(Proof {
+            vk_hash: self.read_element()?,
+            c_wires: self.read_points()?,
+            c_plonk_z: self.read_point()?,
+            c_plonk_t: self.read_points()?,
+            c_pis_quotient: self.read_point()?,
+            o_local: self.read_opening_set()?,
+            o_right: self.read_opening_set()?,
+            o_below: self.read_opening_set()?,
+            halo_l: self.read_points()?,
+            halo_r: self.read_points()?,
+            halo_g: self.read_point()?,
+            schnorr_proof: SchnorrProof {
+                r: self.read_point()?,
+                z1: self.read_element()?,
+                z2: self.read_element()?,
+            },
+        })
+    }
+
+    fn read_opening_set<F: Field>(&mut self) -> std::result::Result<OpeningSet<F>, VerifyError> {
+        Ok(OpeningSet {
+            o_constants: self.read_elements()?,
+            o_plonk_sigmas: self.read_elements()?,
+            o_wires: self.read_elements()?,
+            o_plonk_z: self.read_element()?,
+            o_plonk_t: self.read_elements()?,
+            o_old_proofs: self.read_elements()?,
+            o_pi_quotient: self.read_element()?,
+        })
+    }
+
+    fn read_len(&mut self) -> std::result::Result<usize, VerifyError> {
+        let mut buf = [0u8; 4];
+        self.reader.read_exact(&mut buf)?;
+        Ok(u32::from_le_bytes(buf) as usize)
+    }
+
+    fn read_element<F: Field>(&mut self) -> std::result::Result<F, VerifyError> {
+        Ok(F::read(&mut self.reader)?)
+    }
+
+    // We don't preallocate based on the encoded lengths, since a malicious proof could claim an
+    // enormous length. Reading stops at the end of the input either way.
+    fn read_elements<F: Field>(&mut self) -> std::result::Result<Vec<F>, VerifyError> {
+        let len = self.read_len()?;
+        (0..len).map(|_| self.read_element()).collect()
+    }
+
+    fn read_point<C: HaloCurve>(&mut self) -> std::result::Result<AffinePoint<C>, VerifyError> {
+        let point = AffinePoint::<C>::read(&mut self.reader)?;
+        if point.is_in_subgroup() {
+            Ok(point)
+        } else {
+            Err(VerifyError::PointNotInSubgroup)
+        }
+    }
+
+    fn read_points<C: HaloCurve>(&mut self) -> std::result::Result<Vec<AffinePoint<C>>, VerifyError> {
+        let len = self.read_len()?;
+        (0..len).map(|_| self.read_point()).collect()
+    }
+}
+
+fn write_slice<T: ToBytes, W: Write>(mut writer: W, items: &[T]) -> io::Result<()> {
+    writer.write_all(&(items.len() as u32).to_le_bytes())?;
+    for item in items {
+        item.write(&mut writer)?;
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub struct ProofChallenge<C: Curve> {
     pub beta: C::ScalarField,
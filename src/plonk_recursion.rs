@@ -298,7 +298,7 @@ fn verify_all_ipas<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>(
     let mut points: Vec<Target<C::ScalarField>> = (0..2 * num_public_input_gates)
         .scan(C::ScalarField::ONE, |acc, _| {
             let tmp = *acc;
-            *acc = *acc * generator_n_value;
+            *acc *= generator_n_value;
             Some(tmp)
         })
         .step_by(2)
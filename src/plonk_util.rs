@@ -1,6 +1,7 @@
 use crate::partition::get_subgroup_shift;
 use crate::witness::Witness;
 use crate::{ifft_with_precomputation_power_of_2, msm_execute_parallel, AffinePoint, CircuitBuilder, Curve, FftPrecomputation, Field, HaloCurve, MsmPrecomputation, Polynomial, PolynomialCommitment, ProjectivePoint, Target, NUM_ROUTED_WIRES};
+use rand::{thread_rng, Rng};
 use rayon::prelude::*;
 
 /// Evaluate the polynomial which vanishes on any multiplicative subgroup of a given order `n`.
@@ -113,23 +114,15 @@ pub fn eval_poly<F: Field>(coeffs: &[F], x: F) -> F {
     let mut ans = F::ZERO;
     let mut x_pow = F::ONE;
     for &c in coeffs {
-        ans = ans + (c * x_pow);
-        x_pow = x_pow * x;
+        ans += c * x_pow;
+        x_pow *= x;
     }
     ans
 }
 
 /// Compute `[x^0, x^1, ..., x^(n - 1)]`.
 pub fn powers<F: Field>(x: F, n: usize) -> Vec<F> {
-    let mut powers = Vec::new();
-    let mut current = F::ONE;
-    for i in 0..n {
-        if i != 0 {
-            current = current * x;
-        }
-        powers.push(current);
-    }
-    powers
+    F::powers_of(x, n)
 }
 
 /// Compute `[x^0, x^1, ..., x^(n - 1)]`.
@@ -218,7 +211,23 @@ pub fn commit_polynomials<C: Curve>(
     blinding_point: AffinePoint<C>,
     blinding: bool,
 ) -> Vec<PolynomialCommitment<C>> {
-    PolynomialCommitment::coeffs_vec_to_commitments(
+    commit_polynomials_with_rng(
+        polynomials,
+        msm_precomputation,
+        blinding_point,
+        blinding,
+        &mut thread_rng(),
+    )
+}
+
+pub fn commit_polynomials_with_rng<C: Curve, R: Rng>(
+    polynomials: &[Polynomial<C::ScalarField>],
+    msm_precomputation: &MsmPrecomputation<C>,
+    blinding_point: AffinePoint<C>,
+    blinding: bool,
+    rng: &mut R,
+) -> Vec<PolynomialCommitment<C>> {
+    PolynomialCommitment::coeffs_vec_to_commitments_with_rng(
         polynomials
             .iter()
             .map(|p| p.coeffs())
@@ -227,6 +236,7 @@ pub fn commit_polynomials<C: Curve>(
         msm_precomputation,
         blinding_point,
         blinding,
+        rng,
     )
 }
 
@@ -252,8 +262,8 @@ pub fn permutation_polynomial<F: Field>(
             let k_i = k_is[j];
             let s_id = k_i * x;
             let s_sigma = sigma_values[j][8 * (i - 1)];
-            numerator = numerator * (wire_value + beta * s_id + gamma);
-            denominator = denominator * (wire_value + beta * s_sigma + gamma);
+            numerator *= wire_value + beta * s_id + gamma;
+            denominator *= wire_value + beta * s_sigma + gamma;
         }
         let last = *plonk_z_points.last().unwrap();
         plonk_z_points.push(last * numerator / denominator);
@@ -285,7 +295,7 @@ pub(crate) fn scale_polynomials<F: Field>(
     alpha: F,
     degree: usize,
 ) -> Polynomial<F> {
-    let alpha_powers = powers(alpha, polynomials.len());
+    let alpha_powers = F::powers_of(alpha, polynomials.len());
     Polynomial::from(
         (0..degree)
             .map(|i| {
@@ -316,9 +326,9 @@ pub fn halo_s<F: Field>(us: &[F]) -> Vec<F> {
     for (j, (&u, &u_inv)) in us.iter().rev().zip(us_inv.iter().rev()).enumerate() {
         for (i, x) in res.iter_mut().enumerate() {
             if i & (1 << j) == 0 {
-                *x = *x * u_inv;
+                *x *= u_inv;
             } else {
-                *x = *x * u;
+                *x *= u;
             }
         }
     }
@@ -332,7 +342,7 @@ pub fn halo_g<F: Field>(x: F, us: &[F]) -> F {
     for &u_i in us.iter().rev() {
         let u_i_inv = u_i.multiplicative_inverse_assuming_nonzero();
         let term = u_i * x_power + u_i_inv;
-        product = product * term;
+        product *= term;
         x_power = x_power.square();
     }
     product
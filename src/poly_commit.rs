@@ -1,6 +1,7 @@
 use crate::plonk_util::pedersen_hash;
 use crate::MsmPrecomputation;
 use crate::{AffinePoint, Curve, Field, ProjectivePoint};
+use rand::{thread_rng, Rng};
 
 #[derive(Debug, Copy, Clone)]
 /// Represents a curve point either in affine or projective coordinates.
@@ -34,9 +35,26 @@ impl<C: Curve> PolynomialCommitment<C> {
         msm_precomputation: &MsmPrecomputation<C>,
         blinding_point: AffinePoint<C>,
         blinding: bool,
+    ) -> Self {
+        Self::coeffs_to_commitment_with_rng(
+            coeffs,
+            msm_precomputation,
+            blinding_point,
+            blinding,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Like `coeffs_to_commitment`, but draws the blinding factor from the given RNG.
+    pub fn coeffs_to_commitment_with_rng<R: Rng>(
+        coeffs: &[C::ScalarField],
+        msm_precomputation: &MsmPrecomputation<C>,
+        blinding_point: AffinePoint<C>,
+        blinding: bool,
+        rng: &mut R,
     ) -> Self {
         let blinding_factor = if blinding {
-            C::ScalarField::rand()
+            C::ScalarField::rand_from_rng(rng)
         } else {
             C::ScalarField::ZERO
         };
@@ -54,11 +72,34 @@ impl<C: Curve> PolynomialCommitment<C> {
         msm_precomputation: &MsmPrecomputation<C>,
         blinding_point: AffinePoint<C>,
         blinding: bool,
+    ) -> Vec<Self> {
+        Self::coeffs_vec_to_commitments_with_rng(
+            coefficients_vec,
+            msm_precomputation,
+            blinding_point,
+            blinding,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Like `coeffs_vec_to_commitments`, but draws the blinding factors from the given RNG.
+    pub fn coeffs_vec_to_commitments_with_rng<R: Rng>(
+        coefficients_vec: &[&[C::ScalarField]],
+        msm_precomputation: &MsmPrecomputation<C>,
+        blinding_point: AffinePoint<C>,
+        blinding: bool,
+        rng: &mut R,
     ) -> Vec<Self> {
         let mut comms: Vec<_> = coefficients_vec
             .iter()
             .map(|coeffs| {
-                Self::coeffs_to_commitment(coeffs, &msm_precomputation, blinding_point, blinding)
+                Self::coeffs_to_commitment_with_rng(
+                    coeffs,
+                    &msm_precomputation,
+                    blinding_point,
+                    blinding,
+                    rng,
+                )
             })
             .collect();
         Self::batch_to_affine(&mut comms);
@@ -1,5 +1,6 @@
 #![allow(clippy::many_single_char_names)]
 use crate::{fft_precompute, fft_with_precomputation, ifft_with_precomputation_power_of_2, util::log2_ceil, AffinePoint, Curve, FftPrecomputation, Field, MsmPrecomputation, PolynomialCommitment};
+use rand::Rng;
 use std::cmp::Ordering;
 use std::ops::{Index, IndexMut, RangeBounds};
 use std::slice::{Iter, IterMut, SliceIndex};
@@ -249,8 +250,7 @@ impl<F: Field> Polynomial<F> {
                 quotient[cur_q_degree] = cur_q_coeff;
 
                 for (i, &div_coeff) in b.iter().enumerate() {
-                    remainder[cur_q_degree + i] =
-                        remainder[cur_q_degree + i] - (cur_q_coeff * div_coeff);
+                    remainder[cur_q_degree + i] -= cur_q_coeff * div_coeff;
                 }
                 remainder.trim();
             }
@@ -337,7 +337,7 @@ impl<F: Field> Polynomial<F> {
         let mut g_pow = F::ONE;
         // Multiply the i-th coefficient of `a` by `g^i`. Then `new_a(w^j) = old_a(g.w^j)`.
         a_trim.iter_mut().for_each(|x| {
-            *x = (*x) * g_pow;
+            *x *= g_pow;
             g_pow = g * g_pow;
         });
         let d = a_trim.degree();
@@ -352,7 +352,7 @@ impl<F: Field> Polynomial<F> {
         let denominators = (0..a_eval.len())
             .map(|i| {
                 if i != 0 {
-                    root_pow = root_pow * root_n;
+                    root_pow *= root_n;
                 }
                 denominator_g * root_pow - F::ONE
             })
@@ -364,7 +364,7 @@ impl<F: Field> Polynomial<F> {
             .iter_mut()
             .zip(denominators_inv.iter())
             .for_each(|(x, &d)| {
-                *x = (*x) * d;
+                *x *= d;
             });
         // `p` is the interpolating polynomial of `a_eval` on `{w^i}`.
         let mut p = Self::from_evaluations(&a_eval, &precomputation);
@@ -373,8 +373,8 @@ impl<F: Field> Polynomial<F> {
         let g_inv = g.multiplicative_inverse_assuming_nonzero();
         let mut g_inv_pow = F::ONE;
         p.iter_mut().for_each(|x| {
-            *x = (*x) * g_inv_pow;
-            g_inv_pow = g_inv_pow * g_inv;
+            *x *= g_inv_pow;
+            g_inv_pow *= g_inv;
         });
         p
     }
@@ -393,6 +393,23 @@ impl<F: Field> Polynomial<F> {
             blinding,
         )
     }
+
+    /// Commits the polynomial, drawing any blinding factor from the given RNG.
+    pub fn commit_with_rng<C: Curve<ScalarField = F>, R: Rng>(
+        &self,
+        msm_precomputation: &MsmPrecomputation<C>,
+        blinding_point: AffinePoint<C>,
+        blinding: bool,
+        rng: &mut R,
+    ) -> PolynomialCommitment<C> {
+        PolynomialCommitment::coeffs_to_commitment_with_rng(
+            self.coeffs(),
+            msm_precomputation,
+            blinding_point,
+            blinding,
+            rng,
+        )
+    }
 }
 
 #[cfg(test)]
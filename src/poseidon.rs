@@ -0,0 +1,102 @@
+use crate::type_keyed_cache::get_or_generate;
+use crate::{apply_mds, Field};
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::sync::Arc;
+
+/// The number of full rounds, half of which are applied before the partial rounds and half after.
+pub const POSEIDON_FULL_ROUNDS: usize = 8;
+
+/// The number of partial rounds, in which the S-box is only applied to the first element. This is
+/// the count recommended in the Poseidon paper for a width-4 permutation with `x^5` S-boxes at
+/// 128-bit security.
+pub const POSEIDON_PARTIAL_ROUNDS: usize = 56;
+
+/// Applies the Poseidon permutation to the given state. Each round adds its round constants,
+/// applies the `x^ALPHA` S-box to either every element (in full rounds) or only the first (in
+/// partial rounds), then multiplies by the same MDS matrix as Rescue.
+///
+/// The round constants and MDS matrix are our own, rather than the reference Poseidon parameters;
+/// see `generate_poseidon_constants`.
+pub fn poseidon_permutation<F: Field>(state: &[F]) -> Vec<F> {
+    let mut state = state.to_vec();
+    let width = state.len();
+    let half_full_rounds = POSEIDON_FULL_ROUNDS / 2;
+    let constants = poseidon_constants::<F>(width);
+
+    for (r, round_constants) in constants.iter().enumerate() {
+        for (x, &c) in state.iter_mut().zip(round_constants) {
+            *x += c;
+        }
+
+        let full_round = r < half_full_rounds || r >= half_full_rounds + POSEIDON_PARTIAL_ROUNDS;
+        if full_round {
+            state = state.iter().map(|x| x.exp(F::ALPHA)).collect();
+        } else {
+            state[0] = state[0].exp(F::ALPHA);
+        }
+
+        state = apply_mds(state);
+    }
+
+    state
+}
+
+/// Returns the round constants of each round for the given width, which are generated once and
+/// then cached.
+fn poseidon_constants<F: Field>(width: usize) -> Arc<Vec<Vec<F>>> {
+    get_or_generate("poseidon_constants", width, || generate_poseidon_constants::<F>(width))
+}
+
+/// Generates the round constants of each round, in the same deterministic manner as the Rescue
+/// constants, but with a different seed.
+///
+/// These are not the constants of the Poseidon paper's reference implementation, which generates
+/// them with a Grain LFSR, so our permutation is not interoperable with other Poseidon
+/// implementations, even over the same field and with the same round counts.
+fn generate_poseidon_constants<F: Field>(width: usize) -> Vec<Vec<F>> {
+    let mut rng = ChaCha8Rng::seed_from_u64(0x706f736569646f6e);
+    (0..POSEIDON_FULL_ROUNDS + POSEIDON_PARTIAL_ROUNDS)
+        .map(|_| (0..width).map(|_| F::rand_from_rng(&mut rng)).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::poseidon::{generate_poseidon_constants, poseidon_constants};
+    use crate::{poseidon_permutation, rescue_permutation, Field, TweedledumBase, RESCUE_SPONGE_WIDTH};
+
+    #[test]
+    fn test_poseidon_constants_cached() {
+        type F = TweedledumBase;
+
+        let constants = poseidon_constants::<F>(RESCUE_SPONGE_WIDTH);
+        assert_eq!(*constants, generate_poseidon_constants::<F>(RESCUE_SPONGE_WIDTH));
+        assert!(Arc::ptr_eq(&constants, &poseidon_constants::<F>(RESCUE_SPONGE_WIDTH)));
+        assert_eq!(poseidon_constants::<F>(3)[0].len(), 3);
+    }
+
+    #[test]
+    fn test_poseidon_permutation() {
+        type F = TweedledumBase;
+
+        let input: Vec<F> = (0..RESCUE_SPONGE_WIDTH).map(F::from_canonical_usize).collect();
+        let output = poseidon_permutation(&input);
+        assert_eq!(output.len(), RESCUE_SPONGE_WIDTH);
+        assert_eq!(output, poseidon_permutation(&input));
+        assert_ne!(output, input);
+        assert_ne!(output, rescue_permutation(&input, 128));
+
+        // Changing any element of the input changes every element of the output.
+        for i in 0..RESCUE_SPONGE_WIDTH {
+            let mut modified = input.clone();
+            modified[i] = modified[i] + F::ONE;
+            let modified_output = poseidon_permutation(&modified);
+            for j in 0..RESCUE_SPONGE_WIDTH {
+                assert_ne!(modified_output[j], output[j]);
+            }
+        }
+    }
+}
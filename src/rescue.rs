@@ -1,5 +1,5 @@
 use crate::util::ceil_div_usize;
-use crate::{apply_mds, Field, PRF};
+use crate::{apply_mds, Field, PRF, RESCUE_SPONGE_RATE, RESCUE_SPONGE_WIDTH};
 use rand_chacha::rand_core::SeedableRng;
 use rand_chacha::ChaCha8Rng;
 
@@ -37,40 +37,77 @@ pub fn rescue_hash_n_to_3<F: Field>(inputs: Vec<F>, security_bits: usize) -> (F,
     (outputs[0], outputs[1], outputs[2])
 }
 
+pub fn rescue_hash_n_to_1_with_domain<F: Field>(
+    inputs: Vec<F>,
+    domain: u64,
+    security_bits: usize,
+) -> F {
+    rescue_sponge_with_domain(inputs, 1, domain, security_bits)[0]
+}
+
 pub fn rescue_sponge<F: Field>(inputs: Vec<F>, num_outputs: usize, security_bits: usize) -> Vec<F> {
-    // This is mostly arbitrary, but we wouldn't want a huge width as the MDS layer could get
-    // expensive.
-    let rate = 3;
-    let capacity = 1;
-    let width = rate + capacity;
+    rescue_sponge_with_domain(inputs, num_outputs, 0, security_bits)
+}
+
+/// Like `rescue_sponge`, but with the capacity element initialized to the given domain tag, so
+/// that hashes computed for different purposes cannot collide. Domain 0 is the same as
+/// `rescue_sponge`.
+pub fn rescue_sponge_with_domain<F: Field>(
+    inputs: Vec<F>,
+    num_outputs: usize,
+    domain: u64,
+    security_bits: usize,
+) -> Vec<F> {
+    let rounds = recommended_rounds::<F>(RESCUE_SPONGE_WIDTH, security_bits);
+    rescue_sponge_with_domain_rounds(inputs, num_outputs, domain, rounds)
+}
 
-    let mut state = vec![F::ZERO; width];
+/// Like `rescue_sponge`, but with an explicit number of rounds per permutation, rather than the
+/// number recommended for a given security level. Fewer rounds than `recommended_rounds` give a
+/// weaker hash, so this is mainly useful for testing.
+pub fn rescue_sponge_rounds<F: Field>(inputs: Vec<F>, num_outputs: usize, rounds: usize) -> Vec<F> {
+    rescue_sponge_with_domain_rounds(inputs, num_outputs, 0, rounds)
+}
+
+pub fn rescue_sponge_with_domain_rounds<F: Field>(
+    inputs: Vec<F>,
+    num_outputs: usize,
+    domain: u64,
+    rounds: usize,
+) -> Vec<F> {
+    let mut state = vec![F::ZERO; RESCUE_SPONGE_WIDTH];
+    state[RESCUE_SPONGE_RATE] = F::from_canonical_u64(domain);
 
     // Absorb all input chunks.
-    for input_chunk in inputs.chunks(rate) {
+    for input_chunk in inputs.chunks(RESCUE_SPONGE_RATE) {
         for i in 0..input_chunk.len() {
-            state[i] = state[i] + input_chunk[i];
+            state[i] += input_chunk[i];
         }
-        state = rescue_permutation(&state, security_bits);
+        state = rescue_permutation_rounds(&state, rounds);
     }
 
     // Squeeze until we have the desired number of outputs.
     let mut outputs = Vec::new();
     loop {
-        for i in 0..rate {
+        for i in 0..RESCUE_SPONGE_RATE {
             outputs.push(state[i]);
             if outputs.len() == num_outputs {
                 return outputs;
             }
         }
-        state = rescue_permutation(&state, security_bits);
+        state = rescue_permutation_rounds(&state, rounds);
     }
 }
 
 pub fn rescue_permutation<F: Field>(state: &[F], security_bits: usize) -> Vec<F> {
+    rescue_permutation_rounds(state, recommended_rounds::<F>(state.len(), security_bits))
+}
+
+/// Like `rescue_permutation`, but with an explicit number of rounds.
+pub fn rescue_permutation_rounds<F: Field>(state: &[F], rounds: usize) -> Vec<F> {
     let mut state = state.to_vec();
     let width = state.len();
-    let constants = generate_rescue_constants(width, security_bits);
+    let constants = generate_rescue_constants_rounds(width, rounds);
 
     for (step_a_constants, step_b_constants) in constants {
         // Step A.
@@ -94,9 +131,12 @@ fn add_vecs<F: Field>(a: Vec<F>, b: Vec<F>) -> Vec<F> {
         .collect()
 }
 
-pub(crate) fn generate_rescue_constants<F: Field>(
+/// Generates the round constants for the given number of rounds. The constants of each round do
+/// not depend on the total number of rounds, so a permutation with fewer rounds uses a prefix of
+/// the constants of a permutation with more rounds.
+pub(crate) fn generate_rescue_constants_rounds<F: Field>(
     width: usize,
-    security_bits: usize,
+    rounds: usize,
 ) -> Vec<(Vec<F>, Vec<F>)> {
     // TODO: This should use deterministic randomness.
     // FIX: Use ChaCha CSPRNG with a seed. This is somewhat similar to official implementation
@@ -104,7 +144,7 @@ pub(crate) fn generate_rescue_constants<F: Field>(
     // use SHAKE256 with a seed to generate randomness.
     let mut rng = ChaCha8Rng::seed_from_u64(1337);
     let mut constants = Vec::new();
-    for _i in 0..recommended_rounds::<F>(width, security_bits) {
+    for _i in 0..rounds {
         let mut step_a_constants = Vec::new();
         for _k in 0..width {
             step_a_constants.push(F::rand_from_rng(&mut rng));
@@ -120,6 +160,64 @@ pub(crate) fn generate_rescue_constants<F: Field>(
     constants
 }
 
-pub(crate) fn recommended_rounds<F: Field>(width: usize, security_bits: usize) -> usize {
+/// Returns the number of rounds recommended for a Rescue permutation of the given width to achieve
+/// the given security level.
+pub fn recommended_rounds<F: Field>(width: usize, security_bits: usize) -> usize {
     ceil_div_usize(security_bits, 2 * width).max(10)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_satisfying_witness, recommended_rounds, rescue_permutation, CircuitBuilder, Curve, Field, HaloCurve, PartialWitness, Tweedledee, Tweedledum, RESCUE_SPONGE_WIDTH};
+
+    /// Checks the permutation of `[0, 1, ..., RESCUE_SPONGE_WIDTH - 1]` at 128-bit security
+    /// against fixed outputs, so that any change to the round count, round constants, MDS matrix
+    /// or S-box is caught. The circuit version of the permutation in `C::ScalarField` is checked
+    /// against the same outputs.
+    fn check_test_vector<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>(expected: [&str; RESCUE_SPONGE_WIDTH]) {
+        type F<C> = <C as Curve>::ScalarField;
+        let rounds = recommended_rounds::<F<C>>(RESCUE_SPONGE_WIDTH, 128);
+        assert_eq!(rounds, 16);
+
+        let input: Vec<F<C>> = (0..RESCUE_SPONGE_WIDTH).map(F::<C>::from_canonical_usize).collect();
+        let expected: Vec<F<C>> = expected
+            .iter()
+            .map(|s| F::<C>::from_str_radix(s, 10).unwrap())
+            .collect();
+        assert_eq!(rescue_permutation(&input, 128), expected);
+
+        // Each round uses a RescueStepAGate and a RescueStepBGate, and a BufferGate receives the
+        // outputs.
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let input_targets = builder.add_virtual_targets(RESCUE_SPONGE_WIDTH);
+        let gates_before = builder.num_gates();
+        let output_targets = builder.rescue_permutation(&input_targets);
+        assert_eq!(builder.num_gates() - gates_before, 2 * rounds + 1);
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_targets(&input_targets, &input);
+        let witness = generate_satisfying_witness::<C, InnerC>(&circuit, partial_witness);
+        assert_eq!(witness.get_targets(&output_targets), expected);
+    }
+
+    #[test]
+    fn test_vector_tweedledum_base() {
+        check_test_vector::<Tweedledee, Tweedledum>([
+            "16697153536188129052437639043081344958382235728948206386921054705942460885208",
+            "28071526790064966822342191429927239414017278977595009441846366982255769242645",
+            "7182463645005844439267017678495949807098332832002225389294171186120424335561",
+            "64831544155603838383333563794304417531297734568463678543224337518281308589",
+        ]);
+    }
+
+    #[test]
+    fn test_vector_tweedledee_base() {
+        check_test_vector::<Tweedledum, Tweedledee>([
+            "14405002185764116640416337050200390787690647855004166913888025521497965639198",
+            "27440665450034806716241856544026261194759085560719593244950133447256423965465",
+            "14883837436801547823504941697559234344866450778124275924599377243064425989070",
+            "24880815892206599405244097941080017227484237429755533838978169272097433003358",
+        ]);
+    }
+}
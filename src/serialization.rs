@@ -48,15 +48,23 @@ impl<C: Curve> FromBytes for AffinePoint<C> {
         let mut mask = vec![0u8];
         reader.read_exact(&mut mask)?;
         let mask = mask[0];
+        // Only the canonical encoding is accepted, so that each point has a single encoding.
+        if mask & !3 != 0 {
+            return Err(Error::new(ErrorKind::Other, "Invalid point mask"));
+        }
+        let mut buf = vec![0u8; C::BaseField::BYTES];
+        reader.read_exact(&mut buf)?;
         if mask & 1 == 1 {
+            // The point at infinity is written with an even y and a zero x coordinate.
+            if mask != 1 || buf.iter().any(|&b| b != 0) {
+                return Err(Error::new(ErrorKind::Other, "Invalid encoding of the point at infinity"));
+            }
             return Ok(AffinePoint {
                 x: C::BaseField::ZERO,
                 y: C::BaseField::ZERO,
                 zero: true,
             });
         }
-        let mut buf = vec![0u8; C::BaseField::BYTES];
-        reader.read_exact(&mut buf)?;
         let x = C::BaseField::from_canonical_u8_vec(buf.to_vec())
             .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
         let square_candidate = x.cube() + C::A * x + C::B;
@@ -154,9 +162,14 @@ impl_serde_field!(Bls12377Scalar);
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashSet;
+
     use super::*;
-    use crate::{blake_hash_base_field_to_curve, Bls12377, Bls12377Base, Bls12377Scalar, CircuitBuilder, HaloCurve, PartialWitness, Proof, Tweedledee, TweedledeeBase, Tweedledum, TweedledumBase, VerificationKey};
+    use crate::halo::OpeningProof;
+    use crate::{blake_hash_base_field_to_curve, verify_from_reader, verify_proof, Bls12377, Bls12377Base, Bls12377Scalar, CircuitBuilder, HaloCurve, PartialWitness, Proof, Tweedledee, TweedledeeBase, Tweedledum, TweedledumBase, VerificationKey, VerifyError};
     use anyhow::Result;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
 
     macro_rules! test_field_serialization {
         ($field:ty, $test_name:ident) => {
@@ -200,6 +213,16 @@ mod test {
                 let q = AffinePoint::<$curve>::read(&buf[..])?;
                 assert_eq!(zero, q);
 
+                // Non-canonical encodings are rejected.
+                let mut buf = [0u8; <$basefield>::BYTES + 1];
+                p.write(&mut buf[..])?;
+                buf[0] |= 4;
+                assert!(AffinePoint::<$curve>::read(&buf[..]).is_err());
+                let mut buf = [0u8; <$basefield>::BYTES + 1];
+                zero.write(&mut buf[..])?;
+                buf[1] = 1;
+                assert!(AffinePoint::<$curve>::read(&buf[..]).is_err());
+
                 // Serde (de)serialization
                 let ser = serde_cbor::to_vec(&p)?;
                 let q = serde_cbor::from_slice(&ser)?;
@@ -307,4 +330,165 @@ mod test {
 
     test_proof_vk_serialization!(Tweedledee, Tweedledum, test_proof_vk_serialization_tweedledee);
     test_proof_vk_serialization!(Tweedledum, Tweedledee, test_proof_vk_serialization_tweedledum);
+
+    #[test]
+    fn test_proof_dedup() -> Result<()> {
+        type C = Tweedledee;
+        let (proof, _vk) = get_circuit_vk::<C, Tweedledum>();
+        let deserialized: Proof<C> = serde_cbor::from_slice(&proof.to_bytes())?;
+        assert_eq!(deserialized.to_bytes(), proof.to_bytes());
+
+        let mut tampered = proof.clone();
+        tampered.o_local.o_plonk_z = tampered.o_local.o_plonk_z + <C as Curve>::ScalarField::ONE;
+        assert_ne!(tampered, proof);
+
+        let mut proofs = HashSet::new();
+        assert!(proofs.insert(proof.clone()));
+        assert!(!proofs.insert(proof));
+        assert!(!proofs.insert(deserialized));
+        assert!(proofs.insert(tampered));
+        assert_eq!(proofs.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_from_reader() -> Result<()> {
+        type C = Tweedledee;
+        type InnerC = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+        let (proof, vk) = get_circuit_vk::<C, InnerC>();
+        let public_inputs: Vec<SF> = (1..=10).map(SF::from_canonical_usize).collect();
+
+        let mut bytes = Vec::new();
+        proof.write(&mut bytes)?;
+        assert!(verify_proof::<C, InnerC>(&public_inputs, &proof, &[], &vk, true).is_ok());
+        assert!(verify_from_reader::<C, InnerC, _>(&bytes[..], &public_inputs, &vk).unwrap());
+
+        let mut wrong_public_inputs = public_inputs.clone();
+        wrong_public_inputs[0] = SF::TWO;
+        assert!(!verify_from_reader::<C, InnerC, _>(&bytes[..], &wrong_public_inputs, &vk).unwrap());
+
+        // A truncated proof fails to be read, rather than being rejected as invalid.
+        match verify_from_reader::<C, InnerC, _>(&bytes[..bytes.len() - 1], &public_inputs, &vk) {
+            Err(VerifyError::Io(e)) => assert_eq!(e.kind(), ErrorKind::UnexpectedEof),
+            other => panic!("Expected an I/O error, got {:?}", other),
+        }
+
+        // Corrupting the verifier key digest, which comes first, makes the proof invalid.
+        for delta in 1..=16 {
+            let mut corrupted = bytes.clone();
+            corrupted[0] = corrupted[0].wrapping_add(delta);
+            let result = verify_from_reader::<C, InnerC, _>(&corrupted[..], &public_inputs, &vk);
+            assert!(matches!(result, Ok(false)));
+        }
+
+        // Corrupting the x coordinate of the first wire commitment, which follows the digest, the
+        // length of the wire commitments and the point's mask byte, either produces an invalid
+        // encoding, or another point which makes the proof invalid.
+        let offset = <<C as Curve>::BaseField as Field>::BYTES + 4 + 1;
+        for delta in 1..=16 {
+            let mut corrupted = bytes.clone();
+            corrupted[offset] = corrupted[offset].wrapping_add(delta);
+            if let Ok(corrupted_proof) = Proof::<C>::read_from(&corrupted[..]) {
+                assert_eq!(corrupted_proof.vk_hash, proof.vk_hash);
+                assert_ne!(corrupted_proof.c_wires[0], proof.c_wires[0]);
+            }
+            let result = verify_from_reader::<C, InnerC, _>(&corrupted[..], &public_inputs, &vk);
+            assert!(!matches!(result, Ok(true)));
+        }
+
+        Ok(())
+    }
+
+    /// Reads and verifies untrusted proof bytes, which should never panic. Any proof which is read
+    /// successfully should only contain valid points, and any proof which verifies should be
+    /// `expected`, since other encodings of it differ only in ignored bits of point masks.
+    fn check_untrusted_proof_bytes<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>(
+        bytes: &[u8],
+        public_inputs: &[C::ScalarField],
+        vk: &VerificationKey<C>,
+        expected: &Proof<C>,
+    ) {
+        if let Ok(proof) = Proof::<C>::read_from(bytes) {
+            assert!(proof.all_points().iter().all(|p| p.is_valid() && p.is_in_subgroup()));
+        }
+        if let Ok(true) = verify_from_reader::<C, InnerC, _>(bytes, public_inputs, vk) {
+            assert_eq!(&Proof::<C>::read_from(bytes).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_verify_from_reader_mutated_bytes() -> Result<()> {
+        type C = Tweedledee;
+        type InnerC = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+        let (proof, vk) = get_circuit_vk::<C, InnerC>();
+        let public_inputs: Vec<SF> = (1..=10).map(SF::from_canonical_usize).collect();
+        let mut bytes = Vec::new();
+        proof.write(&mut bytes)?;
+
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for i in 0..64 {
+            let mut mutated = bytes.clone();
+            match i % 4 {
+                // Corrupt a few random bytes, including the length prefixes.
+                0 | 1 => {
+                    for _ in 0..rng.gen_range(1, 4) {
+                        let index = rng.gen_range(0, mutated.len());
+                        mutated[index] ^= rng.gen_range(1, 256) as u8;
+                    }
+                }
+                // Truncate the proof.
+                2 => mutated.truncate(rng.gen_range(0, bytes.len())),
+                // Replace the proof with random bytes.
+                _ => mutated.iter_mut().for_each(|b| *b = rng.gen()),
+            }
+            check_untrusted_proof_bytes::<C, InnerC>(&mutated, &public_inputs, &vk, &proof);
+        }
+        check_untrusted_proof_bytes::<C, InnerC>(&bytes, &public_inputs, &vk, &proof);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_opening_proof_compression() -> Result<()> {
+        type C = Tweedledee;
+        type BF = <C as Curve>::BaseField;
+        let (proof, _vk) = get_circuit_vk::<C, Tweedledum>();
+        let opening_proof = proof.opening_proof();
+        let compressed = opening_proof.to_compressed_bytes()?;
+        assert_eq!(OpeningProof::<C>::from_compressed_bytes(&compressed)?, opening_proof);
+
+        // Compare against an encoding of the same data which includes both coordinates of each
+        // point.
+        let uncompressed_points: Vec<(BF, BF)> = opening_proof
+            .halo_l
+            .iter()
+            .chain(&opening_proof.halo_r)
+            .chain(&[opening_proof.halo_g, opening_proof.schnorr_proof.r])
+            .map(|p| (p.x, p.y))
+            .collect();
+        let uncompressed = serde_cbor::to_vec(&(
+            uncompressed_points,
+            opening_proof.schnorr_proof.z1,
+            opening_proof.schnorr_proof.z2,
+        ))?;
+        let ratio = compressed.len() as f64 / uncompressed.len() as f64;
+        assert!(ratio < 0.6, "Compressed size ratio was {}", ratio);
+
+        // Corrupting a point's x coordinate should make decompression fail, unless we happen to
+        // land on another valid x coordinate.
+        let mut corrupted = compressed;
+        let l_0 = serde_cbor::to_vec(&opening_proof.halo_l[0])?;
+        let offset = corrupted.windows(l_0.len()).position(|w| w == &l_0[..]).unwrap();
+        let mut results = Vec::new();
+        for delta in 1..=64 {
+            corrupted[offset + l_0.len() - 1] = l_0[l_0.len() - 1].wrapping_add(delta);
+            results.push(OpeningProof::<C>::from_compressed_bytes(&corrupted).is_err());
+        }
+        assert!(results.contains(&true));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,74 @@
+use once_cell::sync::Lazy;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// A key for looking up a cached value: the name of the cache it belongs to, the type of the
+/// value, which typically depends on a field type parameter, and the size it was generated for.
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+struct CacheKey {
+    name: &'static str,
+    value_type_id: TypeId,
+    size: usize,
+}
+
+/// A cached value, whose type is given by the key.
+type CachedValue = Arc<dyn Any + Send + Sync>;
+
+static CACHE: Lazy<Mutex<HashMap<CacheKey, CachedValue>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+thread_local! {
+    /// A per-thread copy of `CACHE`, so that threads which look up values in parallel, e.g. while
+    /// evaluating constraints, don't contend for its lock.
+    static THREAD_CACHE: RefCell<HashMap<CacheKey, CachedValue>> = RefCell::new(HashMap::new());
+}
+
+/// Returns the value of type `T` cached under `name` and `size`, generating it with `generate` if
+/// no thread has done so yet. Since statics can't be generic, this lets generic code such as
+/// `mds_matrix::<F>` cache one value per field type.
+pub(crate) fn get_or_generate<T: Any + Send + Sync>(
+    name: &'static str,
+    size: usize,
+    generate: impl FnOnce() -> T,
+) -> Arc<T> {
+    let key = CacheKey { name, value_type_id: TypeId::of::<T>(), size };
+    let cached = THREAD_CACHE.with(|thread_cache| {
+        thread_cache
+            .borrow_mut()
+            .entry(key)
+            .or_insert_with(|| {
+                let mut cache = CACHE.lock().unwrap();
+                cache
+                    .entry(key)
+                    .or_insert_with(|| Arc::new(generate()))
+                    .clone()
+            })
+            .clone()
+    });
+    cached.downcast().expect("Value cached with the wrong type")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::type_keyed_cache::get_or_generate;
+
+    #[test]
+    fn test_get_or_generate() {
+        let a = get_or_generate("test_a", 1, || vec![1u32]);
+        assert!(Arc::ptr_eq(&a, &get_or_generate("test_a", 1, || vec![2u32])));
+
+        // A different name, size or value type is a different entry.
+        assert_eq!(*get_or_generate("test_b", 1, || vec![3u32]), vec![3]);
+        assert_eq!(*get_or_generate("test_a", 2, || vec![4u32]), vec![4]);
+        assert_eq!(*get_or_generate("test_a", 1, || vec![5u64]), vec![5]);
+
+        // Other threads see the same value.
+        let from_other_thread =
+            std::thread::spawn(|| get_or_generate("test_a", 1, || vec![6u32])).join().unwrap();
+        assert!(Arc::ptr_eq(&a, &from_other_thread));
+    }
+}
@@ -1,3 +1,6 @@
+use std::fmt;
+use std::io::{self, Read};
+
 use anyhow::{bail, ensure, Result};
 use serde::{Deserialize, Serialize};
 
@@ -5,13 +8,18 @@ use crate::partition::get_subgroup_shift;
 
 use crate::gates::evaluate_all_constraints;
 use crate::halo::verify_ipa;
+use crate::plonk_challenger::{RescueHash, SpongeHash};
 use crate::plonk_proof::OldProof;
-use crate::plonk_util::{halo_g, halo_n, halo_n_mul, halo_s, pedersen_hash, powers, reduce_with_powers};
+use crate::plonk_util::{halo_g, halo_n, halo_n_mul, halo_s, pedersen_hash, reduce_with_powers};
 use crate::util::{ceil_div_usize, log2_strict};
-use crate::{blake_hash_usize_to_curve, fft_precompute, msm_execute_parallel, msm_precompute, AffinePoint, Circuit, FftPrecomputation, Field, HaloCurve, MsmPrecomputation, Polynomial, Proof, GRID_WIDTH, NUM_ROUTED_WIRES, NUM_WIRES};
+use crate::{blake_hash_usize_to_curve, fft_precompute, msm_execute_parallel, msm_precompute, rescue_hash_n_to_1, rescue_hash_n_to_1_with_domain, AffinePoint, Circuit, FftPrecomputation, Field, HaloCurve, MsmPrecomputation, Polynomial, Proof, GRID_WIDTH, NUM_ROUTED_WIRES, NUM_WIRES, QUOTIENT_POLYNOMIAL_DEGREE_MULTIPLIER};
 
 pub const SECURITY_BITS: usize = 128;
 
+/// The Rescue domain tag of `VerificationKey::verifier_key_hash`, which separates it from other
+/// hashes such as the public input digest.
+const VERIFIER_KEY_HASH_DOMAIN: u64 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct VerificationKey<C: HaloCurve> {
     pub c_constants: Vec<AffinePoint<C>>,
@@ -43,6 +51,54 @@ impl<C: HaloCurve> VerificationKey<C> {
         self.clear_fft_precomputation();
         self.clear_msm_precomputation();
     }
+
+    /// Computes the same digest of the public inputs as `CircuitBuilder::hash_public_inputs`.
+    pub fn hash_public_inputs(&self, public_inputs: &[C::ScalarField]) -> Result<C::ScalarField> {
+        ensure!(
+            public_inputs.len() == self.num_public_inputs,
+            "Expected {} public inputs, got {}",
+            self.num_public_inputs,
+            public_inputs.len()
+        );
+        Ok(rescue_hash_n_to_1(public_inputs.to_vec(), self.security_bits))
+    }
+
+    /// Returns a digest of the circuit this key is for, namely a Rescue hash of the commitments to
+    /// the constant polynomials (which encode the gate layout, including each gate's selector
+    /// prefix), the commitments to the permutation polynomials, and the circuit's dimensions,
+    /// including its number of public inputs. Each proof carries the digest of the circuit it was
+    /// generated for, which `verify_proof` checks, so a proof checked against the wrong key is
+    /// rejected with a clear error.
+    pub fn verifier_key_hash(&self) -> C::BaseField {
+        verifier_key_hash(
+            &self.c_constants,
+            &self.c_s_sigmas,
+            self.degree,
+            self.num_public_inputs,
+            self.num_gates_without_pis,
+            self.security_bits,
+        )
+    }
+}
+
+/// Computes the digest described in `VerificationKey::verifier_key_hash`.
+pub(crate) fn verifier_key_hash<C: HaloCurve>(
+    c_constants: &[AffinePoint<C>],
+    c_s_sigmas: &[AffinePoint<C>],
+    degree: usize,
+    num_public_inputs: usize,
+    num_gates_without_pis: usize,
+    security_bits: usize,
+) -> C::BaseField {
+    let mut inputs = Vec::new();
+    for point in c_constants.iter().chain(c_s_sigmas) {
+        inputs.push(point.x);
+        inputs.push(point.y);
+    }
+    for &n in &[degree, num_public_inputs, num_gates_without_pis] {
+        inputs.push(C::BaseField::from_canonical_usize(n));
+    }
+    rescue_hash_n_to_1_with_domain(inputs, VERIFIER_KEY_HASH_DOMAIN, security_bits)
 }
 
 /// Verifies a proof `proof` and some old proofs G points for a given verification key.
@@ -56,11 +112,181 @@ pub fn verify_proof<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>
     vk: &VerificationKey<C>,
     verify_g: bool,
 ) -> Result<Option<OldProof<C>>> {
+    verify_proof_with_hash::<C, InnerC, RescueHash<C::BaseField>>(
+        public_inputs,
+        proof,
+        old_proofs,
+        vk,
+        verify_g,
+    )
+}
+
+/// Like `verify_proof`, but hashes the transcript with the given `SpongeHash`, which must match the
+/// one the proof was generated with, as in `Circuit::generate_proof_with_hash`.
+pub fn verify_proof_with_hash<
+    C: HaloCurve,
+    InnerC: HaloCurve<BaseField = C::ScalarField>,
+    H: SpongeHash<C::BaseField>,
+>(
+    public_inputs: &[C::ScalarField],
+    proof: &Proof<C>,
+    old_proofs: &[OldProof<C>],
+    vk: &VerificationKey<C>,
+    verify_g: bool,
+) -> Result<Option<OldProof<C>>> {
+    verify_proof_with_precomputation::<C, InnerC, H>(
+        public_inputs,
+        proof,
+        old_proofs,
+        vk,
+        verify_g,
+        None,
+    )
+}
+
+/// Verifies proofs for a fixed circuit, precomputing the data which `verify_proof` would otherwise
+/// recompute for each proof, namely the MSM precomputation for the Pedersen generators `G` (used
+/// in the linear time check of the G point) and the FFT precomputation used to interpolate the
+/// public inputs.
+pub struct PreprocessedVerifier<C: HaloCurve> {
+    vk: VerificationKey<C>,
+    pedersen_g_msm_precomputation: MsmPrecomputation<C>,
+}
+
+impl<C: HaloCurve> PreprocessedVerifier<C> {
+    pub fn from_circuit(circuit: &Circuit<C>) -> Self {
+        let mut vk = circuit.to_vk();
+        let pedersen_g_msm_precomputation = vk
+            .pedersen_g_msm_precomputation
+            .take()
+            .expect("A circuit's verification key should include its MSM precomputation");
+        PreprocessedVerifier {
+            vk,
+            pedersen_g_msm_precomputation,
+        }
+    }
+
+    /// Creates a verifier from a verification key. Any precomputations included in the key are
+    /// discarded and recomputed, since a key from an untrusted source could include incorrect
+    /// precomputations, which would make the G point check meaningless.
+    pub fn from_vk(mut vk: VerificationKey<C>) -> Self {
+        vk.clear_msm_precomputation();
+        vk.fft_precomputation = Some(fft_precompute(vk.degree));
+        let pedersen_g_msm_precomputation = pedersen_g_msm_precompute(vk.degree);
+        PreprocessedVerifier {
+            vk,
+            pedersen_g_msm_precomputation,
+        }
+    }
+
+    pub fn vk(&self) -> &VerificationKey<C> {
+        &self.vk
+    }
+
+    /// Completely verifies a proof which has no old proofs, returning whether it is valid. See
+    /// `verify_with_old_proofs` for proofs which do, or to get the reason a proof was rejected.
+    pub fn verify<InnerC: HaloCurve<BaseField = C::ScalarField>>(
+        &self,
+        proof: &Proof<C>,
+        public_inputs: &[C::ScalarField],
+    ) -> bool {
+        self.verify_with_old_proofs::<InnerC>(public_inputs, proof, &[]).is_ok()
+    }
+
+    /// Completely verifies a proof, like `verify_proof` with `verify_g` set to `true`, returning
+    /// an error describing the failed check if it is invalid.
+    pub fn verify_with_old_proofs<InnerC: HaloCurve<BaseField = C::ScalarField>>(
+        &self,
+        public_inputs: &[C::ScalarField],
+        proof: &Proof<C>,
+        old_proofs: &[OldProof<C>],
+    ) -> Result<()> {
+        verify_proof_with_precomputation::<C, InnerC, RescueHash<C::BaseField>>(
+            public_inputs,
+            proof,
+            old_proofs,
+            &self.vk,
+            true,
+            Some(&self.pedersen_g_msm_precomputation),
+        )?;
+        Ok(())
+    }
+}
+
+/// An error returned by `verify_from_reader` when a proof could not be read.
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The reader failed or ended early, or the proof contained an invalid encoding, such as a
+    /// non-canonical field element or an `x` coordinate with no corresponding point.
+    Io(io::Error),
+    /// A point in the proof is on the curve, but not in the subgroup of order `|ScalarField|`.
+    PointNotInSubgroup,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyError::Io(e) => write!(f, "Failed to read proof: {}", e),
+            VerifyError::PointNotInSubgroup => write!(f, "Proof point is not in the subgroup"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl From<io::Error> for VerifyError {
+    fn from(e: io::Error) -> Self {
+        VerifyError::Io(e)
+    }
+}
+
+/// Reads a proof in the format written by its `ToBytes` implementation and completely verifies
+/// it, like `verify_proof` with `verify_g` set to `true`. Each component is decoded directly from
+/// the reader, and each point is checked for subgroup membership as soon as it is read, so the
+/// encoded proof is never buffered and a malformed proof is rejected without reading the rest.
+///
+/// Returns `Ok(false)` if the proof was read successfully but is invalid.
+pub fn verify_from_reader<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>, R: Read>(
+    reader: R,
+    public_inputs: &[C::ScalarField],
+    vk: &VerificationKey<C>,
+) -> std::result::Result<bool, VerifyError> {
+    let proof = Proof::read_from(reader)?;
+    Ok(verify_proof::<C, InnerC>(public_inputs, &proof, &[], vk, true).is_ok())
+}
+
+/// Returns an MSM precomputation for the Pedersen generators `G` of a circuit of the given degree.
+pub(crate) fn pedersen_g_msm_precompute<C: HaloCurve>(degree: usize) -> MsmPrecomputation<C> {
+    let pedersen_g: Vec<_> = (0..degree).map(blake_hash_usize_to_curve::<C>).collect();
+    let w = 8; // TODO: Should really be set dynamically based on MSM size.
+    msm_precompute(&AffinePoint::batch_to_projective(&pedersen_g), w)
+}
+
+/// Like `verify_proof`, but uses the given MSM precomputation for the Pedersen generators `G`, if
+/// any, rather than computing one.
+fn verify_proof_with_precomputation<
+    C: HaloCurve,
+    InnerC: HaloCurve<BaseField = C::ScalarField>,
+    H: SpongeHash<C::BaseField>,
+>(
+    public_inputs: &[C::ScalarField],
+    proof: &Proof<C>,
+    old_proofs: &[OldProof<C>],
+    vk: &VerificationKey<C>,
+    verify_g: bool,
+    pedersen_g_msm_precomputation: Option<&MsmPrecomputation<C>>,
+) -> Result<Option<OldProof<C>>> {
+    ensure!(
+        proof.vk_hash == vk.verifier_key_hash(),
+        "The proof was generated for a different circuit than the verification key."
+    );
+
     // Verify that the proof parameters are valid.
+    check_proof_shape(public_inputs, proof, old_proofs, vk)?;
     check_proof_parameters(proof)?;
 
     // Observe the transcript and generate the associated challenge points using Fiat-Shamir.
-    let challs = proof.get_challenges(public_inputs, old_proofs)?;
+    let challs = proof.get_challenges_with_hash::<H>(public_inputs, old_proofs)?;
 
     // Check the old proofs' openings.
     verify_old_proof_evaluation(old_proofs, &proof, challs.zeta)?;
@@ -99,8 +325,8 @@ pub fn verify_proof<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>
         let beta_s_sigma = challs.beta * proof.o_local.o_plonk_sigmas[i];
         let f_prime_part = proof.o_local.o_wires[i] + beta_s_id + challs.gamma;
         let g_prime_part = proof.o_local.o_wires[i] + beta_s_sigma + challs.gamma;
-        f_prime = f_prime * f_prime_part;
-        g_prime = g_prime * g_prime_part;
+        f_prime *= f_prime_part;
+        g_prime *= g_prime_part;
     }
     let vanishing_v_shift_term = f_prime * z_x - g_prime * z_gx;
 
@@ -132,7 +358,7 @@ pub fn verify_proof<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>
             acc * (challs.zeta - subgroup_generator_n.exp_usize(vk.num_gates_without_pis + 2 * i))
         });
     let pis_quotient_numerator =
-        C::ScalarField::inner_product(&proof.o_local.o_wires, &powers(challs.alpha, NUM_WIRES))
+        C::ScalarField::inner_product(&proof.o_local.o_wires, &C::ScalarField::powers_of(challs.alpha, NUM_WIRES))
             - public_inputs_to_polynomial(
                 public_inputs,
                 challs.alpha,
@@ -171,14 +397,18 @@ pub fn verify_proof<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>
     );
 
     if verify_g {
-        let pedersen_g: Vec<_> = (0..vk.degree).map(blake_hash_usize_to_curve::<C>).collect();
-        let w = 8; // TODO: Should really be set dynamically based on MSM size.
-        let pedersen_g_msm_precomputation =
-            msm_precompute(&AffinePoint::batch_to_projective(&pedersen_g), w);
+        let computed_precomputation;
+        let pedersen_g_msm_precomputation = match pedersen_g_msm_precomputation {
+            Some(precomputation) => precomputation,
+            None => {
+                computed_precomputation = pedersen_g_msm_precompute(vk.degree);
+                &computed_precomputation
+            }
+        };
 
         // Verify that `self.halo_g = <s, G>`.
         if proof.halo_g
-            == pedersen_hash(&halo_s(&challs.halo_us), &pedersen_g_msm_precomputation).to_affine()
+            == pedersen_hash(&halo_s(&challs.halo_us), pedersen_g_msm_precomputation).to_affine()
         {
             Ok(None)
         } else {
@@ -221,9 +451,9 @@ fn verify_all_ipas<C: HaloCurve>(
         &[proof.c_pis_quotient],
     ]
     .concat();
-    let powers_of_u = powers(u, c_all.len());
-    let actual_scalars = powers_of_u
-        .iter()
+    let actual_scalars = u
+        .powers()
+        .take(c_all.len())
         .map(|u_pow| halo_n::<C>(&u_pow.to_canonical_bool_vec()[..security_bits]))
         .collect::<Vec<_>>();
     let precomputation = msm_precompute(&AffinePoint::batch_to_projective(&c_all), 8);
@@ -285,6 +515,51 @@ fn verify_old_proof_evaluation<C: HaloCurve>(
     Ok(())
 }
 
+/// Checks that the numbers of public inputs, commitments and openings match the circuit, so that
+/// a malformed proof is rejected rather than causing a panic later on. Also checks that no point
+/// is the point at infinity, which an honest prover will never produce (except with negligible
+/// probability) and which cannot be observed by a `Challenger`.
+fn check_proof_shape<C: HaloCurve>(
+    public_inputs: &[C::ScalarField],
+    proof: &Proof<C>,
+    old_proofs: &[OldProof<C>],
+    vk: &VerificationKey<C>,
+) -> Result<()> {
+    ensure!(
+        public_inputs.len() == vk.num_public_inputs,
+        "Expected {} public inputs, got {}",
+        vk.num_public_inputs,
+        public_inputs.len()
+    );
+    ensure!(
+        proof.c_wires.len() == NUM_WIRES,
+        "Wrong number of wire commitments."
+    );
+    ensure!(
+        proof.c_plonk_t.len() == QUOTIENT_POLYNOMIAL_DEGREE_MULTIPLIER,
+        "Wrong number of t commitments."
+    );
+    ensure!(
+        proof.halo_l.len() == log2_strict(vk.degree) && proof.halo_r.len() == log2_strict(vk.degree),
+        "Wrong number of Halo L or R points."
+    );
+    for opening_set in proof.all_opening_sets() {
+        ensure!(
+            opening_set.o_constants.len() == vk.c_constants.len()
+                && opening_set.o_plonk_sigmas.len() == vk.c_s_sigmas.len()
+                && opening_set.o_wires.len() == NUM_WIRES
+                && opening_set.o_plonk_t.len() == QUOTIENT_POLYNOMIAL_DEGREE_MULTIPLIER
+                && opening_set.o_old_proofs.len() == old_proofs.len(),
+            "Wrong number of openings."
+        );
+    }
+
+    for point in proof.all_points() {
+        ensure!(!point.zero, "A proof point is the point at infinity.");
+    }
+    Ok(())
+}
+
 /// Check that the parameters in a proof are well-formed, i.e,
 /// that curve points are on the curve, and field elements are in range.
 /// Panics otherwise.
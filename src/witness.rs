@@ -1,8 +1,22 @@
 use crate::util::transpose;
 use crate::{biguint_to_field, biguint_to_limbs, field_to_biguint, AffinePoint, AffinePointTarget, BigIntTarget, Curve, Field, ForeignFieldTarget, OrderingTarget, Target, Wire, LIMB_BITS, NUM_WIRES, NUM_ADVICE_WIRES, NUM_ROUTED_WIRES};
+use anyhow::{ensure, Result};
 use num::{BigUint, Zero};
+use std::collections::hash_map::Entry;
 use std::{cmp::Ordering, collections::HashMap};
 
+/// Determines how `PartialWitness::extend` handles targets which both witnesses populate with
+/// different values.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum MergePolicy {
+    /// Use the value from the witness being merged in.
+    Overwrite,
+    /// Keep the value which was already present.
+    KeepExisting,
+    /// Return an error.
+    ErrorOnConflict,
+}
+
 #[derive(Debug)]
 pub struct PartialWitness<F: Field> {
     wire_values: HashMap<Target<F>, F>,
@@ -41,6 +55,28 @@ impl<F: Field> PartialWitness<F> {
         self.wire_values.keys().cloned().collect()
     }
 
+    /// Returns an iterator over all populated wires and their values, in arbitrary order. Targets
+    /// which are not wires, such as virtual targets, are skipped.
+    pub fn wires(&self) -> impl Iterator<Item = (Wire, F)> + '_ {
+        self.wire_values.iter().filter_map(|(&t, &v)| match t {
+            Target::Wire(wire) => Some((wire, v)),
+            _ => None,
+        })
+    }
+
+    /// Returns a dense `num_gates` by `num_wires` matrix of wire values, indexed as
+    /// `result[gate][input]`. Unpopulated wires are set to zero, and populated wires outside the
+    /// given bounds are ignored.
+    pub fn to_wire_values(&self, num_gates: usize, num_wires: usize) -> Vec<Vec<F>> {
+        let mut wire_values = vec![vec![F::ZERO; num_wires]; num_gates];
+        for (Wire { gate, input }, value) in self.wires() {
+            if gate < num_gates && input < num_wires {
+                wire_values[gate][input] = value;
+            }
+        }
+        wire_values
+    }
+
     pub fn get_target(&self, target: Target<F>) -> F {
         self.wire_values[&target]
     }
@@ -169,10 +205,30 @@ impl<F: Field> PartialWitness<F> {
         self.set_target(Target::Wire(wire), value);
     }
 
-    pub fn extend(&mut self, other: PartialWitness<F>) {
+    /// Merges the values of `other` into this witness. `policy` determines what happens when both
+    /// witnesses populate the same target with different values; an error is only possible with
+    /// `MergePolicy::ErrorOnConflict`. If there is a conflict, targets which were merged before it
+    /// remain merged.
+    pub fn extend(&mut self, other: PartialWitness<F>, policy: MergePolicy) -> Result<()> {
         for (target, value) in other.wire_values {
-            self.set_target(target, value);
+            match self.wire_values.entry(target) {
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+                Entry::Occupied(mut entry) => match policy {
+                    MergePolicy::Overwrite => {
+                        entry.insert(value);
+                    }
+                    MergePolicy::KeepExisting => (),
+                    MergePolicy::ErrorOnConflict => ensure!(
+                        *entry.get() == value,
+                        "Target {:?} was set twice with different values",
+                        target
+                    ),
+                },
+            }
         }
+        Ok(())
     }
 
     /// Replace all `PublicInput`-type targets by their corresponding `Wire`-type targets
@@ -231,28 +287,164 @@ impl<F: Field> Witness<F> {
     /// Converts a `PartialWitness` to a a `Witness`.
     /// The partial witness should be sufficiently preprocessed, e.g., it should contain copy constraints.
     pub fn from_partial(pw: &PartialWitness<F>, degree: usize) -> Self {
-        let mut wire_values: Vec<Vec<F>> = Vec::new();
-        for i in 0..degree {
-            let mut gate_i_wires = Vec::new();
-            for j in 0..NUM_WIRES {
-                let wire = Wire { gate: i, input: j };
-                let value = if pw.contains_wire(wire) {
-                    pw.get_wire(wire)
-                } else {
-                    // In our circuit model, a lot of wires are unused. We just set them to zero.
-                    F::ZERO
-                };
-                gate_i_wires.push(value);
-            }
-            wire_values.push(gate_i_wires);
-        }
-        Witness::new(wire_values)
+        // In our circuit model, a lot of wires are unused. We just set them to zero.
+        Witness::new(pw.to_wire_values(degree, NUM_WIRES))
     }
 }
 
 pub trait WitnessGenerator<F: Field>: 'static + Sync {
+    /// The targets which must be populated before this generator can run.
     fn dependencies(&self) -> Vec<Target<F>>;
 
     /// Given a partial witness, return any newly generated values. The caller will merge them in.
     fn generate(&self, constants: &[Vec<F>], witness: &PartialWitness<F>) -> PartialWitness<F>;
 }
+
+/// A generator which populates a single target by applying a function to the values of some input
+/// targets, which avoids the boilerplate of implementing `WitnessGenerator` for simple gadgets.
+pub struct SimpleGenerator<F: Field, Func: Fn(&[F]) -> F + 'static + Sync> {
+    inputs: Vec<Target<F>>,
+    output: Target<F>,
+    f: Func,
+}
+
+impl<F: Field, Func: Fn(&[F]) -> F + 'static + Sync> SimpleGenerator<F, Func> {
+    pub fn new(inputs: Vec<Target<F>>, output: Target<F>, f: Func) -> Self {
+        SimpleGenerator { inputs, output, f }
+    }
+}
+
+impl<F: Field, Func: Fn(&[F]) -> F + 'static + Sync> WitnessGenerator<F>
+    for SimpleGenerator<F, Func>
+{
+    fn dependencies(&self) -> Vec<Target<F>> {
+        self.inputs.clone()
+    }
+
+    fn generate(&self, _constants: &[Vec<F>], witness: &PartialWitness<F>) -> PartialWitness<F> {
+        let input_values = witness.get_targets(&self.inputs);
+        let mut result = PartialWitness::new();
+        result.set_target(self.output, (self.f)(&input_values));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CircuitBuilder, Curve, Field, MergePolicy, PartialWitness, SimpleGenerator, Target, Tweedledum, Wire, NUM_WIRES};
+
+    #[test]
+    fn test_simple_generator() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let x = builder.add_virtual_target();
+        let x_squared = builder.add_virtual_target();
+        builder.add_generator(SimpleGenerator::new(vec![x], x_squared, |inputs: &[SF]| {
+            inputs[0].square()
+        }));
+        // Check the generated value in-circuit as well.
+        let expected = builder.square(x);
+        builder.copy(x_squared, expected);
+        let circuit = builder.build();
+
+        let x_value = SF::rand();
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_target(x, x_value);
+        let witness = circuit.generate_partial_witness(partial_witness);
+        assert_eq!(witness.get_target(x_squared), x_value.square());
+    }
+
+    #[test]
+    fn test_to_wire_values() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let mut builder = CircuitBuilder::<C>::new(128);
+        let x = builder.add_virtual_target();
+        let y = builder.add_virtual_target();
+        let z = builder.mul(x, y);
+        let circuit = builder.build();
+
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_target(x, SF::from_canonical_usize(3));
+        partial_witness.set_target(y, SF::from_canonical_usize(5));
+        let witness = circuit.generate_partial_witness(partial_witness);
+
+        // Virtual targets are not wires, so they should not show up here.
+        assert!(witness.wires().all(|(wire, value)| witness.get_wire(wire) == value));
+        let num_wires = witness.wires().count();
+        assert!(num_wires < witness.all_populated_targets().len());
+
+        let wire_values = witness.to_wire_values(circuit.degree(), NUM_WIRES);
+        assert_eq!(wire_values.len(), circuit.degree());
+        assert!(wire_values.iter().all(|gate_wires| gate_wires.len() == NUM_WIRES));
+        for (wire, value) in witness.wires() {
+            assert_eq!(wire_values[wire.gate][wire.input], value);
+        }
+
+        let z_wire = match z {
+            Target::Wire(wire) => wire,
+            _ => panic!("Expected the product to be a wire"),
+        };
+        assert_eq!(wire_values[z_wire.gate][z_wire.input], SF::from_canonical_usize(15));
+
+        let unpopulated = (0..circuit.degree())
+            .flat_map(|gate| (0..NUM_WIRES).map(move |input| Wire { gate, input }))
+            .find(|&wire| !witness.contains_wire(wire))
+            .unwrap();
+        assert_eq!(wire_values[unpopulated.gate][unpopulated.input], SF::ZERO);
+    }
+
+    #[test]
+    fn test_extend_policies() {
+        type C = Tweedledum;
+        type SF = <C as Curve>::ScalarField;
+
+        let shared = Target::Wire(Wire { gate: 0, input: 0 });
+        let agreeing = Target::Wire(Wire { gate: 0, input: 1 });
+        let existing_only = Target::Wire(Wire { gate: 1, input: 0 });
+        let other_only = Target::Wire(Wire { gate: 1, input: 1 });
+
+        let existing = || {
+            let mut witness = PartialWitness::new();
+            witness.set_targets(&[shared, agreeing, existing_only], &[SF::ONE, SF::TWO, SF::ONE]);
+            witness
+        };
+        let other = || {
+            let mut witness = PartialWitness::new();
+            witness.set_targets(&[shared, agreeing, other_only], &[SF::THREE, SF::TWO, SF::FOUR]);
+            witness
+        };
+
+        let mut overwritten = existing();
+        overwritten.extend(other(), MergePolicy::Overwrite).unwrap();
+        assert_eq!(
+            overwritten.get_targets(&[shared, agreeing, existing_only, other_only]),
+            vec![SF::THREE, SF::TWO, SF::ONE, SF::FOUR]
+        );
+
+        let mut kept = existing();
+        kept.extend(other(), MergePolicy::KeepExisting).unwrap();
+        assert_eq!(
+            kept.get_targets(&[shared, agreeing, existing_only, other_only]),
+            vec![SF::ONE, SF::TWO, SF::ONE, SF::FOUR]
+        );
+
+        let mut conflicting = existing();
+        assert!(conflicting
+            .extend(other(), MergePolicy::ErrorOnConflict)
+            .is_err());
+        assert_eq!(conflicting.get_target(shared), SF::ONE);
+
+        // Overlapping targets with equal values are not conflicts.
+        let mut agreeing_witness = existing();
+        let mut subset = PartialWitness::new();
+        subset.set_targets(&[agreeing, other_only], &[SF::TWO, SF::FOUR]);
+        agreeing_witness
+            .extend(subset, MergePolicy::ErrorOnConflict)
+            .unwrap();
+        assert_eq!(agreeing_witness.get_target(other_only), SF::FOUR);
+    }
+}
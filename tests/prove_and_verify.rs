@@ -1,6 +1,10 @@
 use anyhow::Result;
-use plonky::{blake_hash_base_field_to_curve, msm_parallel, rescue_hash_1_to_1, verify_proof, AffinePoint, Base4SumGate, Circuit, CircuitBuilder, Curve, CurveMulOp, Field, HaloCurve, PartialWitness, Target, Tweedledee, Tweedledum, Wire, Witness};
-use rand::{thread_rng, Rng};
+use num::{BigUint, One, Zero};
+use plonky::plonk_challenger::{PoseidonHash, RescueHash};
+use plonky::{blake_hash_base_field_to_curve, stitch, Aggregator, ChunkedProver, field_to_biguint, msm_parallel, rescue_hash_1_to_1, verify_from_reader, verify_proof, verify_proof_with_hash, AffinePoint, Base4SumGate, BitReversal3Table, Circuit, CircuitBuilder, ColumnSumGate, Curve, CurveMulOp, Field, HaloCurve, MdsGate, MergePolicy, PartialWitness, PreprocessedVerifier, Proof, Target, ToBytes, Tweedledee, Tweedledum, UnsatisfiedConstraints, Wire, Witness};
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::ThreadPoolBuilder;
 use std::time::Instant;
 
 fn get_trivial_circuit<C: HaloCurve>(x: C::ScalarField) -> (Circuit<C>, Witness<C::ScalarField>) {
@@ -25,6 +29,212 @@ fn test_proof_trivial() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_proof_with_seeded_rng_is_reproducible() -> Result<()> {
+    let (circuit, witness) = get_trivial_circuit(<Tweedledee as Curve>::ScalarField::ZERO);
+    let prove_with_seed = |seed: u64| {
+        circuit.generate_proof_with_rng::<Tweedledum, _>(
+            &witness,
+            &[],
+            true,
+            &mut ChaCha8Rng::seed_from_u64(seed),
+        )
+    };
+    let proof_1 = prove_with_seed(0)?;
+    let proof_2 = prove_with_seed(0)?;
+    let proof_3 = prove_with_seed(1)?;
+    assert_eq!(serde_cbor::to_vec(&proof_1)?, serde_cbor::to_vec(&proof_2)?);
+    assert_ne!(serde_cbor::to_vec(&proof_1)?, serde_cbor::to_vec(&proof_3)?);
+    verify_proof::<Tweedledee, Tweedledum>(&[], &proof_1, &[], &circuit.into(), true)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_parallel_proof_matches_serial() -> Result<()> {
+    type SF = <Tweedledee as Curve>::ScalarField;
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let input = builder.add_virtual_target();
+    let hash = builder.rescue_hash_n_to_1(&[input]);
+    builder.rescue_hash_n_to_1(&[hash]);
+    let circuit = builder.build();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(input, SF::rand());
+    let witness = circuit.generate_witness(partial_witness);
+
+    let prove_with_threads = |num_threads: usize| {
+        let pool = ThreadPoolBuilder::new().num_threads(num_threads).build().unwrap();
+        pool.install(|| {
+            circuit.generate_proof_with_rng::<Tweedledum, _>(
+                &witness,
+                &[],
+                true,
+                &mut ChaCha8Rng::seed_from_u64(0),
+            )
+        })
+    };
+    let serial_proof = prove_with_threads(1)?;
+    let parallel_proof = prove_with_threads(4)?;
+    assert_eq!(serde_cbor::to_vec(&serial_proof)?, serde_cbor::to_vec(&parallel_proof)?);
+    verify_proof::<Tweedledee, Tweedledum>(&[], &parallel_proof, &[], &circuit.into(), true)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_proof_checked_unsatisfied() -> Result<()> {
+    type SF = <Tweedledee as Curve>::ScalarField;
+
+    let (circuit, witness) = get_trivial_circuit::<Tweedledee>(SF::ZERO);
+    assert_eq!(circuit.check_witness::<Tweedledum>(&witness), Ok(()));
+    let proof = circuit.generate_proof_checked::<Tweedledum>(&witness, &[], true)?;
+    verify_proof::<Tweedledee, Tweedledum>(&[], &proof, &[], &circuit.into(), true)?;
+
+    // Tamper with the output of a multiplication. This should be reported before any proving work
+    // is done.
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let x = builder.add_virtual_target();
+    let x_squared = builder.square(x);
+    let circuit = builder.build();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(x, SF::rand());
+    let mut partial_witness = circuit.generate_partial_witness(partial_witness);
+    let bad_gate = match x_squared {
+        Target::Wire(wire) => {
+            partial_witness.set_wire(wire, SF::rand());
+            wire.gate
+        }
+        _ => panic!("Expected a wire"),
+    };
+    let witness = Witness::from_partial(&partial_witness, circuit.degree());
+    let error = circuit
+        .generate_proof_checked::<Tweedledum>(&witness, &[], true)
+        .unwrap_err();
+    let unsatisfied = error
+        .downcast_ref::<UnsatisfiedConstraints>()
+        .expect("Expected an UnsatisfiedConstraints error");
+    assert_eq!(unsatisfied.gates, vec![bad_gate]);
+
+    Ok(())
+}
+
+#[test]
+fn test_proof_rejected_by_other_circuits_key() -> Result<()> {
+    type SF = <Tweedledee as Curve>::ScalarField;
+
+    let (circuit_a, witness) = get_trivial_circuit::<Tweedledee>(SF::ZERO);
+    let proof = circuit_a.generate_proof::<Tweedledum>(&witness, &[], true)?;
+    assert_eq!(proof.vk_hash, circuit_a.verifier_key_hash());
+    assert_eq!(proof.vk_hash, circuit_a.to_vk().verifier_key_hash());
+
+    // Circuit B differs only in its constant.
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let t = builder.constant_wire(SF::ONE);
+    builder.assert_zero(t);
+    let circuit_b = builder.build();
+    assert_ne!(circuit_b.verifier_key_hash(), circuit_a.verifier_key_hash());
+
+    let error = verify_proof::<Tweedledee, Tweedledum>(&[], &proof, &[], &circuit_b.to_vk(), true)
+        .unwrap_err();
+    assert!(error.to_string().contains("different circuit"));
+    verify_proof::<Tweedledee, Tweedledum>(&[], &proof, &[], &circuit_a.into(), true)?;
+
+    Ok(())
+}
+
+/// Builds a circuit which applies `x -> x^2 + x` to its input `steps` times. The input is a public
+/// input, followed by the output.
+fn iterated_circuit(
+    steps: usize,
+) -> (Circuit<Tweedledee>, Target<<Tweedledee as Curve>::ScalarField>) {
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let input = builder.add_public_input();
+    let output_pi = builder.add_public_input();
+    let mut acc = input;
+    for _ in 0..steps {
+        let square = builder.square(acc);
+        acc = builder.add(square, acc);
+    }
+    builder.copy(acc, output_pi);
+    (builder.build(), input)
+}
+
+#[test]
+fn test_chunked_prover() -> Result<()> {
+    type SF = <Tweedledee as Curve>::ScalarField;
+    let (circuit, input) = iterated_circuit(100);
+    let witness_for = |x: SF| {
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_target(input, x);
+        circuit.generate_witness(partial_witness)
+    };
+
+    let witness = witness_for(SF::rand());
+    let public_inputs = circuit.get_public_inputs(&witness);
+    let proof = circuit.generate_proof::<Tweedledum>(&witness, &[], true)?;
+    let vk = circuit.to_vk();
+    verify_proof::<Tweedledee, Tweedledum>(&public_inputs, &proof, &[], &vk, true)?;
+
+    // Split the circuit in the middle of the iteration, so that the chunks share the intermediate
+    // value along with the copied rows after the boundary. Like the monolithic proof, the chunks'
+    // proofs are accepted for the right public inputs, and only for those.
+    let half = circuit.degree() / 2;
+    let prover = ChunkedProver::new(&circuit, &[half])?;
+    let ranges = prover.chunks().iter().map(|chunk| chunk.gates.clone()).collect::<Vec<_>>();
+    assert_eq!(ranges, vec![0..half, half..circuit.degree()]);
+    let chunked_vk = prover.verification_key();
+    let chunk_proofs = prover.prove::<Tweedledum>(&witness, true)?;
+    stitch::<Tweedledee, Tweedledum>(&chunked_vk, &public_inputs, &chunk_proofs)?;
+
+    let mut wrong_public_inputs = public_inputs.clone();
+    wrong_public_inputs[1] = wrong_public_inputs[1] + SF::ONE;
+    assert!(
+        verify_proof::<Tweedledee, Tweedledum>(&wrong_public_inputs, &proof, &[], &vk, true)
+            .is_err()
+    );
+    assert!(
+        stitch::<Tweedledee, Tweedledum>(&chunked_vk, &wrong_public_inputs, &chunk_proofs)
+            .is_err()
+    );
+
+    // A second chunk proven for a different input is rejected, even though its own proof is valid.
+    let other_chunk_proofs = prover.prove::<Tweedledum>(&witness_for(SF::rand()), true)?;
+    let mixed_chunk_proofs = vec![chunk_proofs[0].clone(), other_chunk_proofs[1].clone()];
+    assert!(
+        stitch::<Tweedledee, Tweedledum>(&chunked_vk, &public_inputs, &mixed_chunk_proofs)
+            .is_err()
+    );
+
+    // Boundaries must be increasing and within the circuit.
+    assert!(ChunkedProver::new(&circuit, &[0]).is_err());
+    assert!(ChunkedProver::new(&circuit, &[20, 10]).is_err());
+    assert!(ChunkedProver::new(&circuit, &[circuit.degree()]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_proof_with_poseidon_transcript() -> Result<()> {
+    type C = Tweedledee;
+    type InnerC = Tweedledum;
+
+    let (circuit, witness) = get_trivial_circuit(<C as Curve>::ScalarField::ZERO);
+    let proof = circuit.generate_proof_with_hash::<InnerC, PoseidonHash<_>, _>(
+        &witness,
+        &[],
+        true,
+        &mut thread_rng(),
+    )?;
+    let vk = circuit.into();
+    verify_proof_with_hash::<C, InnerC, PoseidonHash<_>>(&[], &proof, &[], &vk, true)?;
+
+    // The verifier must use the same transcript hash as the prover.
+    assert!(verify_proof_with_hash::<C, InnerC, RescueHash<_>>(&[], &proof, &[], &vk, true).is_err());
+    assert!(verify_proof::<C, InnerC>(&[], &proof, &[], &vk, true).is_err());
+
+    Ok(())
+}
+
 #[test]
 #[allow(clippy::same_item_push)]
 fn test_proof_trivial_circuit_many_proofs() -> Result<()> {
@@ -251,6 +461,202 @@ fn test_proof_public_input() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_hash_public_inputs() -> Result<()> {
+    type C = Tweedledee;
+    type InnerC = Tweedledum;
+    type SF = <C as Curve>::ScalarField;
+
+    let values = (0..5).map(|_| SF::rand()).collect::<Vec<_>>();
+    let mut builder = CircuitBuilder::<C>::new(128);
+    let pis = builder.add_public_inputs(values.len());
+    assert_eq!(builder.num_public_inputs(), values.len());
+    let digest = builder.hash_public_inputs();
+    let circuit = builder.build();
+
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_targets(&pis, &values);
+    let partial_witness = circuit.generate_partial_witness(partial_witness);
+    let vk = circuit.to_vk();
+    let expected = vk.hash_public_inputs(&values)?;
+    assert_eq!(partial_witness.get_target(digest), expected);
+    assert!(vk.hash_public_inputs(&values[1..]).is_err());
+
+    let witness = Witness::from_partial(&partial_witness, circuit.degree());
+    let proof = circuit.generate_proof::<InnerC>(&witness, &[], true)?;
+    verify_proof::<C, InnerC>(&values, &proof, &[], &vk, true)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_assert_equal_to_public_input() -> Result<()> {
+    type C = Tweedledee;
+    type InnerC = Tweedledum;
+    type SF = <C as Curve>::ScalarField;
+
+    // A circuit proving knowledge of x such that x^3 + x is the second public input.
+    let mut builder = CircuitBuilder::<C>::new(128);
+    let first = builder.add_public_input();
+    builder.add_public_input();
+    let x = builder.add_virtual_target();
+    let x_squared = builder.square(x);
+    let output = builder.mul_add(x_squared, x, x);
+    builder.assert_equal_to_public_input(output, 1);
+    let circuit = builder.build();
+    let vk = circuit.to_vk();
+
+    let x_value = SF::rand();
+    let first_value = SF::rand();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(first, first_value);
+    partial_witness.set_target(x, x_value);
+    let witness = circuit.generate_witness(partial_witness);
+    let proof = circuit.generate_proof::<InnerC>(&witness, &[], true)?;
+
+    let output_value = x_value.cube() + x_value;
+    verify_proof::<C, InnerC>(&[first_value, output_value], &proof, &[], &vk, true)?;
+    assert!(verify_proof::<C, InnerC>(
+        &[first_value, output_value + SF::ONE],
+        &proof,
+        &[],
+        &vk,
+        true
+    )
+    .is_err());
+    assert!(
+        verify_proof::<C, InnerC>(&[output_value, first_value], &proof, &[], &vk, true).is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_preprocessed_verifier() -> Result<()> {
+    type C = Tweedledee;
+    type InnerC = Tweedledum;
+    type SF = <C as Curve>::ScalarField;
+
+    // A circuit proving knowledge of x such that x^2 is the public input.
+    let mut builder = CircuitBuilder::<C>::new(128);
+    let x = builder.add_virtual_target();
+    let pi = builder.add_public_input();
+    let x_squared = builder.square(x);
+    builder.copy(x_squared, pi);
+    let circuit = builder.build();
+
+    let vk = circuit.to_vk();
+    let preprocessed = PreprocessedVerifier::from_circuit(&circuit);
+    let preprocessed_from_vk = PreprocessedVerifier::from_vk(vk.clone());
+    for _ in 0..10 {
+        let x_value = SF::rand();
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_target(x, x_value);
+        let witness = circuit.generate_witness(partial_witness);
+        let proof = circuit.generate_proof::<InnerC>(&witness, &[], true)?;
+
+        let public_inputs = [x_value.square()];
+        verify_proof::<C, InnerC>(&public_inputs, &proof, &[], &vk, true)?;
+        preprocessed.verify_with_old_proofs::<InnerC>(&public_inputs, &proof, &[])?;
+        preprocessed_from_vk.verify_with_old_proofs::<InnerC>(&public_inputs, &proof, &[])?;
+        assert!(preprocessed.verify::<InnerC>(&proof, &public_inputs));
+        assert!(preprocessed_from_vk.verify::<InnerC>(&proof, &public_inputs));
+
+        let wrong_public_inputs = [x_value.square() + SF::ONE];
+        assert!(verify_proof::<C, InnerC>(&wrong_public_inputs, &proof, &[], &vk, true).is_err());
+        assert!(preprocessed
+            .verify_with_old_proofs::<InnerC>(&wrong_public_inputs, &proof, &[])
+            .is_err());
+        assert!(!preprocessed.verify::<InnerC>(&proof, &wrong_public_inputs));
+    }
+
+    Ok(())
+}
+
+/// Returns a circuit proving knowledge of x such that x^2 is the public input, along with `n`
+/// proofs for random values of x and their public inputs.
+fn square_circuit_proofs<C: HaloCurve, InnerC: HaloCurve<BaseField = C::ScalarField>>(
+    n: usize,
+) -> Result<(Circuit<C>, Vec<(Vec<C::ScalarField>, Proof<C>)>)> {
+    let mut builder = CircuitBuilder::<C>::new(128);
+    let x = builder.add_virtual_target();
+    let pi = builder.add_public_input();
+    let x_squared = builder.square(x);
+    builder.copy(x_squared, pi);
+    let circuit = builder.build();
+
+    let mut proofs = Vec::new();
+    for _ in 0..n {
+        let x_value = C::ScalarField::rand();
+        let mut partial_witness = PartialWitness::new();
+        partial_witness.set_target(x, x_value);
+        let witness = circuit.generate_witness(partial_witness);
+        let proof = circuit.generate_proof::<InnerC>(&witness, &[], true)?;
+        proofs.push((vec![x_value.square()], proof));
+    }
+    Ok((circuit, proofs))
+}
+
+#[test]
+fn test_aggregation() -> Result<()> {
+    type C = Tweedledee;
+    type InnerC = Tweedledum;
+
+    let (circuit, proofs) = square_circuit_proofs::<C, InnerC>(3)?;
+    let vk = circuit.to_vk();
+
+    let mut aggregator = Aggregator::new(vk.clone());
+    for (public_inputs, proof) in &proofs {
+        aggregator.absorb::<InnerC>(public_inputs, proof, &[])?;
+    }
+    assert_eq!(aggregator.num_accumulators(), 3);
+    let aggregate = aggregator.finalize()?;
+    assert!(aggregate.verify(&vk, true)?.is_none());
+
+    // The aggregate's own G point check can be deferred to a further aggregate.
+    let accumulator = aggregate.verify(&vk, false)?.unwrap();
+    let mut aggregator = Aggregator::new(vk.clone());
+    aggregator.absorb_accumulator(accumulator)?;
+    aggregator.finalize()?.verify(&vk, true)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_aggregation_invalid_input() -> Result<()> {
+    type C = Tweedledee;
+    type InnerC = Tweedledum;
+    type SF = <C as Curve>::ScalarField;
+
+    let (circuit, proofs) = square_circuit_proofs::<C, InnerC>(3)?;
+    let vk = circuit.to_vk();
+
+    // A proof with the wrong public inputs is rejected when it is absorbed.
+    let mut aggregator = Aggregator::new(vk.clone());
+    let (public_inputs, proof) = &proofs[0];
+    assert!(aggregator
+        .absorb::<InnerC>(&[public_inputs[0] + SF::ONE], proof, &[])
+        .is_err());
+    assert_eq!(aggregator.num_accumulators(), 0);
+
+    // An accumulator with an incorrect G point is only caught by the aggregate proof.
+    let mut accumulators = proofs
+        .iter()
+        .map(|(public_inputs, proof)| {
+            Ok(verify_proof::<C, InnerC>(public_inputs, proof, &[], &vk, false)?.unwrap())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    accumulators[1].halo_g = accumulators[2].halo_g;
+    for accumulator in accumulators {
+        aggregator.absorb_accumulator(accumulator)?;
+    }
+    let aggregate = aggregator.finalize()?;
+    assert!(aggregate.verify(&vk, true).is_err());
+    assert!(aggregate.verify(&vk, false).is_err());
+
+    Ok(())
+}
+
 #[test]
 fn test_proof_public_input_copied() -> Result<()> {
     // Set many random public inputs
@@ -306,6 +712,489 @@ fn test_rescue_hash() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_mds_layer() -> Result<()> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let xs = (0..4).map(|_| F::rand()).collect::<Vec<_>>();
+    let ys = MdsGate::<Tweedledee>::mix(&xs);
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let ts = builder.add_virtual_targets(4);
+    let ys_pur = builder.mds_layer(&ts);
+    let cs = builder.constant_wires(&ys);
+    builder.assert_equal_vec(&ys_pur, &cs);
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_targets(&ts, &xs);
+    let circuit = builder.build();
+    prove_and_verify(&circuit, partial_witness)
+}
+
+#[test]
+fn test_small_table_lookup() -> Result<()> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let ts = builder.add_virtual_targets(8);
+    let mut partial_witness = PartialWitness::new();
+    for (i, &t) in ts.iter().enumerate() {
+        partial_witness.set_target(t, F::from_canonical_usize(i));
+        let out = builder.small_table_lookup::<BitReversal3Table>(t);
+        let expected = builder.constant_wire(F::from_canonical_usize(
+            (i & 1) << 2 | (i & 2) | (i & 4) >> 2,
+        ));
+        builder.copy(out, expected);
+    }
+    let circuit = builder.build();
+    prove_and_verify(&circuit, partial_witness)
+}
+
+#[test]
+#[should_panic(expected = "Input to SmallTableGate<BitReversal3Table> is out of range")]
+fn test_small_table_lookup_out_of_range() {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let t = builder.add_virtual_target();
+    builder.small_table_lookup::<BitReversal3Table>(t);
+    let circuit = builder.build();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(t, F::from_canonical_usize(8));
+    circuit.generate_witness(partial_witness);
+}
+
+#[test]
+fn test_or_many_and_many() -> Result<()> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let combinations: &[&[bool]] = &[
+        &[false, false, false],
+        &[true, true, true],
+        &[true, false, false],
+        &[false, true, true],
+        &[false],
+        &[true],
+    ];
+
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let mut partial_witness = PartialWitness::new();
+    for &bits in combinations {
+        let ts = builder.add_virtual_targets(bits.len());
+        for (&t, &b) in ts.iter().zip(bits) {
+            partial_witness.set_target(t, F::from_canonical_bool(b));
+        }
+        let or = builder.or_many(&ts);
+        let and = builder.and_many(&ts);
+        let expected_or = builder.constant_wire(F::from_canonical_bool(bits.iter().any(|&b| b)));
+        let expected_and = builder.constant_wire(F::from_canonical_bool(bits.iter().all(|&b| b)));
+        builder.copy(or, expected_or);
+        builder.copy(and, expected_and);
+    }
+    let circuit = builder.build();
+    prove_and_verify(&circuit, partial_witness)
+}
+
+/// Generates a witness for `circuit` from `inputs`, which must satisfy it, then proves and
+/// verifies it.
+fn prove_and_verify(
+    circuit: &Circuit<Tweedledee>,
+    inputs: PartialWitness<<Tweedledee as Curve>::ScalarField>,
+) -> Result<()> {
+    let witness = circuit.generate_witness(inputs);
+    assert_eq!(circuit.check_witness::<Tweedledum>(&witness), Ok(()));
+    let proof = circuit.generate_proof::<Tweedledum>(&witness, &[], true)?;
+    verify_proof::<Tweedledee, Tweedledum>(&[], &proof, &[], &circuit.to_vk(), true)?;
+
+    Ok(())
+}
+
+/// Generates a witness for `circuit` from `inputs`, which must satisfy it, then sets every wire
+/// copied from `target` to `value`. This models a prover which respects the copy constraints but
+/// lies about `target`, so the resulting witness should be rejected with `UnsatisfiedConstraints`.
+fn assert_unsatisfiable(
+    circuit: &Circuit<Tweedledee>,
+    inputs: PartialWitness<<Tweedledee as Curve>::ScalarField>,
+    target: Target<<Tweedledee as Curve>::ScalarField>,
+    value: <Tweedledee as Curve>::ScalarField,
+) {
+    let mut partial_witness = circuit.generate_partial_witness(inputs);
+    let witness = Witness::from_partial(&partial_witness, circuit.degree());
+    assert_eq!(circuit.check_witness::<Tweedledum>(&witness), Ok(()));
+
+    let mut lies = PartialWitness::new();
+    for &sibling in circuit.routing_target_partitions.get_partition(target) {
+        lies.set_target(sibling, value);
+    }
+    partial_witness.extend(lies, MergePolicy::Overwrite).unwrap();
+    let witness = Witness::from_partial(&partial_witness, circuit.degree());
+    let unsatisfied = circuit.check_witness::<Tweedledum>(&witness).unwrap_err();
+    assert!(!unsatisfied.gates.is_empty());
+    let error = circuit
+        .generate_proof_checked::<Tweedledum>(&witness, &[], true)
+        .unwrap_err();
+    assert_eq!(error.downcast_ref::<UnsatisfiedConstraints>(), Some(&unsatisfied));
+}
+
+/// Returns a circuit which negates `b` with `not_checked` and `not`, and checks the results.
+fn not_circuit() -> (Circuit<Tweedledee>, Target<<Tweedledee as Curve>::ScalarField>) {
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let b = builder.add_virtual_target();
+    let not_b = builder.not_checked(b);
+    let not_not_b = builder.not(not_b);
+    builder.copy(not_not_b, b);
+    let sum = builder.add(b, not_b);
+    builder.assert_one(sum);
+    (builder.build(), b)
+}
+
+fn prove_not(value: u64) -> Result<()> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, b) = not_circuit();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(b, F::from_canonical_u64(value));
+    prove_and_verify(&circuit, partial_witness)
+}
+
+#[test]
+fn test_not_and_assert_one() -> Result<()> {
+    prove_not(0)?;
+    prove_not(1)
+}
+
+#[test]
+fn test_not_checked_non_binary() {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, b) = not_circuit();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(b, F::ZERO);
+    assert_unsatisfiable(&circuit, partial_witness, b, F::TWO);
+}
+
+#[test]
+fn test_proof_padded_circuit() -> Result<()> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let x = builder.add_virtual_target();
+    let mut acc = x;
+    // Along with the 3 blinding gates added by build(), this gives a circuit of exactly 100
+    // gates, which should be padded to 128.
+    while builder.num_gates() < 97 {
+        acc = builder.add(acc, x);
+    }
+    assert_eq!(builder.num_gates(), 97);
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(x, F::rand());
+    let circuit = builder.build();
+    assert_eq!(circuit.degree(), 128);
+    prove_and_verify(&circuit, partial_witness)
+}
+
+/// Returns a circuit asserting that `x` equals 12345.
+fn assert_equal_const_circuit(
+) -> (Circuit<Tweedledee>, Target<<Tweedledee as Curve>::ScalarField>) {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let x = builder.add_virtual_target();
+    builder.assert_equal_const(x, F::from_canonical_u64(12345));
+    (builder.build(), x)
+}
+
+#[test]
+fn test_assert_equal_const() -> Result<()> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, x) = assert_equal_const_circuit();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(x, F::from_canonical_u64(12345));
+    prove_and_verify(&circuit, partial_witness)
+}
+
+#[test]
+fn test_assert_equal_const_wrong_value() {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, x) = assert_equal_const_circuit();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(x, F::from_canonical_u64(12345));
+    assert_unsatisfiable(&circuit, partial_witness, x, F::from_canonical_u64(12346));
+}
+
+/// Returns a circuit asserting that `x <= 1000` if `upper`, or `x >= 1000` otherwise, using 10-bit
+/// range checks.
+fn assert_const_bound_circuit(
+    upper: bool,
+) -> (Circuit<Tweedledee>, Target<<Tweedledee as Curve>::ScalarField>) {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let x = builder.add_virtual_target();
+    if upper {
+        builder.assert_le_const(x, F::from_canonical_u64(1000), 10);
+    } else {
+        builder.assert_ge_const(x, F::from_canonical_u64(1000), 10);
+    }
+    (builder.build(), x)
+}
+
+/// Proves that `value` is within the bound of `assert_const_bound_circuit`.
+fn prove_assert_const_bound(value: u64, upper: bool) -> Result<()> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, x) = assert_const_bound_circuit(upper);
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(x, F::from_canonical_u64(value));
+    prove_and_verify(&circuit, partial_witness)
+}
+
+/// Asserts that a witness for `assert_const_bound_circuit` which sets `x` to `value` is rejected.
+fn assert_const_bound_unsatisfiable(value: u64, upper: bool) {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, x) = assert_const_bound_circuit(upper);
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(x, F::from_canonical_u64(1000));
+    assert_unsatisfiable(&circuit, partial_witness, x, F::from_canonical_u64(value));
+}
+
+#[test]
+fn test_assert_le_const() -> Result<()> {
+    prove_assert_const_bound(0, true)?;
+    prove_assert_const_bound(999, true)?;
+    prove_assert_const_bound(1000, true)
+}
+
+#[test]
+fn test_assert_le_const_past_bound() {
+    assert_const_bound_unsatisfiable(1001, true);
+}
+
+#[test]
+fn test_assert_ge_const() -> Result<()> {
+    prove_assert_const_bound(1000, false)?;
+    prove_assert_const_bound(1001, false)?;
+    prove_assert_const_bound(1023, false)
+}
+
+#[test]
+fn test_assert_ge_const_past_bound() {
+    assert_const_bound_unsatisfiable(999, false);
+}
+
+/// Returns a circuit asserting that `a * b == c` with `assert_mul`, along with `[a, b, c]`.
+fn assert_mul_circuit() -> (Circuit<Tweedledee>, Vec<Target<<Tweedledee as Curve>::ScalarField>>) {
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let targets = builder.add_virtual_targets(3);
+    builder.zero_wire();
+    let gates_before = builder.num_gates();
+    builder.assert_mul(targets[0], targets[1], targets[2]);
+    assert_eq!(builder.num_gates() - gates_before, 1);
+    (builder.build(), targets)
+}
+
+/// Sets the targets of `assert_mul_circuit` to `a`, `b` and `c`.
+fn assert_mul_inputs(
+    targets: &[Target<<Tweedledee as Curve>::ScalarField>],
+    a: u64,
+    b: u64,
+    c: u64,
+) -> PartialWitness<<Tweedledee as Curve>::ScalarField> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_targets(
+        targets,
+        &[
+            F::from_canonical_u64(a),
+            F::from_canonical_u64(b),
+            F::from_canonical_u64(c),
+        ],
+    );
+    partial_witness
+}
+
+fn prove_assert_mul(a: u64, b: u64, c: u64) -> Result<()> {
+    let (circuit, targets) = assert_mul_circuit();
+    prove_and_verify(&circuit, assert_mul_inputs(&targets, a, b, c))
+}
+
+#[test]
+fn test_assert_mul() -> Result<()> {
+    prove_assert_mul(6, 7, 42)?;
+    prove_assert_mul(0, 12345, 0)
+}
+
+#[test]
+fn test_assert_mul_wrong_product() {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, targets) = assert_mul_circuit();
+    let inputs = assert_mul_inputs(&targets, 6, 7, 42);
+    assert_unsatisfiable(&circuit, inputs, targets[2], F::from_canonical_u64(43));
+}
+
+/// Returns a circuit asserting that `x == 0` if `flag == 1`, along with `flag` and `x`.
+fn conditional_assert_zero_circuit() -> (
+    Circuit<Tweedledee>,
+    Target<<Tweedledee as Curve>::ScalarField>,
+    Target<<Tweedledee as Curve>::ScalarField>,
+) {
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let flag_target = builder.add_virtual_target();
+    let x = builder.add_virtual_target();
+    builder.assert_binary(flag_target);
+    builder.conditional_assert_zero(flag_target, x);
+    (builder.build(), flag_target, x)
+}
+
+fn prove_conditional_assert_zero(flag: bool, value: u64) -> Result<()> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, flag_target, x) = conditional_assert_zero_circuit();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(flag_target, F::from_canonical_bool(flag));
+    partial_witness.set_target(x, F::from_canonical_u64(value));
+    prove_and_verify(&circuit, partial_witness)
+}
+
+#[test]
+fn test_conditional_assert_zero() -> Result<()> {
+    prove_conditional_assert_zero(true, 0)?;
+    prove_conditional_assert_zero(false, 0)?;
+    // The constraint is vacuous when the flag is unset.
+    prove_conditional_assert_zero(false, 12345)
+}
+
+#[test]
+fn test_conditional_assert_zero_violated() {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, flag_target, x) = conditional_assert_zero_circuit();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(flag_target, F::ONE);
+    partial_witness.set_target(x, F::ZERO);
+    assert_unsatisfiable(&circuit, partial_witness, x, F::from_canonical_u64(12345));
+}
+
+/// Returns a circuit asserting that the given number of bits are a canonical decomposition.
+fn canonical_decomposition_circuit(
+    num_bits: usize,
+) -> (Circuit<Tweedledee>, Vec<Target<<Tweedledee as Curve>::ScalarField>>) {
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let bit_targets = builder.add_virtual_targets(num_bits);
+    for &bit in &bit_targets {
+        builder.assert_binary(bit);
+    }
+    builder.assert_boolean_decomposition_is_canonical(&bit_targets);
+    (builder.build(), bit_targets)
+}
+
+/// Sets the targets of `canonical_decomposition_circuit` to the given bits.
+fn canonical_decomposition_inputs(
+    bit_targets: &[Target<<Tweedledee as Curve>::ScalarField>],
+    bits: &[bool],
+) -> PartialWitness<<Tweedledee as Curve>::ScalarField> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let mut partial_witness = PartialWitness::new();
+    for (&target, &bit) in bit_targets.iter().zip(bits) {
+        partial_witness.set_target(target, F::from_canonical_bool(bit));
+    }
+    partial_witness
+}
+
+fn prove_canonical_decomposition(bits: Vec<bool>) -> Result<()> {
+    let (circuit, bit_targets) = canonical_decomposition_circuit(bits.len());
+    prove_and_verify(&circuit, canonical_decomposition_inputs(&bit_targets, &bits))
+}
+
+/// Returns the `F::BITS` little-endian bits of `x`.
+fn field_bits<F: Field>(x: BigUint) -> Vec<bool> {
+    (0..F::BITS).map(|i| ((&x >> i) & BigUint::one()).is_one()).collect()
+}
+
+#[test]
+fn test_canonical_decomposition() -> Result<()> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let modulus = F::modulus();
+    prove_canonical_decomposition(field_bits::<F>(BigUint::zero()))?;
+    prove_canonical_decomposition(field_bits::<F>(&modulus - 1u32))?;
+    prove_canonical_decomposition(field_bits::<F>(field_to_biguint(F::rand())))
+}
+
+#[test]
+fn test_non_canonical_decomposition() {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, bit_targets) = canonical_decomposition_circuit(F::BITS);
+    // Since p is odd, its bits are those of p - 1 with the lowest bit set.
+    let max_bits = field_bits::<F>(F::modulus() - 1u32);
+    let mut modulus_bits = max_bits.clone();
+    modulus_bits[0] = true;
+    assert_eq!(modulus_bits, field_bits::<F>(F::modulus()));
+
+    let inputs = canonical_decomposition_inputs(&bit_targets, &max_bits);
+    assert_unsatisfiable(&circuit, inputs, bit_targets[0], F::ONE);
+}
+
+#[test]
+fn test_running_sum() -> Result<()> {
+    type C = Tweedledee;
+    type SF = <C as Curve>::ScalarField;
+
+    let values: Vec<SF> = (0..50).map(|_| SF::rand()).collect();
+    let expected = values.iter().fold(SF::ZERO, |sum, &value| sum + value);
+
+    let mut builder = CircuitBuilder::<C>::new(128);
+    let terms = builder.add_virtual_targets(values.len());
+    let sum = builder.running_sum(&terms);
+    let expected_target = builder.constant_wire(expected);
+    builder.copy(sum, expected_target);
+    let circuit = builder.build();
+
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_targets(&terms, &values);
+    prove_and_verify(&circuit, partial_witness)
+}
+
+#[test]
+fn test_column_sum() -> Result<()> {
+    type C = Tweedledee;
+    type SF = <C as Curve>::ScalarField;
+
+    let (a, b) = (SF::rand(), SF::rand());
+    let mut builder = CircuitBuilder::<C>::new(128);
+    let terms = builder.add_virtual_targets(2);
+    builder.add_gate_no_constants(ColumnSumGate::new(0));
+    builder.copy(terms[0], Target::Wire(Wire { gate: 0, input: ColumnSumGate::<C>::WIRE_ACC }));
+    builder.copy(terms[1], Target::Wire(Wire { gate: 0, input: ColumnSumGate::<C>::WIRE_VALUE }));
+    let sum = builder.wire_below(0, ColumnSumGate::<C>::WIRE_ACC);
+    let expected_target = builder.constant_wire(a + b);
+    builder.copy(sum, expected_target);
+    let circuit = builder.build();
+
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_targets(&terms, &[a, b]);
+    prove_and_verify(&circuit, partial_witness)
+}
+
+/// Returns a circuit asserting that `x` is one of 3, 5 and 8.
+fn assert_in_set_circuit() -> (Circuit<Tweedledee>, Target<<Tweedledee as Curve>::ScalarField>) {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let allowed = [3, 5, 8].iter().map(|&a| F::from_canonical_u64(a)).collect::<Vec<_>>();
+
+    let mut builder = CircuitBuilder::<Tweedledee>::new(128);
+    let x = builder.add_virtual_target();
+    builder.assert_in_set(x, &allowed);
+    (builder.build(), x)
+}
+
+fn prove_assert_in_set(value: u64) -> Result<()> {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, x) = assert_in_set_circuit();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(x, F::from_canonical_u64(value));
+    prove_and_verify(&circuit, partial_witness)
+}
+
+#[test]
+fn test_assert_in_set_member() -> Result<()> {
+    prove_assert_in_set(3)?;
+    prove_assert_in_set(8)
+}
+
+#[test]
+fn test_assert_in_set_non_member() {
+    type F = <Tweedledee as Curve>::ScalarField;
+    let (circuit, x) = assert_in_set_circuit();
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(x, F::from_canonical_u64(3));
+    assert_unsatisfiable(&circuit, partial_witness, x, F::from_canonical_u64(4));
+}
+
 #[test]
 fn test_curve_add() -> Result<()> {
     type F = <Tweedledee as Curve>::ScalarField;
@@ -339,6 +1228,51 @@ fn test_curve_add() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_curve_msm_4_terms_gate_count() -> Result<()> {
+    type SF = <Tweedledee as Curve>::ScalarField;
+    type BF = <Tweedledee as Curve>::BaseField;
+    let n = 4;
+    let xs = (0..n).map(|_| SF::rand()).collect::<Vec<_>>();
+    let ps = (0..n)
+        .map(|_| blake_hash_base_field_to_curve::<Tweedledee>(BF::rand()))
+        .collect::<Vec<_>>();
+    let res = msm_parallel(&xs, &AffinePoint::batch_to_projective(&ps), 8);
+
+    let mut builder = CircuitBuilder::<Tweedledum>::new(128);
+    let txs = builder.add_virtual_targets(n);
+    let tps = builder.add_virtual_point_targets(n);
+    // Add the constant wires which subtracting the filler point needs, so that they aren't counted.
+    builder.zero_wire();
+    builder.one_wire();
+    builder.neg_one_wire();
+    let gates_before = builder.num_gates();
+    let (x, y) = builder.msm::<Tweedledee>(
+        &txs,
+        &tps.iter().map(|p| (p.x, p.y)).collect::<Vec<_>>(),
+    );
+    let msm_gates = builder.num_gates() - gates_before;
+    assert_eq!(msm_gates, (SF::BITS - 1) * (n + 1) + 7);
+    let tres_true = builder.constant_affine_point(res.to_affine());
+    builder.copy(x, tres_true.x);
+    builder.copy(y, tres_true.y);
+
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_targets(
+        &txs,
+        &xs.into_iter()
+            .map(|x| x.try_convert().unwrap())
+            .collect::<Vec<_>>(),
+    );
+    partial_witness.set_point_targets(&tps, &ps);
+    let circuit = builder.build();
+    let witness = circuit.generate_witness(partial_witness);
+    let proof = circuit.generate_proof::<Tweedledee>(&witness, &[], true)?;
+    verify_proof::<Tweedledum, Tweedledee>(&[], &proof, &[], &circuit.into(), true)?;
+
+    Ok(())
+}
+
 #[test]
 fn test_curve_msm() -> Result<()> {
     type SF = <Tweedledee as Curve>::ScalarField;
@@ -468,3 +1402,99 @@ fn test_curve_double_gate() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_tampered_witness_and_proof_rejected() -> Result<()> {
+    type C = Tweedledee;
+    type InnerC = Tweedledum;
+    type SF = <C as Curve>::ScalarField;
+
+    // All randomness is seeded, so that any failure can be reproduced.
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+    let mut builder = CircuitBuilder::<C>::new(128);
+    let hash_pi = builder.add_public_input();
+    let x = builder.add_virtual_target();
+    let limbs = builder.add_virtual_targets(Base4SumGate::<C>::NUM_ROUTED_LIMBS);
+    builder.assert_all_base_4(&limbs);
+    let mut inputs = vec![x];
+    inputs.extend(&limbs);
+    let hash = builder.rescue_hash_rounds(&inputs, 1, 2)[0];
+    builder.copy(hash, hash_pi);
+    let circuit = builder.build();
+    let vk = circuit.to_vk();
+
+    let mut partial_witness = PartialWitness::new();
+    partial_witness.set_target(x, SF::rand_from_rng(&mut rng));
+    for &limb in &limbs {
+        partial_witness.set_target(limb, SF::from_canonical_usize(rng.gen_range(0, 4)));
+    }
+    let partial_witness = circuit.generate_partial_witness(partial_witness);
+    let witness = Witness::from_partial(&partial_witness, circuit.degree());
+    let public_inputs = circuit.get_public_inputs(&witness);
+
+    let proof = circuit.generate_proof_with_rng::<InnerC, _>(&witness, &[], true, &mut rng)?;
+    verify_proof::<C, InnerC>(&public_inputs, &proof, &[], &vk, true)?;
+
+    // Changing any constrained wire must either be refused by the prover, or yield a proof which
+    // the verifier rejects. A wire is constrained if it's routed, or if changing it violates a gate
+    // constraint; other wires, such as those of padding gates, may hold arbitrary values.
+    let num_wires = witness.transpose().len();
+    let mut wires: Vec<Wire> = partial_witness.wires().map(|(wire, _value)| wire).collect();
+    wires.sort_by_key(|wire| (wire.gate, wire.input));
+    let mut num_constrained_wires = 0;
+    for wire in wires {
+        let tampered = Witness::new(
+            (0..circuit.degree())
+                .map(|gate| {
+                    (0..num_wires)
+                        .map(|input| {
+                            let value = witness.get_indices(gate, input);
+                            if (gate, input) == (wire.gate, wire.input) {
+                                value + SF::ONE
+                            } else {
+                                value
+                            }
+                        })
+                        .collect()
+                })
+                .collect(),
+        );
+        let routed = circuit
+            .routing_target_partitions
+            .try_get_partition(Target::Wire(wire))
+            .map_or(false, |partition| partition.len() > 1);
+        if !routed && circuit.check_witness::<InnerC>(&tampered).is_ok() {
+            continue;
+        }
+        num_constrained_wires += 1;
+
+        if let Ok(tampered_proof) =
+            circuit.generate_proof_with_rng::<InnerC, _>(&tampered, &[], true, &mut rng)
+        {
+            assert!(
+                verify_proof::<C, InnerC>(&public_inputs, &tampered_proof, &[], &vk, true).is_err(),
+                "A proof with a tampered value of {:?} was accepted",
+                wire
+            );
+        }
+    }
+
+    assert!(num_constrained_wires > 3 * circuit.num_gates_without_pis);
+
+    // Flipping any bit of the serialized proof must make it unreadable or invalid. Verifying is
+    // relatively slow, so we try a random sample of positions.
+    let mut proof_bytes = Vec::new();
+    proof.write(&mut proof_bytes)?;
+    for _ in 0..100 {
+        let i = rng.gen_range(0, proof_bytes.len());
+        let mut tampered_bytes = proof_bytes.clone();
+        tampered_bytes[i] ^= 1 << rng.gen_range(0, 8);
+        let accepted = verify_from_reader::<C, InnerC, _>(&tampered_bytes[..], &public_inputs, &vk)
+            .unwrap_or(false);
+        assert!(!accepted, "A proof with a tampered byte {} was accepted", i);
+    }
+
+    Ok(())
+}
+